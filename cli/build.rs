@@ -21,6 +21,35 @@ fn generate_completions(out_dir: &Path) {
     ] {
         generate_to(shell, &mut cmd, "hyde-ipc", out_dir).unwrap();
     }
+
+    add_runtime_completions(out_dir);
+}
+
+/// Hook `hyde-ipc _complete` into the fish and zsh scripts so window classes and workspaces are
+/// completed from the running compositor.
+fn add_runtime_completions(out_dir: &Path) {
+    let fish_path = out_dir.join("hyde-ipc.fish");
+    let fish = fs::read_to_string(&fish_path)
+        .unwrap()
+        .replace("-l class -r", "-l class -r -f -a \"(hyde-ipc _complete windows)\"");
+    let fish = fish
+        + "complete -c hyde-ipc -n \"__fish_hyde_ipc_using_subcommand dispatch; and \
+           __fish_seen_subcommand_from workspace move-to-workspace move-to-workspace-silent\" -f \
+           -a \"(hyde-ipc _complete workspaces)\"\n";
+    fs::write(&fish_path, fish).unwrap();
+
+    let zsh_path = out_dir.join("_hyde-ipc");
+    let zsh = fs::read_to_string(&zsh_path)
+        .unwrap()
+        .replace(
+            ":CLASS:_default",
+            ":CLASS:{compadd -- ${(f)\"$(hyde-ipc _complete windows 2>/dev/null)\"}}",
+        )
+        .replace(
+            ":workspace:_default",
+            ":workspace:{compadd -- ${(f)\"$(hyde-ipc _complete workspaces 2>/dev/null)\"}}",
+        );
+    fs::write(&zsh_path, zsh).unwrap();
 }
 
 fn main() {
@@ -0,0 +1,145 @@
+//! Per-application keyword sets ("app modes").
+//!
+//! Each `[apps.<class>]` table in the reaction config maps keywords to values. When a window of
+//! that class gains focus the keywords are applied, and the previous values are restored once
+//! focus moves to a different application.
+
+use hyprland::keyword::{Keyword, OptionValue};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+
+/// A keyword value as written in the config; TOML numbers and booleans are accepted as-is.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum KeywordValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for KeywordValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeywordValue::String(s) => write!(f, "{s}"),
+            KeywordValue::Int(i) => write!(f, "{i}"),
+            KeywordValue::Float(x) => write!(f, "{x}"),
+            KeywordValue::Bool(b) => write!(f, "{}", *b as u8),
+        }
+    }
+}
+
+impl KeywordValue {
+    /// Compare against a value reported by Hyprland. Numbers compare numerically, so `1` matches
+    /// `1.0` and booleans match `0`/`1`.
+    pub fn matches(&self, value: &OptionValue) -> bool {
+        let number = match value {
+            OptionValue::Int(i) => Some(*i as f64),
+            OptionValue::Float(x) => Some(*x),
+            OptionValue::String(s) => s.trim().parse::<f64>().ok(),
+        };
+        match (self, number) {
+            (KeywordValue::Int(i), Some(n)) => *i as f64 == n,
+            (KeywordValue::Float(x), Some(n)) => *x == n,
+            (KeywordValue::Bool(b), Some(n)) => (*b as u8 as f64) == n,
+            _ => self.to_string() == value.to_string(),
+        }
+    }
+}
+
+pub type AppMode = BTreeMap<String, KeywordValue>;
+
+/// The application currently holding focus and the keyword values it replaced.
+#[derive(Debug, Default)]
+struct ActiveMode {
+    class: Option<String>,
+    saved: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+pub struct AppModes {
+    apps: HashMap<String, AppMode>,
+    active: Mutex<ActiveMode>,
+}
+
+impl AppModes {
+    pub fn new(apps: HashMap<String, AppMode>) -> Self {
+        Self { apps, active: Mutex::new(ActiveMode::default()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.apps.len()
+    }
+
+    /// Switch modes for a focus change. `None` means no window is focused.
+    pub fn focus_changed(&self, class: Option<&str>) {
+        let mut active = self.active.lock().unwrap();
+        if active.class.as_deref() == class {
+            return;
+        }
+        restore(&mut active);
+        active.class = class.map(str::to_string);
+        if let Some(mode) = class.and_then(|c| self.apps.get(c)) {
+            active.saved = apply_keywords(mode);
+        }
+    }
+
+    /// The class whose mode is applied, if any.
+    pub fn active_mode(&self) -> Option<String> {
+        self.active
+            .lock()
+            .unwrap()
+            .class
+            .clone()
+            .filter(|class| self.apps.contains_key(class))
+    }
+
+    /// Hyprland resets every keyword on config reload, so the saved values are stale and the
+    /// focused application's mode has to be applied again.
+    pub fn config_reloaded(&self) {
+        let mut active = self.active.lock().unwrap();
+        if let Some(mode) = active
+            .class
+            .as_deref()
+            .and_then(|c| self.apps.get(c))
+        {
+            active.saved = apply_keywords(mode);
+        }
+    }
+}
+
+/// Apply a set of keywords, returning the values they replaced.
+pub fn apply_keywords(mode: &AppMode) -> Vec<(String, String)> {
+    let mut saved = Vec::with_capacity(mode.len());
+    for (keyword, value) in mode {
+        match Keyword::get(keyword) {
+            Ok(current) => saved.push((keyword.clone(), current.value.to_string())),
+            Err(e) => {
+                eprintln!("Error reading keyword {keyword}: {e}");
+                continue;
+            },
+        }
+        if let Err(e) = Keyword::set(keyword, value.to_string()) {
+            eprintln!("Error setting keyword {keyword}: {e}");
+        }
+    }
+    saved
+}
+
+fn restore(active: &mut ActiveMode) {
+    restore_keywords(active.saved.drain(..));
+}
+
+pub fn restore_keywords(saved: impl IntoIterator<Item = (String, String)>) {
+    for (keyword, value) in saved {
+        if let Err(e) = Keyword::set(&keyword, value) {
+            eprintln!("Error restoring keyword {keyword}: {e}");
+        }
+    }
+}
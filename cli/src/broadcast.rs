@@ -0,0 +1,84 @@
+//! `listen --broadcast`: hand parsed events to any number of local subscribers.
+//!
+//! Every tool that wants Hyprland's events normally opens its own `.socket2.sock` connection and
+//! parses the raw lines itself. With `--broadcast` one `listen` does that and serves the events on
+//! a unix socket of its own, one JSON object per line in the same shape as `listen --json`.
+//! Subscribers just connect and read, e.g. `socat - UNIX-CONNECT:<socket>`. A subscriber that
+//! stops reading is dropped rather than holding up the others.
+
+use crate::{daemon_lock, instance};
+use std::io::Write;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fs, thread};
+
+/// How long a write to one subscriber may block before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// `events-<instance>.sock` in the runtime dir, so each Hyprland instance gets its own socket.
+fn default_path() -> Result<PathBuf, String> {
+    let signature = instance::current_signature().unwrap_or_else(|| "default".to_string());
+    Ok(daemon_lock::runtime_dir()?.join(format!("events-{signature}.sock")))
+}
+
+#[derive(Clone)]
+pub struct Broadcaster {
+    subscribers: Arc<Mutex<Vec<UnixStream>>>,
+}
+
+impl Broadcaster {
+    /// Start broadcasting on `path`, or on the default socket if it is empty.
+    pub fn start(path: &str) -> Result<Self, String> {
+        let path = match path {
+            "" => default_path()?,
+            path => PathBuf::from(path),
+        };
+        let broadcaster = Self::bind(&path)?;
+        eprintln!("Broadcasting events on {}", path.display());
+        Ok(broadcaster)
+    }
+
+    /// Listen on `path` and accept subscribers in the background.
+    fn bind(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(format!("Events are already broadcast on {}", path.display()));
+            }
+            // Left behind by a broadcaster that didn't get to clean up.
+            fs::remove_file(path).map_err(|e| format!("Failed to remove stale socket: {e}"))?;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+
+        let subscribers = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&subscribers);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream.and_then(|stream| {
+                    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                    Ok(stream)
+                }) {
+                    Ok(stream) => accepted.lock().unwrap().push(stream),
+                    Err(e) => eprintln!("Error accepting subscriber: {e}"),
+                }
+            }
+        });
+        Ok(Self { subscribers })
+    }
+
+    /// Send one line to every subscriber, dropping the ones that are gone or too slow.
+    pub fn send(&self, line: &str) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain_mut(|stream| {
+            writeln!(stream, "{line}")
+                .and_then(|()| stream.flush())
+                .is_ok()
+        });
+    }
+}
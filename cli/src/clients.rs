@@ -0,0 +1,102 @@
+//! Helpers for looking up windows (clients) through Hyprland's data queries.
+
+use hyprland::data::{Client, Clients, Monitors};
+use hyprland::dispatch::{CycleDirection, CycleScope, Dispatch, DispatchType, WindowIdentifier};
+use hyprland::prelude::*;
+use hyprland::shared::Address;
+
+/// Find the first client matching `id`. Class and title identifiers match by substring, the same
+/// way reaction window filters do.
+pub fn find_client(id: &WindowIdentifier) -> Result<Client, String> {
+    Clients::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| client_matches(c, id))
+        .ok_or_else(|| format!("No window found matching {id}"))
+}
+
+pub fn find_client_by_address(address: &Address) -> Result<Client, String> {
+    find_client(&WindowIdentifier::Address(address.clone()))
+}
+
+pub fn client_matches(client: &Client, id: &WindowIdentifier) -> bool {
+    match id {
+        WindowIdentifier::Address(address) => &client.address == address,
+        WindowIdentifier::ClassRegularExpression(class) => pattern_matches(&client.class, class),
+        WindowIdentifier::Title(title) => pattern_matches(&client.title, title),
+        WindowIdentifier::ProcessId(pid) => client.pid == *pid as i32,
+    }
+}
+
+/// Match `text` against a class or title pattern. Patterns match by substring, and the `^` and `$`
+/// anchors (with an optional group around the rest, as in `^(firefox)$`) pin the match to the start
+/// or end of the text.
+pub fn pattern_matches(text: &str, pattern: &str) -> bool {
+    let (start, pattern) = match pattern.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let (end, pattern) = match pattern.strip_suffix('$') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let pattern = pattern
+        .strip_prefix('(')
+        .and_then(|p| p.strip_suffix(')'))
+        .filter(|_| start || end)
+        .unwrap_or(pattern);
+    match (start, end) {
+        (true, true) => text == pattern,
+        (true, false) => text.starts_with(pattern),
+        (false, true) => text.ends_with(pattern),
+        (false, false) => text.contains(pattern),
+    }
+}
+
+/// The center point of a client in layout coordinates.
+pub fn client_center(client: &Client) -> (i64, i64) {
+    (client.at.0 as i64 + client.size.0 as i64 / 2, client.at.1 as i64 + client.size.1 as i64 / 2)
+}
+
+/// Focus the next or previous window sharing the active window's class. Like `cyclenext`, only
+/// windows on the active workspace are considered unless the scope includes visible workspaces.
+pub fn cycle_same_class(direction: CycleDirection, scope: CycleScope) -> Result<(), String> {
+    let active = Client::get_active()
+        .map_err(|e| e.to_string())?
+        .ok_or("No active window")?;
+    let visible_workspaces: Vec<i32> = if scope.visible {
+        Monitors::get()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .flat_map(|m| [m.active_workspace.id, m.special_workspace.id])
+            .collect()
+    } else {
+        vec![active.workspace.id]
+    };
+    let candidates: Vec<Client> = Clients::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| {
+            c.class == active.class
+                && visible_workspaces.contains(&c.workspace.id)
+                && (c.floating || !scope.floating)
+                && (!c.floating || !scope.tiled)
+        })
+        .collect();
+
+    let Some(index) = candidates
+        .iter()
+        .position(|c| c.address == active.address)
+    else {
+        return Ok(());
+    };
+    let target = match direction {
+        CycleDirection::Next => &candidates[(index + 1) % candidates.len()],
+        CycleDirection::Previous => &candidates[(index + candidates.len() - 1) % candidates.len()],
+    };
+    if target.address == active.address {
+        return Ok(());
+    }
+    Dispatch::call(DispatchType::FocusWindow(WindowIdentifier::Address(target.address.clone())))
+        .map_err(|e| e.to_string())
+}
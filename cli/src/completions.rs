@@ -0,0 +1,76 @@
+//! Values for shell completion scripts, taken from the same tables the parsers use so the two
+//! never drift apart.
+
+use crate::flags::{CompletionValues, DispatchCommand, LiveCompletion};
+use crate::parsers;
+use crate::reaction_handler::EventType;
+use clap::CommandFactory;
+use hyprland::data::{Clients, Workspaces};
+use hyprland::prelude::*;
+use std::collections::BTreeSet;
+
+pub fn print_values(kind: CompletionValues) {
+    let values: Vec<String> = match kind {
+        CompletionValues::Dispatchers => DispatchCommand::command()
+            .get_subcommands()
+            .filter(|cmd| !cmd.is_hide_set())
+            .map(|cmd| cmd.get_name().to_string())
+            .collect(),
+        CompletionValues::Events => EventType::NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        CompletionValues::Subtypes => EventType::subtypes()
+            .into_iter()
+            .map(|(event, subtype)| format!("{event} {subtype}"))
+            .collect(),
+        CompletionValues::Corners => to_strings(parsers::corner_names()),
+        CompletionValues::Directions => to_strings(parsers::direction_names()),
+        CompletionValues::CycleDirections => to_strings(parsers::cycle_direction_names()),
+        CompletionValues::FullscreenModes => to_strings(parsers::fullscreen_type_names()),
+    };
+    for value in values {
+        println!("{value}");
+    }
+}
+
+/// Print values read from the running compositor. Errors print nothing, so a shell without
+/// Hyprland just gets no suggestions.
+pub fn print_live_values(kind: LiveCompletion) {
+    let values: BTreeSet<String> = match kind {
+        LiveCompletion::Windows => Clients::get()
+            .map(|clients| {
+                clients
+                    .into_iter()
+                    .map(|c| c.class)
+                    .filter(|class| !class.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        LiveCompletion::Workspaces => Workspaces::get()
+            .map(|workspaces| {
+                workspaces
+                    .into_iter()
+                    .filter(|w| w.id != 0 && !w.name.starts_with("special"))
+                    .map(|w| {
+                        if w.id > 0 && w.name == w.id.to_string() {
+                            w.name
+                        } else {
+                            format!("name:{}", w.name)
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    for value in values {
+        println!("{value}");
+    }
+}
+
+fn to_strings(names: Vec<&'static str>) -> Vec<String> {
+    names
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
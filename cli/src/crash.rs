@@ -0,0 +1,101 @@
+//! Crash reports for the reaction daemon.
+//!
+//! A panic anywhere in the daemon writes `crash-<unix time>.txt` to the state dir with the panic
+//! message, a backtrace, the reaction that was running on the panicking thread and the last events
+//! reactions were triggered by, then exits with [`CRASHED`]. A crash loop under the user service
+//! then leaves something to read instead of only restarts in the journal.
+
+use hyde_ipc_lib::service;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::{fs, process};
+
+/// Exit code after a panic, next to [`crate::failure::LISTENER_FAILED`].
+pub const CRASHED: i32 = 4;
+
+/// How many recent events a report lists.
+const RECENT_EVENTS: usize = 20;
+
+static RECENT: Mutex<VecDeque<(Instant, String)>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+    static ACTIVE_REACTION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Remember an event a reaction is about to handle.
+pub fn record_event(event: String) {
+    let Ok(mut recent) = RECENT.lock() else {
+        return;
+    };
+    if recent.len() == RECENT_EVENTS {
+        recent.pop_front();
+    }
+    recent.push_back((Instant::now(), event));
+}
+
+/// Marks a reaction as running on this thread until dropped.
+pub struct ActiveReaction(Option<String>);
+
+impl Drop for ActiveReaction {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ACTIVE_REACTION.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+pub fn running(reaction: &str) -> ActiveReaction {
+    let previous = ACTIVE_REACTION.with(|active| active.replace(Some(reaction.to_string())));
+    ActiveReaction(previous)
+}
+
+/// Write a report and exit with [`CRASHED`] on any panic, after the default hook has printed it.
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        match write_report(info) {
+            Ok(path) => eprintln!("Crash report written to {path}"),
+            Err(e) => eprintln!("Error writing crash report: {e}"),
+        }
+        process::exit(CRASHED);
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> Result<String, String> {
+    let now = Instant::now();
+    let mut report = String::new();
+    let _ = writeln!(report, "hyde-ipc {} crashed", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "\n{info}");
+    let reaction = ACTIVE_REACTION.with(|active| active.borrow().clone());
+    let _ = writeln!(report, "\nRunning reaction: {}", reaction.as_deref().unwrap_or("none"));
+
+    let _ = writeln!(report, "\nLast events, oldest first:");
+    // The panic may have happened while the list was locked.
+    let recent = RECENT
+        .try_lock()
+        .map(|recent| recent.clone())
+        .unwrap_or_default();
+    if recent.is_empty() {
+        let _ = writeln!(report, "  none");
+    }
+    for (at, event) in recent {
+        let _ = writeln!(report, "  {:.3}s ago: {event}", now.duration_since(at).as_secs_f64());
+    }
+
+    let _ = writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture());
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut path = service::get_state_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create state dir: {e}"))?;
+    path.push(format!("crash-{timestamp}.txt"));
+    fs::write(&path, report).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
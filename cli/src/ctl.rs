@@ -0,0 +1,82 @@
+//! `hyde-ipc ctl`: control a running reaction daemon.
+//!
+//! Pausing is a file in the state dir that every daemon checks whenever a reaction or placement
+//! policy is about to run, like the active scene. A timed pause holds its end time, so the daemon
+//! picks up again on its own once it has passed, with no timer to keep alive.
+
+use crate::flags::{CtlAction, CtlConfigAction, OutputFormat};
+use crate::parsers::ParsedDuration;
+use crate::react_config::ReactConfig;
+use crate::{state_file, status};
+use hyde_ipc_lib::service;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Pause {
+    /// Unix seconds when the pause ends, none to pause until `ctl resume`.
+    until: Option<u64>,
+}
+
+const STATE_FILE: &str = "pause.toml";
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The pause in effect, if any. A pause that has run out counts as none.
+fn active_pause() -> Option<Pause> {
+    let pause: Pause = state_file::load_cached(STATE_FILE)?;
+    pause
+        .until
+        .is_none_or(|until| until > now())
+        .then_some(pause)
+}
+
+/// Whether reactions and placement policies are paused right now.
+pub fn is_paused() -> bool {
+    active_pause().is_some()
+}
+
+fn pause(duration: Option<String>) -> Result<(), String> {
+    let until = duration
+        .as_deref()
+        .map(str::parse::<ParsedDuration>)
+        .transpose()?
+        .map(|duration| now() + duration.0.as_secs().max(1));
+    state_file::save(STATE_FILE, &Pause { until })?;
+    match duration {
+        Some(duration) => println!("Reactions paused for {duration}"),
+        None => println!("Reactions paused until `hyde-ipc ctl resume`"),
+    }
+    Ok(())
+}
+
+fn resume() -> Result<(), String> {
+    state_file::remove(STATE_FILE)?;
+    println!("Reactions resumed");
+    Ok(())
+}
+
+/// Print `config`, or the global config, the way the daemon runs it.
+fn show_config(config: Option<String>) -> Result<(), String> {
+    let path = match config {
+        Some(path) => PathBuf::from(path),
+        None => service::get_config_path().map_err(|e| e.to_string())?,
+    };
+    print!("{}", ReactConfig::effective_toml(path)?);
+    Ok(())
+}
+
+pub fn run(action: CtlAction, format: OutputFormat) -> Result<(), String> {
+    match action {
+        CtlAction::Pause { duration } => pause(duration),
+        CtlAction::Resume => resume(),
+        CtlAction::Status { waybar } => status::run(waybar, format),
+        CtlAction::Config { action: CtlConfigAction::Show { config } } => show_config(config),
+    }
+}
@@ -0,0 +1,54 @@
+//! `hyde-ipc cursor`: set the cursor theme and size, and keep them set.
+//!
+//! Hyprland resets the cursor to the one in its config whenever the config is reloaded. The theme
+//! chosen with `cursor set` is saved to the state dir, and the reaction daemon sets it again after
+//! every reload until `cursor reset` forgets it.
+
+use crate::flags::CursorAction;
+use crate::state_file;
+use hyprland::dispatch::{Dispatch, DispatchType};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub theme: String,
+    pub size: u16,
+}
+
+const STATE_FILE: &str = "cursor.toml";
+
+/// The cursor saved by `cursor set`, if any.
+pub fn saved() -> Option<Cursor> {
+    state_file::load(STATE_FILE)
+}
+
+fn apply(cursor: &Cursor) -> Result<(), String> {
+    Dispatch::call(DispatchType::SetCursor(&cursor.theme, cursor.size)).map_err(|e| e.to_string())
+}
+
+/// Set the saved cursor again, for after Hyprland reset it. Does nothing if none is saved.
+pub fn reassert() {
+    if let Some(cursor) = saved() {
+        if let Err(e) = apply(&cursor) {
+            eprintln!("Error restoring cursor {} {}: {e}", cursor.theme, cursor.size);
+        }
+    }
+}
+
+pub fn run(action: CursorAction) -> Result<(), String> {
+    match action {
+        CursorAction::Set { theme, size } => {
+            let cursor = Cursor { theme, size };
+            apply(&cursor)?;
+            state_file::save(STATE_FILE, &cursor)
+        },
+        CursorAction::Show => {
+            match saved() {
+                Some(cursor) => println!("{} {}", cursor.theme, cursor.size),
+                None => println!("No cursor saved, Hyprland's config is in charge"),
+            }
+            Ok(())
+        },
+        CursorAction::Reset => state_file::remove(STATE_FILE),
+    }
+}
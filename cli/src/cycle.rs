@@ -0,0 +1,213 @@
+//! `hyde-ipc cycle`: alt-tab through windows in most-recently-used order.
+//!
+//! `start` snapshots the windows in the order they were focused and selects the one focused
+//! before the current one. `next` and `prev` move through the snapshot without touching focus, so
+//! the order stays put while cycling, and `commit` focuses the selected window. Each step prints
+//! the selected window for bars to preview. The session is kept in the state dir between calls
+//! and is dropped once no step touched it for [`SESSION_TIMEOUT`].
+//!
+//! The order comes from the reaction daemon, which records every focus change, and falls back to
+//! Hyprland's own focus history when no daemon is keeping up with it.
+
+use crate::flags::{CycleAction, OutputFormat};
+use crate::ignore::IgnoreList;
+use crate::output::{Printer, Record};
+use crate::state_file;
+use hyprland::data::{Client, Clients};
+use hyprland::dispatch::{Dispatch, DispatchType, WindowIdentifier};
+use hyprland::event_listener::EventListener;
+use hyprland::prelude::*;
+use hyprland::shared::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a cycle is kept without a step, so a cycle abandoned without `commit` or `cancel`
+/// doesn't come back the next time.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Windows kept in the daemon's focus history.
+const HISTORY_LENGTH: usize = 100;
+
+const STATE_FILE: &str = "cycle.toml";
+
+const HISTORY_FILE: &str = "focus-history.toml";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Session {
+    /// Window addresses, most recently focused first.
+    windows: Vec<String>,
+    selected: usize,
+    /// Unix seconds of the last step.
+    #[serde(default)]
+    touched: u64,
+}
+
+/// Window addresses in the order the daemon saw them focused, most recent first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FocusHistory {
+    windows: Vec<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The cycle in progress, unless it went untouched for too long.
+fn load() -> Option<Session> {
+    state_file::load::<Session>(STATE_FILE)
+        .filter(|session| now().saturating_sub(session.touched) < SESSION_TIMEOUT.as_secs())
+}
+
+fn save(session: &Session) -> Result<(), String> {
+    state_file::save(STATE_FILE, &Session { touched: now(), ..session.clone() })
+}
+
+fn forget() -> Result<(), String> {
+    state_file::remove(STATE_FILE)
+}
+
+/// Keep the focus history `cycle` orders windows by. Run by the reaction daemon.
+pub fn track_focus(event_listener: &mut EventListener) {
+    let history = Arc::new(Mutex::new(FocusHistory::default()));
+    let update = |history: &Mutex<FocusHistory>, change: &dyn Fn(&mut Vec<String>)| {
+        let mut history = history.lock().unwrap();
+        change(&mut history.windows);
+        history.windows.truncate(HISTORY_LENGTH);
+        if let Err(e) = state_file::save(HISTORY_FILE, &*history) {
+            eprintln!("Error saving focus history: {e}");
+        }
+    };
+    let focus_history = Arc::clone(&history);
+    event_listener.add_active_window_changed_handler(move |data| {
+        if let Some(data) = data {
+            let address = data.address.to_string();
+            update(&focus_history, &|windows| {
+                windows.retain(|window| *window != address);
+                windows.insert(0, address.clone());
+            });
+        }
+    });
+    event_listener.add_window_closed_handler(move |address| {
+        let address = address.to_string();
+        update(&history, &|windows| windows.retain(|window| *window != address));
+    });
+}
+
+/// Mapped, not ignored windows, most recently focused first.
+fn windows() -> Result<Vec<Client>, String> {
+    let ignore = IgnoreList::from_global_config();
+    let mut clients: Vec<Client> = Clients::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|client| client.mapped && !ignore.is_client_ignored(client))
+        .collect();
+    clients.sort_by_key(|client| client.focus_history_id);
+    // The daemon's history only counts while it saw the latest focus change, which Hyprland's
+    // history has first. Windows it never saw focused keep Hyprland's order, after the others.
+    let history = state_file::load::<FocusHistory>(HISTORY_FILE)
+        .map(|history| history.windows)
+        .filter(|windows| {
+            clients
+                .first()
+                .is_some_and(|client| windows.first() == Some(&client.address.to_string()))
+        })
+        .unwrap_or_default();
+    clients.sort_by_key(|client| {
+        history
+            .iter()
+            .position(|address| *address == client.address.to_string())
+            .unwrap_or(usize::MAX)
+    });
+    Ok(clients)
+}
+
+fn start() -> Result<Session, String> {
+    let windows: Vec<String> = windows()?
+        .iter()
+        .map(|client| client.address.to_string())
+        .collect();
+    if windows.is_empty() {
+        return Err("No windows to cycle through".to_string());
+    }
+    let selected = usize::from(windows.len() > 1);
+    Ok(Session { windows, selected, touched: 0 })
+}
+
+/// Move the selection by `step`, dropping windows that were closed since the session started.
+fn step(mut session: Session, step: isize) -> Result<(Session, Client), String> {
+    let open = windows()?;
+    let selected = session
+        .windows
+        .get(session.selected)
+        .cloned();
+    session.windows.retain(|address| {
+        open.iter()
+            .any(|client| client.address.to_string() == *address)
+    });
+    if session.windows.is_empty() {
+        return Err("Every window of the cycle was closed".to_string());
+    }
+    // If the selected window was closed, the next one took its place.
+    let index = match selected.and_then(|address| {
+        session
+            .windows
+            .iter()
+            .position(|window| *window == address)
+    }) {
+        Some(index) => index as isize,
+        None if step > 0 => session.selected as isize - 1,
+        None => session.selected as isize,
+    };
+    let len = session.windows.len() as isize;
+    session.selected = (index + step).rem_euclid(len) as usize;
+    let address = &session.windows[session.selected];
+    let client = open
+        .into_iter()
+        .find(|client| client.address.to_string() == *address)
+        .ok_or("The selected window was closed")?;
+    Ok((session, client))
+}
+
+fn print(session: &Session, client: &Client, format: OutputFormat) -> Result<(), String> {
+    let value = json!({
+        "address": client.address.to_string(),
+        "class": client.class,
+        "title": client.title,
+        "workspace": client.workspace.name,
+        "index": session.selected,
+        "count": session.windows.len(),
+    });
+    let plain = format!("{}: {}", client.class, client.title);
+    Printer::new(format).print(&Record::new(&value, plain)?)
+}
+
+pub fn run(action: CycleAction, format: OutputFormat) -> Result<(), String> {
+    let (session, client) = match action {
+        CycleAction::Start => step(start()?, 0)?,
+        CycleAction::Next => match load() {
+            Some(session) => step(session, 1)?,
+            None => step(start()?, 0)?,
+        },
+        CycleAction::Prev => match load() {
+            Some(session) => step(session, -1)?,
+            None => step(Session { selected: 0, ..start()? }, -1)?,
+        },
+        CycleAction::Commit => {
+            let session = load().ok_or("No cycle in progress, run `hyde-ipc cycle start` first")?;
+            let (_, client) = step(session, 0)?;
+            forget()?;
+            return Dispatch::call(DispatchType::FocusWindow(WindowIdentifier::Address(
+                Address::new(&client.address),
+            )))
+            .map_err(|e| e.to_string());
+        },
+        CycleAction::Cancel => return forget(),
+    };
+    save(&session)?;
+    print(&session, &client, format)
+}
@@ -0,0 +1,124 @@
+//! One reaction daemon per config and Hyprland instance.
+//!
+//! `react -c` takes an exclusive `flock` on a file in the runtime dir named after the config path
+//! and the instance it connects to. A second daemon for the same pair refuses to start instead of
+//! firing every reaction twice. The kernel drops the lock when the daemon exits, however it exits,
+//! so a crash never leaves a stale lock behind.
+//!
+//! With `--replace` the running daemon, whose pid is in the lock file, is sent `SIGTERM` and the
+//! new one starts as soon as the old one has let go of the lock.
+
+use crate::instance;
+use hyde_ipc_lib::service;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, process, thread};
+
+/// How long `--replace` waits for the running daemon to exit.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Held for as long as the daemon runs.
+#[derive(Debug)]
+pub struct DaemonLock {
+    _file: File,
+}
+
+/// Where hyde-ipc keeps its locks and sockets: the runtime dir, or the state dir without one.
+pub fn runtime_dir() -> Result<PathBuf, String> {
+    match env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) => Ok(PathBuf::from(dir).join("hyde-ipc")),
+        Err(_) => service::get_state_dir().map_err(|e| e.to_string()),
+    }
+}
+
+/// FNV-1a, so the file name stays the same across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// The lock file for the daemon running `config` against the current instance.
+pub fn lock_path(config: &Path) -> Result<PathBuf, String> {
+    let config = fs::canonicalize(config).unwrap_or_else(|_| config.to_path_buf());
+    let key =
+        format!("{}\n{}", config.display(), instance::current_signature().unwrap_or_default());
+    Ok(runtime_dir()?.join(format!("react-{:016x}.lock", fnv1a(key.as_bytes()))))
+}
+
+/// The pid written by the daemon holding the lock at `path`.
+pub fn holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn try_lock(file: &File) -> bool {
+    // SAFETY: flock only reads the descriptor, which `file` keeps open.
+    unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) == 0 }
+}
+
+/// Ask the daemon holding the lock on `file` to exit, and wait until it has.
+fn replace(file: &File, path: &Path) -> Result<(), String> {
+    let pid = holder(path).ok_or("The running daemon didn't record its pid, stop it by hand")?;
+    let pid = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid pid {pid}"))?;
+    // SAFETY: kill takes no pointers.
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        return Err(format!(
+            "Failed to stop the running daemon (pid {pid}): {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    println!("Stopping the running daemon (pid {pid})");
+    let start = Instant::now();
+    while !try_lock(file) {
+        if start.elapsed() > REPLACE_TIMEOUT {
+            return Err(format!(
+                "The running daemon (pid {pid}) didn't exit within {}s",
+                REPLACE_TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+/// Take the lock for `config`. If another daemon already holds it, fail, or with `takeover` stop
+/// that daemon first.
+pub fn acquire(config: &Path, takeover: bool) -> Result<DaemonLock, String> {
+    let path = lock_path(config)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    if !try_lock(&file) {
+        if takeover {
+            replace(&file, &path)?;
+        } else {
+            let running = holder(&path)
+                .map(|pid| format!(" (pid {pid})"))
+                .unwrap_or_default();
+            return Err(format!(
+                "A daemon for {} is already running{running}, use --replace to take over",
+                config.display()
+            ));
+        }
+    }
+    file.set_len(0)
+        .and_then(|()| writeln!(file, "{}", process::id()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(DaemonLock { _file: file })
+}
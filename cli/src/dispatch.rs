@@ -1,24 +1,116 @@
-use crate::flags::{Dispatch as DispatchCmd, ResizeCmd};
+use crate::clients::{self, client_center, find_client};
+use crate::flags::{
+    CycleScopeArgs, Dispatch as DispatchCmd, DispatchCommand, ExecRules, ResizeCmd,
+};
+use crate::ignore::IgnoreList;
 use crate::parsers::{
-    ParsedCorner, ParsedCycleDirection, ParsedDirection, ParsedFullscreenType,
+    ParsedCorner, ParsedCycleDirection, ParsedDirection, ParsedFocusMasterParam,
+    ParsedFullscreenType, ParsedGroupDirection, ParsedLayoutCmd, ParsedSwapWithMasterParam,
     ParsedWindowIdentifier, ParsedWindowMove, ParsedWorkspaceIdentifier,
 };
-use hyprland::dispatch::{Dispatch, DispatchType, Position};
+use crate::send_signal::{self, Target};
+use crate::template::Vars;
+use crate::{float_memory, foreach, notify, state_file, swap};
+use clap::Parser;
+use hyprland::ctl::switch_xkb_layout;
+use hyprland::data::{CursorPosition, Workspace};
+use hyprland::dispatch::{
+    CycleScope, Dispatch, DispatchType, Position, SplitRatioValue, WindowIdentifier,
+    WorkspaceIdentifierWithSpecial,
+};
+use hyprland::prelude::*;
 use std::convert::TryFrom;
+use std::io::BufRead;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{process, thread};
+
+impl From<CycleScopeArgs> for CycleScope {
+    fn from(scope: CycleScopeArgs) -> Self {
+        Self { tiled: scope.tiled, floating: scope.floating, visible: scope.visible }
+    }
+}
+
+impl ExecRules {
+    /// The `[rule; rule]` prefix Hyprland's `exec` expects, if there are any rules.
+    pub fn to_prefix(&self) -> Option<String> {
+        let rules: Vec<&str> = self
+            .rules
+            .iter()
+            .map(String::as_str)
+            .chain(self.float.then_some("float"))
+            .chain(self.fullscreen.then_some("fullscreen"))
+            .collect();
+        (!rules.is_empty()).then(|| format!("[{}]", rules.join("; ")))
+    }
+
+    /// The `--env` variables split into key and value.
+    pub fn env_vars(&self) -> Result<Vec<(&str, &str)>, String> {
+        self.env
+            .iter()
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value))
+                    if !key.is_empty()
+                        && key
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    Ok((key, value))
+                },
+                _ => Err(format!("Invalid environment variable {entry:?}, expected KEY=VALUE")),
+            })
+            .collect()
+    }
+
+    /// `env 'KEY=VALUE' ...` to put in front of the command Hyprland runs, if there are variables.
+    pub fn env_prefix(&self) -> Result<Option<String>, String> {
+        let vars = self.env_vars()?;
+        Ok((!vars.is_empty()).then(|| {
+            let assignments: Vec<String> = vars
+                .iter()
+                .map(|(key, value)| format!("'{key}={}'", value.replace('\'', r"'\''")))
+                .collect();
+            format!("env {}", assignments.join(" "))
+        }))
+    }
+}
 
 impl TryFrom<DispatchCmd> for DispatchType<'static> {
     type Error = String;
 
     fn try_from(command: DispatchCmd) -> Result<Self, Self::Error> {
         match command {
-            DispatchCmd::Exec { command } => {
-                let command = command.join(" ");
+            DispatchCmd::Exec { rules, .. } if rules.no_detach => {
+                Err("exec --no-detach is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::Exec { rules, command } => {
+                let command = [
+                    rules.to_prefix(),
+                    rules.env_prefix()?,
+                    Some(command.join(" ")),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
                 let command_static = Box::leak(command.into_boxed_str());
                 Ok(DispatchType::Exec(command_static))
             },
+            DispatchCmd::Raw { dispatcher } => {
+                let dispatcher = dispatcher.join(" ");
+                let (name, args) = dispatcher
+                    .trim()
+                    .split_once(' ')
+                    .unwrap_or((dispatcher.trim(), ""));
+                if name.is_empty() {
+                    return Err("Missing dispatcher name".to_string());
+                }
+                let name_static = Box::leak(name.to_string().into_boxed_str());
+                let args_static = Box::leak(args.trim().to_string().into_boxed_str());
+                Ok(DispatchType::Custom(name_static, args_static))
+            },
             DispatchCmd::KillActiveWindow => Ok(DispatchType::KillActiveWindow),
-            DispatchCmd::ToggleFloating { window } => {
+            DispatchCmd::ToggleFloating { window, .. } => {
                 let window_id = if let Some(window_str) = window.to_identifier_string() {
                     Some(ParsedWindowIdentifier::from_str(&window_str)?.0)
                 } else {
@@ -27,17 +119,39 @@ impl TryFrom<DispatchCmd> for DispatchType<'static> {
                 Ok(DispatchType::ToggleFloating(window_id))
             },
             DispatchCmd::ToggleSplit => Ok(DispatchType::ToggleSplit),
+            DispatchCmd::SplitRatio { value, exact } => {
+                let value = if exact {
+                    SplitRatioValue::Exact(value)
+                } else {
+                    SplitRatioValue::Relative(value)
+                };
+                Ok(DispatchType::ChangeSplitRatio(value))
+            },
             DispatchCmd::ToggleOpaque => Ok(DispatchType::ToggleOpaque),
+            DispatchCmd::ToggleSwallow => Ok(DispatchType::ToggleSwallow),
             DispatchCmd::MoveCursorToCorner { corner } => {
                 let corner = ParsedCorner::from_str(&corner)?.0;
                 Ok(DispatchType::MoveCursorToCorner(corner))
             },
-            DispatchCmd::MoveCursor { x, y } => Ok(DispatchType::MoveCursor(x, y)),
+            DispatchCmd::MoveCursor { x, y, relative, to_window } => {
+                if let Some(window) = to_window {
+                    let window_id = ParsedWindowIdentifier::from_str(&window)?.0;
+                    let (x, y) = client_center(&find_client(&window_id)?);
+                    return Ok(DispatchType::MoveCursor(x, y));
+                }
+                let (x, y) = (x.ok_or("Missing x coordinate")?, y.ok_or("Missing y coordinate")?);
+                if relative {
+                    let pos = CursorPosition::get().map_err(|e| e.to_string())?;
+                    Ok(DispatchType::MoveCursor(pos.x + x, pos.y + y))
+                } else {
+                    Ok(DispatchType::MoveCursor(x, y))
+                }
+            },
             DispatchCmd::ToggleFullscreen { mode } => {
                 let mode = ParsedFullscreenType::from_str(&mode)?.0;
                 Ok(DispatchType::ToggleFullscreen(mode))
             },
-            DispatchCmd::MoveToWorkspace { workspace } => {
+            DispatchCmd::MoveToWorkspace { workspace, .. } => {
                 let workspace_id = ParsedWorkspaceIdentifier::from_str(&workspace)?.0;
                 Ok(DispatchType::MoveToWorkspace(workspace_id, None))
             },
@@ -54,9 +168,34 @@ impl TryFrom<DispatchCmd> for DispatchType<'static> {
                 let workspace_id = ParsedWorkspaceIdentifier::from_str(&workspace)?.0;
                 Ok(DispatchType::Workspace(workspace_id))
             },
-            DispatchCmd::CycleWindow { direction } => {
+            DispatchCmd::WorkspaceToggle { workspace } => {
+                let workspace_id = ParsedWorkspaceIdentifier::from_str(&workspace)?.0;
+                let active = Workspace::get_active().map_err(|e| e.to_string())?;
+                let focused = match workspace_id {
+                    WorkspaceIdentifierWithSpecial::Id(id) => id == active.id,
+                    WorkspaceIdentifierWithSpecial::Name(name) => name == active.name,
+                    _ => false,
+                };
+                if focused {
+                    Ok(DispatchType::Workspace(WorkspaceIdentifierWithSpecial::Previous))
+                } else {
+                    Ok(DispatchType::Workspace(workspace_id))
+                }
+            },
+            DispatchCmd::CycleWindow { direction, scope } => {
+                let dir = ParsedCycleDirection::from_str(&direction)?.0;
+                if scope.same_class {
+                    return Err("--same-class has no Hyprland dispatcher equivalent".to_string());
+                }
+                if scope == CycleScopeArgs::default() {
+                    Ok(DispatchType::CycleWindow(dir))
+                } else {
+                    Ok(DispatchType::CycleWindowIn(dir, scope.into()))
+                }
+            },
+            DispatchCmd::SwapNext { direction } => {
                 let dir = ParsedCycleDirection::from_str(&direction)?.0;
-                Ok(DispatchType::CycleWindow(dir))
+                Ok(DispatchType::SwapNext(dir))
             },
             DispatchCmd::MoveFocus { direction } => {
                 let dir = ParsedDirection::from_str(&direction)?.0;
@@ -66,14 +205,21 @@ impl TryFrom<DispatchCmd> for DispatchType<'static> {
                 let dir = ParsedDirection::from_str(&direction)?.0;
                 Ok(DispatchType::SwapWindow(dir))
             },
-            DispatchCmd::FocusWindow { window } => {
+            DispatchCmd::FocusWindow { window, .. } => {
                 let window_id = window
                     .to_identifier_string()
                     .ok_or("Missing window identifier")?;
                 let window_id = ParsedWindowIdentifier::from_str(&window_id)?.0;
                 Ok(DispatchType::FocusWindow(window_id))
             },
-            DispatchCmd::MoveWindow { target } => {
+            DispatchCmd::Pass { window } => {
+                let window_id = window
+                    .to_identifier_string()
+                    .ok_or("Missing window identifier")?;
+                let window_id = ParsedWindowIdentifier::from_str(&window_id)?.0;
+                Ok(DispatchType::Pass(window_id))
+            },
+            DispatchCmd::MoveWindow { target, .. } => {
                 let window_move = ParsedWindowMove::from_str(&target)?.0;
                 Ok(DispatchType::MoveWindow(window_move))
             },
@@ -86,6 +232,27 @@ impl TryFrom<DispatchCmd> for DispatchType<'static> {
             DispatchCmd::FocusCurrentOrLast => Ok(DispatchType::FocusCurrentOrLast),
             DispatchCmd::ForceRendererReload => Ok(DispatchType::ForceRendererReload),
             DispatchCmd::Exit => Ok(DispatchType::Exit),
+            DispatchCmd::SwapWithMaster { param } => {
+                let param = ParsedSwapWithMasterParam::from_str(&param)?.0;
+                Ok(DispatchType::SwapWithMaster(param))
+            },
+            DispatchCmd::FocusMaster { param } => {
+                let param = ParsedFocusMasterParam::from_str(&param)?.0;
+                Ok(DispatchType::FocusMaster(param))
+            },
+            DispatchCmd::AddMaster => Ok(DispatchType::AddMaster),
+            DispatchCmd::RemoveMaster => Ok(DispatchType::RemoveMaster),
+            DispatchCmd::OrientationLeft => Ok(DispatchType::OrientationLeft),
+            DispatchCmd::OrientationRight => Ok(DispatchType::OrientationRight),
+            DispatchCmd::OrientationTop => Ok(DispatchType::OrientationTop),
+            DispatchCmd::OrientationBottom => Ok(DispatchType::OrientationBottom),
+            DispatchCmd::OrientationCenter => Ok(DispatchType::OrientationCenter),
+            DispatchCmd::OrientationNext => Ok(DispatchType::OrientationNext),
+            DispatchCmd::OrientationPrev => Ok(DispatchType::OrientationPrev),
+            DispatchCmd::MoveGroupWindow { direction } => {
+                let dir = ParsedGroupDirection::from_str(&direction)?.0;
+                Ok(DispatchType::MoveGroupWindow(dir))
+            },
             DispatchCmd::ResizeActive { params } => {
                 let position = match params {
                     ResizeCmd::Delta { dx, dy } => Position::Delta(dx, dy),
@@ -104,29 +271,529 @@ impl TryFrom<DispatchCmd> for DispatchType<'static> {
                 let win_id = ParsedWindowIdentifier::from_str(&win_id_str)?.0;
                 Ok(DispatchType::ResizeWindowPixel(position, win_id))
             },
+            DispatchCmd::Submap { name } => {
+                let name_static = Box::leak(name.into_boxed_str());
+                Ok(DispatchType::Submap(name_static))
+            },
+            DispatchCmd::SetProp { window, property, value, lock } => {
+                let window_id = window
+                    .to_identifier_string()
+                    .map(|id| ParsedWindowIdentifier::from_str(&id).map(|id| id.0))
+                    .transpose()?;
+                let property_static = Box::leak(property.to_lowercase().into_boxed_str());
+                let value_static = Box::leak(value.into_boxed_str());
+                Ok(DispatchType::SetProp(window_id, property_static, value_static, lock))
+            },
+            DispatchCmd::SetCursor { theme, size } => {
+                let theme_static = Box::leak(theme.into_boxed_str());
+                Ok(DispatchType::SetCursor(theme_static, size))
+            },
+            DispatchCmd::TagWindow { tag, window } => {
+                let name = tag.trim_start_matches(['+', '-']);
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(format!("Invalid tag: {tag}"));
+                }
+                let window_id = window
+                    .to_identifier_string()
+                    .map(|id| ParsedWindowIdentifier::from_str(&id).map(|id| id.0))
+                    .transpose()?;
+                let tag_static = Box::leak(tag.into_boxed_str());
+                Ok(DispatchType::TagWindow(tag_static, window_id))
+            },
+            DispatchCmd::Signal { .. } => {
+                Err("signal is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::WriteFile { .. } => {
+                Err("write-file is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::ForEach { .. } => {
+                Err("for-each is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::Sleep { .. } => {
+                Err("sleep is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::Notify { .. } => {
+                Err("notify is carried out by hyde-ipc, not Hyprland".to_string())
+            },
+            DispatchCmd::SwitchLayout { .. } => {
+                Err("switch-layout is a hyprctl command, not a dispatcher".to_string())
+            },
         }
     }
 }
 
-pub fn handle_dispatch(command: DispatchCmd, is_async: bool) {
-    match DispatchType::try_from(command) {
-        Ok(dispatch_type) => {
-            if is_async {
-                let rt = tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()
-                    .unwrap();
-                rt.block_on(async {
-                    if let Err(e) = Dispatch::call_async(dispatch_type).await {
-                        eprintln!("Error: {e}");
-                    }
-                });
-            } else if let Err(e) = Dispatch::call(dispatch_type) {
-                eprintln!("Error: {e}");
-            }
+/// Check a dispatcher's arguments without querying Hyprland, so mistakes in a config surface when
+/// it is loaded rather than when the event fires.
+pub fn validate(command: &DispatchCmd) -> Result<(), String> {
+    match command {
+        DispatchCmd::MoveCursor { to_window: Some(window), .. } => {
+            ParsedWindowIdentifier::from_str(window).map(drop)
+        },
+        DispatchCmd::MoveCursor { x, y, .. } => {
+            x.ok_or("Missing x coordinate")?;
+            y.ok_or("Missing y coordinate")?;
+            Ok(())
         },
-        Err(e) => {
-            eprintln!("Error: {e}");
+        DispatchCmd::CycleWindow { direction, .. } => {
+            ParsedCycleDirection::from_str(direction).map(drop)
         },
+        DispatchCmd::SwitchLayout { layout, .. } => ParsedLayoutCmd::from_str(layout).map(drop),
+        DispatchCmd::WorkspaceToggle { workspace } => {
+            ParsedWorkspaceIdentifier::from_str(workspace).map(drop)
+        },
+        DispatchCmd::Exec { rules, .. } if rules.no_detach => rules.env_vars().map(drop),
+        DispatchCmd::WriteFile { .. } | DispatchCmd::Sleep { .. } => Ok(()),
+        DispatchCmd::Notify { urgency, .. } => notify::validate_urgency(urgency.as_deref()),
+        DispatchCmd::ForEach { conditions, .. } => foreach::validate_conditions(conditions),
+        DispatchCmd::Signal { signal, .. } => send_signal::parse(signal).map(drop),
+        other => DispatchType::try_from(other.clone()).map(drop),
+    }
+}
+
+/// How `dispatch` handles each command, from its flags.
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    pub is_async: bool,
+    pub explain: bool,
+    pub dry_run: bool,
+    pub retry: Option<Retry>,
+}
+
+impl From<&DispatchCommand> for RunOptions {
+    fn from(command: &DispatchCommand) -> Self {
+        let retry = command
+            .retry_until_success
+            .then(|| Retry {
+                attempts: command.retry_attempts.max(1),
+                timeout: Duration::from_secs(command.retry_timeout),
+            });
+        Self {
+            is_async: command.r#async,
+            explain: command.explain,
+            dry_run: command.dry_run,
+            retry,
+        }
+    }
+}
+
+/// How hard `--retry-until-success` tries before giving up.
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    pub attempts: u32,
+    pub timeout: Duration,
+}
+
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Call `run` until it succeeds, waiting twice as long after each failure. Without `retry` it is
+/// called once. Gives up with the last error once the attempts or the total timeout run out.
+pub fn retrying(
+    retry: Option<Retry>,
+    mut run: impl FnMut() -> Result<(), String>,
+) -> Result<(), String> {
+    let Some(retry) = retry else {
+        return run();
+    };
+    let start = Instant::now();
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let Err(e) = run() else {
+            return Ok(());
+        };
+        if attempt >= retry.attempts || start.elapsed() + delay > retry.timeout {
+            return Err(format!("{e} (gave up after {attempt} attempts)"));
+        }
+        eprintln!("Attempt {attempt} failed: {e}, retrying in {}ms", delay.as_millis());
+        thread::sleep(delay);
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+        attempt += 1;
+    }
+}
+
+/// Whether `command` can only be turned into a socket command against the running compositor.
+fn needs_live_state(command: &DispatchCmd) -> bool {
+    matches!(
+        command,
+        DispatchCmd::ToggleFloating { remember: true, .. }
+            | DispatchCmd::FocusWindow { strict: true, .. }
+            | DispatchCmd::MoveToWorkspace { swap_if_occupied: true, .. }
+            | DispatchCmd::MoveWindow { swap_if_occupied: true, .. }
+            | DispatchCmd::CycleWindow { scope: CycleScopeArgs { same_class: true, .. }, .. }
+            | DispatchCmd::ForEach { .. }
+            | DispatchCmd::MoveCursor { to_window: Some(_), .. }
+            | DispatchCmd::MoveCursor { relative: true, .. }
+            | DispatchCmd::WorkspaceToggle { .. }
+    )
+}
+
+/// Print the socket command `command` would send, without sending it or talking to Hyprland.
+/// Commands that depend on the compositor's current state, and those hyde-ipc carries out itself,
+/// are only validated.
+pub fn dry_run(command: &DispatchCmd) -> Result<(), String> {
+    if let DispatchCmd::SwitchLayout { device, layout } = command {
+        println!("switchxkblayout {device} {}", ParsedLayoutCmd::from_str(layout)?.0);
+        return Ok(());
+    }
+    if let DispatchCmd::Sleep { ms } = command {
+        println!("# sleep {ms}ms");
+        return Ok(());
+    }
+    if needs_live_state(command) {
+        validate(command)?;
+        println!("# {command:?} depends on the compositor's state and is resolved when dispatched");
+        return Ok(());
+    }
+    if is_local(command) {
+        validate(command)?;
+        println!("# {command:?} is carried out by hyde-ipc and sends nothing to Hyprland");
+        return Ok(());
+    }
+    let dispatch_type = DispatchType::try_from(command.clone())?;
+    println!("{}", Dispatch::command_string(dispatch_type).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// Print what a command turns into before it is sent, for comparing against `hyprctl dispatch`.
+pub fn explain(command: &DispatchCmd) -> Result<(), String> {
+    if let DispatchCmd::SwitchLayout { device, layout } = command {
+        println!(
+            "Socket command: switchxkblayout {device} {}",
+            ParsedLayoutCmd::from_str(layout)?.0
+        );
+        return Ok(());
+    }
+    if is_local(command) {
+        validate(command)?;
+        println!("Carried out by hyde-ipc: {command:?}");
+        return Ok(());
+    }
+    let dispatch_type = DispatchType::try_from(command.clone())?;
+    println!("DispatchType: {dispatch_type:?}");
+    let socket_command = Dispatch::command_string(dispatch_type).map_err(|e| e.to_string())?;
+    println!("Socket command: {socket_command}");
+    Ok(())
+}
+
+/// Split a command line at the `--then` (or lone `;`) separators after the `dispatch` subcommand.
+/// Returns the command line to parse as usual and the arguments of each dispatcher that follows.
+pub fn split_batch(args: Vec<String>) -> (Vec<String>, Vec<Vec<String>>) {
+    let Some(dispatch_at) = args
+        .iter()
+        .position(|arg| arg == "dispatch")
+    else {
+        return (args, Vec::new());
+    };
+    let mut parts = args[dispatch_at + 1..].split(|arg| arg == "--then" || arg == ";");
+    let first = args[..=dispatch_at]
+        .iter()
+        .chain(parts.next().unwrap_or_default())
+        .cloned()
+        .collect();
+    (first, parts.map(<[String]>::to_vec).collect())
+}
+
+/// Run one dispatcher per line of `input`, written as on the command line (`workspace 3`,
+/// `exec "notify-send 'hi there'"`). Blank lines and `#` comments are skipped. A failing line is
+/// reported with its number and the rest still run. Returns the number of failed lines.
+pub fn run_lines(input: impl BufRead, options: RunOptions) -> usize {
+    let mut failed = 0;
+    for (index, line) in input.lines().enumerate() {
+        let result = line
+            .map_err(|e| e.to_string())
+            .and_then(|line| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    return Ok(());
+                }
+                let command = parse_line(line)?;
+                if options.dry_run {
+                    return dry_run(&command);
+                }
+                if options.explain {
+                    explain(&command)?;
+                }
+                if options.retry.is_some() {
+                    validate(&command)?;
+                }
+                retrying(options.retry, || run_dispatch(command.clone(), options.is_async))
+            });
+        if let Err(e) = result {
+            eprintln!("Line {}: {e}", index + 1);
+            failed += 1;
+        }
+    }
+    failed
+}
+
+fn parse_line(line: &str) -> Result<DispatchCmd, String> {
+    parse_words(split_words(line)?)
+}
+
+/// Parse a dispatcher from its command line words, e.g. `["workspace", "3"]`.
+pub fn parse_words(args: Vec<String>) -> Result<DispatchCmd, String> {
+    let parsed =
+        DispatchCommand::try_parse_from(std::iter::once("dispatch".to_string()).chain(args))
+            .map_err(|e| {
+                let rendered = e.render().to_string();
+                rendered
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches("error: ")
+                    .to_string()
+            })?;
+    parsed
+        .command
+        .ok_or_else(|| "expected a dispatcher".to_string())
+}
+
+/// Split a line into words the way a shell would for simple quoting: single quotes are literal,
+/// double quotes allow `\"`, and a backslash outside quotes escapes the next character.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            },
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            },
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            },
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    word.get_or_insert_with(String::new)
+                        .push(c);
+                }
+            },
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word
+                .get_or_insert_with(String::new)
+                .push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Whether hyde-ipc carries out `command` itself instead of sending it to Hyprland as is.
+fn is_local(command: &DispatchCmd) -> bool {
+    matches!(
+        command,
+        DispatchCmd::ToggleFloating { remember: true, .. }
+            | DispatchCmd::FocusWindow { strict: true, .. }
+            | DispatchCmd::MoveToWorkspace { swap_if_occupied: true, .. }
+            | DispatchCmd::MoveWindow { swap_if_occupied: true, .. }
+            | DispatchCmd::CycleWindow { scope: CycleScopeArgs { same_class: true, .. }, .. }
+            | DispatchCmd::SwitchLayout { .. }
+            | DispatchCmd::WriteFile { .. }
+            | DispatchCmd::Signal { .. }
+            | DispatchCmd::ForEach { .. }
+            | DispatchCmd::Sleep { .. }
+            | DispatchCmd::Notify { .. }
+            | DispatchCmd::Exec { rules: ExecRules { no_detach: true, .. }, .. }
+    )
+}
+
+enum BatchStep {
+    /// Run on its own through [`run_dispatch`].
+    Single(DispatchCmd),
+    /// Sent together with the neighbouring `Socket` steps.
+    Socket(DispatchType<'static>),
+}
+
+/// Whether `command` asked to exit with a failing status when it can't be carried out.
+pub fn is_strict(command: &DispatchCmd) -> bool {
+    matches!(command, DispatchCmd::FocusWindow { strict: true, .. })
+}
+
+/// Run several dispatchers in order. Consecutive dispatchers that go straight to Hyprland share one
+/// socket request. Nothing runs if any of them fails to parse, and the batch stops at the first
+/// dispatcher Hyprland rejects.
+pub fn handle_batch(commands: Vec<DispatchCmd>, is_async: bool) {
+    if let Err(e) = run_batch(commands, is_async) {
+        eprintln!("Error: {e}");
+    }
+}
+
+/// [`handle_batch`], returning the first error instead of printing it.
+pub fn run_batch(commands: Vec<DispatchCmd>, is_async: bool) -> Result<(), String> {
+    let steps: Result<Vec<BatchStep>, String> = commands
+        .into_iter()
+        .map(|command| {
+            if is_local(&command) {
+                return Ok(BatchStep::Single(command));
+            }
+            let dispatch_type = DispatchType::try_from(command.clone())?;
+            // Hyprland splits batches on `;`, so commands containing one are sent on their own.
+            let socket_command =
+                Dispatch::command_string(dispatch_type.clone()).map_err(|e| e.to_string())?;
+            if socket_command.contains(';') {
+                Ok(BatchStep::Single(command))
+            } else {
+                Ok(BatchStep::Socket(dispatch_type))
+            }
+        })
+        .collect();
+
+    let mut pending = Vec::new();
+    for step in steps? {
+        match step {
+            BatchStep::Socket(dispatch_type) => pending.push(dispatch_type),
+            BatchStep::Single(command) => {
+                call_batch(std::mem::take(&mut pending), is_async).map_err(|e| e.to_string())?;
+                run_dispatch(command, is_async)?;
+            },
+        }
+    }
+    call_batch(pending, is_async).map_err(|e| e.to_string())
+}
+
+fn call_batch(dispatch_types: Vec<DispatchType<'static>>, is_async: bool) -> hyprland::Result<()> {
+    if is_async {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(Dispatch::call_batch_async(dispatch_types))
+    } else {
+        Dispatch::call_batch(dispatch_types)
+    }
+}
+
+pub fn handle_dispatch(command: DispatchCmd, is_async: bool) {
+    if let Err(e) = run_dispatch(command, is_async) {
+        eprintln!("Error: {e}");
+    }
+}
+
+/// Run `command` through the shell like Hyprland's `exec` would, but wait for it to exit.
+fn exec_attached(rules: &ExecRules, command: &[String]) -> Result<(), String> {
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(command.join(" "))
+        .envs(rules.env_vars()?)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {e}", command.join(" ")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {status}", command.join(" ")))
+    }
+}
+
+/// Run a single dispatcher, returning its error instead of printing it.
+pub fn run_dispatch(command: DispatchCmd, is_async: bool) -> Result<(), String> {
+    if let DispatchCmd::ToggleFloating { window, remember: true } = &command {
+        return window
+            .to_identifier_string()
+            .map(|id| ParsedWindowIdentifier::from_str(&id).map(|id| id.0))
+            .transpose()
+            .and_then(float_memory::toggle_floating);
+    }
+
+    if let DispatchCmd::FocusWindow { window, strict: true } = &command {
+        let id = window
+            .to_identifier_string()
+            .ok_or("Missing window identifier")?;
+        let client = clients::find_client(&ParsedWindowIdentifier::from_str(&id)?.0)?;
+        return Dispatch::call(DispatchType::FocusWindow(WindowIdentifier::Address(
+            client.address,
+        )))
+        .map_err(|e| e.to_string());
+    }
+
+    if let DispatchCmd::MoveToWorkspace { workspace, swap_if_occupied: true } = &command {
+        return call_batch(swap::to_workspace(workspace)?, is_async).map_err(|e| e.to_string());
+    }
+
+    if let DispatchCmd::MoveWindow { target, swap_if_occupied: true } = &command {
+        return call_batch(swap::move_window(target)?, is_async).map_err(|e| e.to_string());
+    }
+
+    if let DispatchCmd::CycleWindow { direction, scope } = &command {
+        if scope.same_class {
+            return ParsedCycleDirection::from_str(direction)
+                .and_then(|dir| clients::cycle_same_class(dir.0, (*scope).into()));
+        }
+    }
+
+    if let DispatchCmd::WriteFile { path, content } = &command {
+        return state_file::write(path, content);
+    }
+
+    if let DispatchCmd::Sleep { ms } = &command {
+        thread::sleep(Duration::from_millis(*ms));
+        return Ok(());
+    }
+
+    if let DispatchCmd::Notify { summary, body, urgency } = &command {
+        return notify::send(summary, body.as_deref(), urgency.as_deref());
+    }
+
+    if let DispatchCmd::ForEach { conditions, dispatcher } = &command {
+        return foreach::run(
+            conditions,
+            dispatcher,
+            &Vars::new(),
+            &IgnoreList::from_global_config(),
+        );
+    }
+
+    if let DispatchCmd::Exec { rules, command } = &command {
+        if rules.no_detach {
+            return exec_attached(rules, command);
+        }
+    }
+
+    if let DispatchCmd::Signal { process, pidfile, signal } = &command {
+        let target = match (process, pidfile) {
+            (Some(name), _) => Target::Process(name.clone()),
+            (None, Some(path)) => Target::Pidfile(path.clone()),
+            (None, None) => return Err("Missing --process or --pidfile".to_string()),
+        };
+        return send_signal::send(&target, signal);
+    }
+
+    if let DispatchCmd::SwitchLayout { device, layout } = &command {
+        let layout = ParsedLayoutCmd::from_str(layout)?.0;
+        return switch_xkb_layout::call(device, layout).map_err(|e| e.to_string());
+    }
+
+    let dispatch_type = DispatchType::try_from(command)?;
+    if is_async {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(Dispatch::call_async(dispatch_type))
+            .map_err(|e| e.to_string())
+    } else {
+        Dispatch::call(dispatch_type).map_err(|e| e.to_string())
     }
 }
@@ -0,0 +1,130 @@
+//! `dispatch --list-dispatchers`, generated from the clap definition of [`Dispatch`] so the list
+//! can't drift from what is implemented.
+//!
+//! [`Dispatch`]: crate::flags::Dispatch
+
+use crate::flags::{DispatchCommand, OutputFormat};
+use crate::output::{Printer, Record};
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct DispatcherInfo {
+    name: String,
+    usage: String,
+    description: String,
+    arguments: Vec<ArgumentInfo>,
+}
+
+#[derive(Debug, Serialize)]
+struct ArgumentInfo {
+    name: String,
+    /// `--long` for options and flags, absent for positional arguments.
+    flag: Option<String>,
+    required: bool,
+    takes_value: bool,
+    help: Option<String>,
+}
+
+pub fn print(format: OutputFormat) -> Result<(), String> {
+    let dispatchers: Vec<DispatcherInfo> = DispatchCommand::command()
+        .get_subcommands()
+        .filter(|cmd| !cmd.is_hide_set())
+        .map(describe)
+        .collect();
+
+    let mut lines = vec!["Available dispatchers:".to_string()];
+    for dispatcher in &dispatchers {
+        lines.push(format!("  {}", dispatcher.usage));
+        if !dispatcher.description.is_empty() {
+            lines.push(format!("      {}", dispatcher.description));
+        }
+    }
+    Printer::new(format).print(&Record::new(&dispatchers, lines.join("\n"))?)
+}
+
+fn describe(cmd: &Command) -> DispatcherInfo {
+    let arguments: Vec<&Arg> = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .collect();
+    let mut usage = vec![cmd.get_name().to_string()];
+    usage.extend(
+        arguments
+            .iter()
+            .filter(|arg| !arg.is_positional())
+            .map(|arg| arg_usage(arg)),
+    );
+    usage.extend(
+        arguments
+            .iter()
+            .filter(|arg| arg.is_positional())
+            .map(|arg| arg_usage(arg)),
+    );
+    let nested: Vec<&str> = cmd
+        .get_subcommands()
+        .map(Command::get_name)
+        .collect();
+    if !nested.is_empty() {
+        usage.push(format!("<{}>", nested.join("|")));
+    }
+
+    DispatcherInfo {
+        name: cmd.get_name().to_string(),
+        usage: usage.join(" "),
+        description: cmd
+            .get_about()
+            .map(|about| about.to_string())
+            .unwrap_or_default(),
+        arguments: arguments
+            .iter()
+            .map(|arg| ArgumentInfo {
+                name: arg.get_id().to_string(),
+                flag: arg
+                    .get_long()
+                    .map(|long| format!("--{long}")),
+                required: arg.is_required_set(),
+                takes_value: takes_value(arg),
+                help: arg
+                    .get_help()
+                    .map(|help| help.to_string()),
+            })
+            .collect(),
+    }
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    !matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse | ArgAction::Count)
+}
+
+/// `<value>`, `[value]...`, `--flag` or `[--option <value>]`, the way clap's usage line writes
+/// them.
+fn arg_usage(arg: &Arg) -> String {
+    let value = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(|| arg.get_id().to_string(), |name| name.to_string())
+        .to_lowercase()
+        .replace('_', "-");
+    let repeated = matches!(arg.get_action(), ArgAction::Append)
+        || arg
+            .get_num_args()
+            .is_some_and(|range| range.max_values() > 1);
+    let mut usage = match arg.get_long() {
+        Some(long) if takes_value(arg) => format!("--{long} <{value}>"),
+        Some(long) => format!("--{long}"),
+        None => format!("<{value}>"),
+    };
+    if repeated {
+        usage.push_str("...");
+    }
+    if arg.is_required_set() {
+        usage
+    } else if arg.is_positional() {
+        usage
+            .replace('<', "[")
+            .replace('>', "]")
+    } else {
+        format!("[{usage}]")
+    }
+}
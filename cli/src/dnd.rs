@@ -0,0 +1,100 @@
+//! `hyde-ipc dnd`: a do-not-disturb switch for reactions and the notification daemon.
+//!
+//! The switch is a file in the state dir, so reactions can check it with `when = { dnd = false }`
+//! and it survives daemon restarts. With `--notifier` the notification daemon is paused too.
+
+use crate::flags::{DndAction, Notifier, OutputFormat};
+use crate::output::{Printer, Record};
+use crate::{expose, state_file};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Dnd {
+    enabled: bool,
+}
+
+const STATE_FILE: &str = "dnd.toml";
+
+/// Whether do-not-disturb is on. Off unless it was turned on.
+pub fn is_enabled() -> bool {
+    state_file::load_cached::<Dnd>(STATE_FILE).is_some_and(|dnd| dnd.enabled)
+}
+
+fn save(dnd: Dnd) -> Result<(), String> {
+    state_file::save(STATE_FILE, &dnd)
+}
+
+fn is_running(process: &str) -> bool {
+    Command::new("pgrep")
+        .args(["-x", process])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// The first notification daemon that is running.
+fn detect() -> Option<Notifier> {
+    [
+        (Notifier::Dunst, "dunst"),
+        (Notifier::Mako, "mako"),
+        (Notifier::Swaync, "swaync"),
+    ]
+    .into_iter()
+    .find(|(_, process)| is_running(process))
+    .map(|(notifier, _)| notifier)
+}
+
+fn pause_notifications(notifier: Notifier, enabled: bool) -> Result<(), String> {
+    let notifier = match notifier {
+        Notifier::Auto => detect().ok_or("No running notification daemon found")?,
+        notifier => notifier,
+    };
+    let (program, args): (&str, Vec<&str>) = match notifier {
+        Notifier::Dunst => ("dunstctl", vec![
+            "set-paused",
+            if enabled { "true" } else { "false" },
+        ]),
+        Notifier::Mako => ("makoctl", vec![
+            "mode",
+            if enabled { "-a" } else { "-r" },
+            "do-not-disturb",
+        ]),
+        Notifier::Swaync => ("swaync-client", vec![if enabled { "--dnd-on" } else { "--dnd-off" }]),
+        Notifier::Auto => unreachable!(),
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+pub fn run(
+    action: DndAction,
+    notifier: Option<Notifier>,
+    format: OutputFormat,
+) -> Result<(), String> {
+    let enabled = match action {
+        DndAction::On => true,
+        DndAction::Off => false,
+        DndAction::Toggle => !is_enabled(),
+        DndAction::Status => {
+            let enabled = is_enabled();
+            let plain = if enabled { "on" } else { "off" }.to_string();
+            return Printer::new(format)
+                .print(&Record::new(&json!({ "enabled": enabled }), plain)?);
+        },
+    };
+    save(Dnd { enabled })?;
+    expose::dnd(&expose::from_global_config(), enabled);
+    if let Some(notifier) = notifier {
+        pause_notifications(notifier, enabled)?;
+    }
+    println!("Do not disturb {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! DPMS (monitor power) tracking.
+//!
+//! Hyprland doesn't emit an event when a monitor is powered off or on, so the state is polled
+//! from the monitor list and changes are reported by comparing against the last poll.
+
+use hyprland::data::Monitors;
+use hyprland::prelude::*;
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// DPMS state of every monitor, keyed by monitor name.
+pub fn states() -> hyprland::Result<BTreeMap<String, bool>> {
+    Ok(Monitors::get()?
+        .into_iter()
+        .map(|m| (m.name, m.dpms_status))
+        .collect())
+}
+
+/// Poll forever, calling `on_change` with the monitor name and its new state. Monitors that are
+/// already present on the first poll are reported only if `report_initial` is set.
+pub fn watch(report_initial: bool, mut on_change: impl FnMut(&str, bool)) -> ! {
+    let mut last: Option<BTreeMap<String, bool>> = None;
+    loop {
+        match states() {
+            Ok(current) => {
+                for (name, &on) in &current {
+                    let previous = last
+                        .as_ref()
+                        .map(|l| l.get(name).copied());
+                    let changed = match previous {
+                        None => report_initial,
+                        Some(previous) => previous != Some(on),
+                    };
+                    if changed {
+                        on_change(name, on);
+                    }
+                }
+                last = Some(current);
+            },
+            Err(e) => eprintln!("Error reading monitor DPMS state: {e}"),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,68 @@
+//! Built-in variables about the machine and session, for placeholders and `when.env`.
+//!
+//! `{host}`, `{monitor_count}`, `{session_uptime}` (seconds since Hyprland started) and
+//! `{hypr_version}` let one config behave differently per machine or setup without wrapping
+//! commands in shell guards. Each is worked out only when a placeholder or `when.env` uses it. The
+//! host and Hyprland version are read once, the others every time a reaction fires.
+
+use crate::template::Vars;
+use crate::{instance, query_cache};
+use hyprland::data::{Monitors, Version};
+use hyprland::prelude::*;
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const NAMES: [&str; 4] = [
+    "host",
+    "monitor_count",
+    "session_uptime",
+    "hypr_version",
+];
+
+fn host() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(|| {
+        fs::read_to_string("/proc/sys/kernel/hostname")
+            .or_else(|_| fs::read_to_string("/etc/hostname"))
+            .map(|host| host.trim().to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Empty if Hyprland can't be asked, which is tried again next time.
+fn hypr_version() -> String {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    if let Some(version) = VERSION.get() {
+        return version.clone();
+    }
+    Version::get()
+        .map(|version| {
+            VERSION
+                .get_or_init(|| version.version.unwrap_or(version.tag))
+                .clone()
+        })
+        .unwrap_or_default()
+}
+
+fn monitor_count() -> usize {
+    query_cache::get("monitors", || Monitors::get().map_or(0, |monitors| monitors.iter().count()))
+}
+
+fn session_uptime() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    instance::started_at().map_or(0, |started| now.saturating_sub(started))
+}
+
+/// Every variable, each read the first time it is looked up.
+pub fn vars() -> Vars {
+    let mut vars = Vars::new();
+    vars.set_lazy("host", || host().to_string())
+        .set_lazy("monitor_count", || monitor_count().to_string())
+        .set_lazy("session_uptime", || session_uptime().to_string())
+        .set_lazy("hypr_version", hypr_version);
+    vars
+}
@@ -0,0 +1,173 @@
+//! `listen --db`: store events in an sqlite database for later analysis.
+//!
+//! Each event is a row with its time (unix milliseconds), type, subtype, the window class when the
+//! payload has one, and the full payload as JSON. Type, class and time are indexed, so questions
+//! like "when did this window last open" stay cheap on a long history, which is what `hyde-ipc
+//! history` asks. Needs the `sqlite` feature.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Which stored events `history` asks for. Times are unix milliseconds.
+#[derive(Debug, Default)]
+#[cfg_attr(
+    not(feature = "sqlite"),
+    allow(dead_code)
+)]
+pub struct Query {
+    pub event: Option<String>,
+    /// Types containing this, ignoring case, as `listen --filter` picks them.
+    pub event_containing: Option<String>,
+    pub subtype: Option<String>,
+    pub class: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoredEvent {
+    pub time: i64,
+    #[serde(rename = "type")]
+    pub event: String,
+    pub subtype: String,
+    pub class: Option<String>,
+    pub data: Value,
+}
+
+#[cfg(feature = "sqlite")]
+pub struct EventDb {
+    conn: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl EventDb {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let conn = rusqlite::Connection::open(path)
+            .map_err(|e| format!("Failed to open event database {path}: {e}"))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS events (
+                 id INTEGER PRIMARY KEY,
+                 time INTEGER NOT NULL,
+                 type TEXT NOT NULL,
+                 subtype TEXT NOT NULL,
+                 class TEXT,
+                 data TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS events_type ON events (type, subtype);
+             CREATE INDEX IF NOT EXISTS events_class ON events (class);
+             CREATE INDEX IF NOT EXISTS events_time ON events (time);",
+        )
+        .map_err(|e| format!("Failed to set up event database {path}: {e}"))?;
+        Ok(Self { conn })
+    }
+
+    pub fn insert(&self, event: &str, change: &str, data: &Value) -> Result<(), String> {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as i64);
+        let class = data
+            .get("class")
+            .and_then(Value::as_str);
+        self.conn
+            .execute(
+                "INSERT INTO events (time, type, subtype, class, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![time, event, change, class, data.to_string()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store event: {e}"))
+    }
+
+    /// Matching events, newest first.
+    pub fn query(&self, query: &Query) -> Result<Vec<StoredEvent>, String> {
+        self.query_matching(query, |_| true)
+    }
+
+    /// Events matching `query` that `keep` accepts as well, newest first. Only the ones it keeps
+    /// count toward the limit.
+    pub fn query_matching(
+        &self,
+        query: &Query,
+        keep: impl Fn(&StoredEvent) -> bool,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let mut conditions = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let mut condition = |sql: &str, param: Box<dyn rusqlite::ToSql>| {
+            params.push(param);
+            conditions.push(sql.replace('?', &format!("?{}", params.len())));
+        };
+        if let Some(event) = &query.event {
+            condition("type = ?", Box::new(event.clone()));
+        }
+        if let Some(event) = &query.event_containing {
+            condition("instr(lower(type), ?) > 0", Box::new(event.to_lowercase()));
+        }
+        if let Some(subtype) = &query.subtype {
+            condition("subtype = ?", Box::new(subtype.clone()));
+        }
+        if let Some(class) = &query.class {
+            condition("class = ?", Box::new(class.clone()));
+        }
+        if let Some(since) = query.since {
+            condition("time >= ?", Box::new(since));
+        }
+        if let Some(until) = query.until {
+            condition("time < ?", Box::new(until));
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT time, type, subtype, class, data FROM events {filter} ORDER BY time DESC"
+        );
+        let mut statement = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to query events: {e}"))?;
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                let data: String = row.get(4)?;
+                Ok(StoredEvent {
+                    time: row.get(0)?,
+                    event: row.get(1)?,
+                    subtype: row.get(2)?,
+                    class: row.get(3)?,
+                    data: serde_json::from_str(&data).unwrap_or(Value::Null),
+                })
+            })
+            .map_err(|e| format!("Failed to query events: {e}"))?;
+        // Rows are read as they are needed, so this stops at the limit.
+        rows.filter(|row| row.as_ref().is_err() || row.as_ref().is_ok_and(&keep))
+            .take(query.limit)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read events: {e}"))
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub struct EventDb;
+
+#[cfg(not(feature = "sqlite"))]
+impl EventDb {
+    pub fn open(_path: &str) -> Result<Self, String> {
+        Err("The event database needs hyde-ipc built with the sqlite feature".to_string())
+    }
+
+    pub fn insert(&self, _event: &str, _change: &str, _data: &Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn query(&self, _query: &Query) -> Result<Vec<StoredEvent>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn query_matching(
+        &self,
+        _query: &Query,
+        _keep: impl Fn(&StoredEvent) -> bool,
+    ) -> Result<Vec<StoredEvent>, String> {
+        Ok(Vec::new())
+    }
+}
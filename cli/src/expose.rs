@@ -0,0 +1,78 @@
+//! Mirror hyde-ipc state into Hyprland's environment, from the config's top-level `expose` list.
+//!
+//! Hyprland has no runtime variables that `hyprland.conf` can read, but `keyword env` changes the
+//! environment of everything it launches afterwards. With `expose = ["dnd"]` an `exec` bind sees
+//! `HYDE_IPC_DND=1` while do-not-disturb is on and can branch on it in its shell command.
+
+use crate::{dnd, scene};
+use hyde_ipc_lib::service;
+use hyprland::keyword::Keyword;
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    /// `HYDE_IPC_DND`: `1` while do-not-disturb is on, `0` otherwise.
+    Dnd,
+    /// `HYDE_IPC_SCENE`: the name of the applied scene, empty if none is.
+    Scene,
+    /// `HYDE_IPC_APP_MODE`: the class whose app mode is active, empty if none is.
+    AppMode,
+}
+
+impl State {
+    fn var(self) -> &'static str {
+        match self {
+            Self::Dnd => "HYDE_IPC_DND",
+            Self::Scene => "HYDE_IPC_SCENE",
+            Self::AppMode => "HYDE_IPC_APP_MODE",
+        }
+    }
+}
+
+/// Just the `expose` list of a config file.
+#[derive(Deserialize)]
+struct ExposeConfig {
+    #[serde(default)]
+    expose: Vec<State>,
+}
+
+/// The expose list of the global config, or nothing if there is none.
+pub fn from_global_config() -> Vec<State> {
+    service::get_config_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str::<ExposeConfig>(&content).ok())
+        .map(|config| config.expose)
+        .unwrap_or_default()
+}
+
+/// Set the variable for `state` if it is in `exposed`.
+pub fn set(exposed: &[State], state: State, value: &str) {
+    if !exposed.contains(&state) {
+        return;
+    }
+    let var = state.var();
+    if let Err(e) = Keyword::set("env", format!("{var},{value}")) {
+        eprintln!("Error exposing {var}: {e}");
+    }
+}
+
+pub fn dnd(exposed: &[State], enabled: bool) {
+    set(exposed, State::Dnd, if enabled { "1" } else { "0" });
+}
+
+/// Set every exposed variable from the saved state. The app mode lives in the reaction daemon, so
+/// it is passed in.
+pub fn publish(exposed: &[State], app_mode: Option<&str>) {
+    dnd(exposed, dnd::is_enabled());
+    set(
+        exposed,
+        State::Scene,
+        scene::active_name()
+            .as_deref()
+            .unwrap_or_default(),
+    );
+    set(exposed, State::AppMode, app_mode.unwrap_or_default());
+}
@@ -0,0 +1,37 @@
+//! Reporting when the connection to Hyprland's event socket fails.
+//!
+//! `listen` and `react` exit with [`LISTENER_FAILED`] instead of the generic `1`, so scripts and
+//! service managers can tell a lost compositor apart from bad arguments or config. When running as
+//! the user service the failure is also sent as a desktop notification, since otherwise it only
+//! shows up as a restart loop in the journal.
+
+use std::env;
+use std::fmt::Display;
+use std::process::{self, Command, Stdio};
+
+/// Exit code for a failed or lost event listener.
+pub const LISTENER_FAILED: i32 = 3;
+
+pub fn listener_failed(error: impl Display) -> ! {
+    let message = format!("Event listener failed: {error}");
+    eprintln!("Error: {message}");
+    if is_service() {
+        // Best effort: there may be no notification daemon either.
+        let _ = Command::new("notify-send")
+            .args([
+                "--urgency=critical",
+                "--app-name=hyde-ipc",
+                "hyde-ipc stopped",
+                &message,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    process::exit(LISTENER_FAILED)
+}
+
+/// systemd sets `INVOCATION_ID` for every unit it starts.
+fn is_service() -> bool {
+    env::var_os("INVOCATION_ID").is_some()
+}
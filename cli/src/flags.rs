@@ -3,7 +3,7 @@
 //! This module defines the command-line interface using clap, including all subcommands and their
 //! options.
 
-use clap::{ArgGroup, Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 use serde::Deserialize;
 
 /// Command-line interface for hyde-ipc.
@@ -13,6 +13,34 @@ pub struct Cli {
     #[command(subcommand)]
     /// The subcommand to execute.
     pub command: Commands,
+
+    /// Output format for commands that print data.
+    #[arg(
+        short = 'o',
+        long = "output",
+        global = true,
+        value_enum
+    )]
+    pub output: Option<OutputFormat>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Json,
+    Csv,
+    /// A `{"text", "tooltip"}` object for waybar custom modules.
+    Waybar,
+}
+
+/// How `listen --timestamps` stamps events.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Local wall-clock time, e.g. `2025-06-01T21:04:05.123+02:00`.
+    Rfc3339,
+    /// Milliseconds since `listen` started.
+    Mono,
 }
 
 /// All supported subcommands for hyde-ipc.
@@ -22,7 +50,7 @@ pub enum Commands {
     #[command(group(
         ArgGroup::new("action")
             .required(true)
-            .args(["get", "set"]),
+            .args(["get", "set", "unwatch"]),
     ))]
     Keyword {
         /// Use async mode
@@ -45,6 +73,18 @@ pub enum Commands {
         )]
         set: bool,
 
+        /// With --set, set the keyword again whenever Hyprland reloads its config (done by the
+        /// reaction daemon)
+        #[arg(
+            long = "watch-config-reload",
+            requires = "set"
+        )]
+        watch_config_reload: bool,
+
+        /// Stop setting the keyword again after config reloads
+        #[arg(long = "unwatch", group = "action")]
+        unwatch: bool,
+
         /// The keyword to get or set
         keyword: String,
 
@@ -52,7 +92,8 @@ pub enum Commands {
         value: Option<String>,
     },
 
-    /// Execute a dispatcher command.
+    /// Execute a dispatcher command. Chain more with `--then`, e.g.
+    /// `dispatch workspace 3 --then exec kitty`.
     Dispatch(DispatchCommand),
 
     /// Listen for and log Hyprland events.
@@ -68,6 +109,173 @@ pub enum Commands {
             default_value = "0"
         )]
         max_events: usize,
+
+        /// Stop listening after this long, e.g. 30s, 5m or 1h
+        #[arg(
+            long = "timeout",
+            value_name = "DURATION"
+        )]
+        timeout: Option<String>,
+
+        /// Collapse bursts: hold events until no other of the same type and subtype came for
+        /// this many milliseconds, then print only the last one
+        #[arg(long = "debounce", value_name = "MS")]
+        debounce: Option<u64>,
+
+        /// Skip events identical to the one printed just before
+        #[arg(long = "dedup")]
+        dedup: bool,
+
+        /// Only print events whose window class matches (`^`/`$` anchor the pattern)
+        #[arg(long = "class")]
+        class: Option<String>,
+
+        /// Only print events whose window title matches (`^`/`$` anchor the pattern)
+        #[arg(long = "title")]
+        title: Option<String>,
+
+        /// Only print events on this workspace, by name or id
+        #[arg(long = "workspace")]
+        workspace: Option<String>,
+
+        /// Also store every printed event in this sqlite database (needs the sqlite feature)
+        #[arg(long = "db", value_name = "PATH")]
+        db: Option<String>,
+
+        /// Serve the events as JSON lines to any number of clients on a unix socket (default:
+        /// events-<instance>.sock in $XDG_RUNTIME_DIR/hyde-ipc)
+        #[arg(long = "broadcast", value_name = "SOCKET", num_args = 0..=1, default_missing_value = "")]
+        broadcast: Option<String>,
+
+        /// Print one JSON object per event with its type, subtype and payload fields side by side.
+        /// `--output json` puts the payload under `data` instead
+        #[arg(short = 'j', long = "json")]
+        json: bool,
+
+        /// Newline-delimited JSON: one compact object per line, flushed as each event arrives
+        /// (the same as `--json`, spelled out for consumers that rely on it)
+        #[arg(long = "ndjson", conflicts_with = "json")]
+        ndjson: bool,
+
+        /// CSV with the same columns for every event; payload fields without a column of their
+        /// own go into `data` as JSON. Shorthand for `--output csv`
+        #[arg(long = "csv", conflicts_with_all = ["json", "ndjson"])]
+        csv: bool,
+
+        /// Add a `time` to every event, as local RFC 3339 time or milliseconds since start
+        #[arg(
+            long = "timestamps",
+            value_name = "FORMAT",
+            num_args = 0..=1,
+            default_missing_value = "rfc3339"
+        )]
+        timestamps: Option<TimestampFormat>,
+
+        /// Look up the window of each window event and add its pid, workspace, monitor and
+        /// floating state (one extra query per event)
+        #[arg(long = "enrich")]
+        enrich: bool,
+
+        /// Print the last N recorded events that pass the filters before the live ones, from the
+        /// `--db` database or the default one (needs the sqlite feature and a recorder running
+        /// `listen --db`)
+        #[arg(long = "history", value_name = "N")]
+        history: Option<usize>,
+
+        /// Browse events in an interactive monitor with type filters, pause and search (needs
+        /// the tui feature)
+        #[arg(long = "tui", conflicts_with_all = ["json", "ndjson", "csv", "timeout", "max_events"])]
+        tui: bool,
+    },
+
+    /// Block until a matching event arrives, e.g. `wait -e window -s opened --filter class:kitty`.
+    /// Exits 0 when it does, or 2 once the timeout runs out.
+    Wait {
+        /// Event type (e.g. "window", "workspace")
+        #[arg(short = 'e', long = "event")]
+        event: Option<String>,
+
+        /// Event subtype (e.g. "opened")
+        #[arg(short = 's', long = "subtype")]
+        subtype: Option<String>,
+
+        /// Condition on the payload as FIELD:PATTERN, with FIELD one of class, title or
+        /// workspace (`^`/`$` anchor class and title patterns). Repeat to require several
+        #[arg(
+            long = "filter",
+            value_name = "FIELD:PATTERN"
+        )]
+        filter: Vec<String>,
+
+        /// Give up after this long, e.g. 10s or 2m
+        #[arg(
+            long = "timeout",
+            value_name = "DURATION"
+        )]
+        timeout: Option<String>,
+
+        /// Print the event once it arrives
+        #[arg(short = 'p', long = "print")]
+        print: bool,
+
+        /// Print the event as one flat JSON object, like `listen --json`
+        #[arg(short = 'j', long = "json")]
+        json: bool,
+    },
+
+    /// Serve fake Hyprland sockets from a script, for testing without a compositor. Run other
+    /// commands with the printed HYPRLAND_INSTANCE_SIGNATURE to talk to it.
+    #[cfg(feature = "mock-server")]
+    MockServer {
+        /// TOML script with the replies to requests and the events to send
+        script: String,
+
+        /// Instance signature to serve under $XDG_RUNTIME_DIR/hypr
+        #[arg(
+            long = "signature",
+            default_value = "hyde-ipc-mock"
+        )]
+        signature: String,
+    },
+
+    /// Look back at events recorded with `listen --db`, newest first.
+    History {
+        /// Event database to read (default: events.sqlite in the state dir)
+        #[arg(long = "db", value_name = "PATH")]
+        db: Option<String>,
+
+        /// Only events of this type (e.g. "window", "workspace")
+        #[arg(short = 't', long = "type")]
+        event: Option<String>,
+
+        /// Only events with this subtype (e.g. "opened")
+        #[arg(short = 's', long = "subtype")]
+        subtype: Option<String>,
+
+        /// Only events of windows with exactly this class
+        #[arg(long = "class")]
+        class: Option<String>,
+
+        /// Only events from this time on: today, yesterday, YYYY-MM-DD, or a span back like 2h or
+        /// 3d
+        #[arg(long = "since", value_name = "TIME")]
+        since: Option<String>,
+
+        /// Only events before this time, in the same forms as --since
+        #[arg(long = "until", value_name = "TIME")]
+        until: Option<String>,
+
+        /// Maximum number of events to print
+        #[arg(
+            short = 'n',
+            long = "limit",
+            default_value = "50"
+        )]
+        limit: usize,
+
+        /// Print the result as JSON. Shorthand for `--output json`
+        #[arg(short = 'j', long = "json")]
+        json: bool,
     },
 
     /// React to specific events by dispatching commands.
@@ -109,6 +317,10 @@ pub enum Commands {
         #[arg(short = 'f', long = "filter")]
         filter: Option<String>,
 
+        /// Keyboard name pattern for layout events (e.g., "at-translated-set-2-keyboard")
+        #[arg(long = "device")]
+        device: Option<String>,
+
         /// Dispatcher command to execute when the event occurs
         #[command(subcommand)]
         dispatch: Option<Dispatch>,
@@ -120,6 +332,29 @@ pub enum Commands {
             default_value = "0"
         )]
         max_reactions: usize,
+
+        /// Log p50/p95 latency from event to finished dispatchers for each reaction
+        #[arg(long = "trace-latency")]
+        trace_latency: bool,
+
+        /// Exit after the first reaction fires, regardless of --max-reactions or max_count
+        #[arg(long = "once")]
+        once: bool,
+
+        /// Print the config as the daemon would run it, then exit
+        #[arg(
+            long = "print-effective-config",
+            requires = "config"
+        )]
+        print_effective_config: bool,
+
+        /// Validate the config, including every dispatcher's arguments, then exit
+        #[arg(long = "check", requires = "config")]
+        check: bool,
+
+        /// Stop the daemon already running this config and take its place
+        #[arg(long = "replace", requires = "config")]
+        replace: bool,
     },
 
     /// Manage the hyde-ipc user service.
@@ -130,17 +365,247 @@ pub enum Commands {
         /// Path to the config file to load.
         #[arg(short = 'c', long)]
         config_path: String,
+
+        /// Print what the service would load instead of installing the config.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Query Hyprland for information.
     Query(QueryCommand),
+
+    /// Apply or revert a scene from the config.
+    Scene {
+        /// Config file defining the scenes (defaults to the global config).
+        #[arg(short = 'c', long, global = true)]
+        config: Option<String>,
+
+        #[command(subcommand)]
+        action: SceneAction,
+    },
+
+    /// Switch do-not-disturb, which reactions can check with `when = { dnd = false }`.
+    Dnd {
+        #[command(subcommand)]
+        action: DndAction,
+
+        /// Pause this notification daemon too (`auto` picks the running one)
+        #[arg(long, value_enum, global = true)]
+        notifier: Option<Notifier>,
+    },
+
+    /// Alt-tab through windows, most recently focused first.
+    Cycle {
+        #[command(subcommand)]
+        action: CycleAction,
+    },
+
+    /// Pause or resume the reaction daemon.
+    Ctl {
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+
+    /// Reload Hyprland's config, then re-apply the active scene, saved cursor and exposed state.
+    Reload {
+        /// Config file defining the scenes (defaults to the global config).
+        #[arg(short = 'c', long)]
+        config: Option<String>,
+        /// Also force the renderer to reload
+        #[arg(long)]
+        renderer: bool,
+    },
+
+    /// Restart Hyprland without losing the window layout.
+    Hypr {
+        #[command(subcommand)]
+        action: HyprAction,
+    },
+
+    /// Set the cursor theme and size, and keep them across Hyprland config reloads.
+    Cursor {
+        #[command(subcommand)]
+        action: CursorAction,
+    },
+
+    /// Bundle the effective config, versions, service status and recent service log into a
+    /// tarball for bug reports. Nothing is uploaded.
+    Report {
+        /// Config file to include (defaults to the global config).
+        #[arg(short = 'c', long)]
+        config: Option<String>,
+
+        /// Where to write the tarball (defaults to `hyde-ipc-report-<time>.tar.gz`).
+        #[arg(short = 'f', long = "file")]
+        file: Option<String>,
+
+        /// How many minutes of service log to include.
+        #[arg(long, default_value_t = 10)]
+        minutes: u64,
+
+        /// Keep window titles and commands instead of redacting them.
+        #[arg(long)]
+        no_redact: bool,
+    },
+
+    /// Compare this hyde-ipc with the running Hyprland.
+    Version {
+        #[command(subcommand)]
+        action: VersionAction,
+    },
+
+    /// Print live compositor data for shell completion scripts, one per line.
+    #[command(name = "_complete", hide = true)]
+    Complete {
+        #[arg(value_enum)]
+        kind: LiveCompletion,
+    },
+
+    /// Print values for shell completion scripts, one per line.
+    #[command(hide = true)]
+    Completions {
+        #[arg(
+            long = "list-dispatcher-values",
+            value_enum
+        )]
+        list_dispatcher_values: CompletionValues,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SceneAction {
+    /// Apply a scene, reverting the active one first.
+    Apply { name: String },
+    /// Restore the state from before the active scene was applied.
+    Revert,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DndAction {
+    /// Turn do-not-disturb on.
+    On,
+    /// Turn do-not-disturb off.
+    Off,
+    /// Turn do-not-disturb on if it is off, and off if it is on.
+    Toggle,
+    /// Print whether do-not-disturb is on.
+    Status,
+}
+
+/// Notification daemons `dnd` can pause.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notifier {
+    Auto,
+    Dunst,
+    Mako,
+    Swaync,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CycleAction {
+    /// Start cycling and select the previously focused window.
+    Start,
+    /// Select the next window, starting a cycle if none is in progress.
+    Next,
+    /// Select the previous window, starting a cycle if none is in progress.
+    Prev,
+    /// Focus the selected window and end the cycle.
+    Commit,
+    /// End the cycle without changing focus.
+    Cancel,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CursorAction {
+    /// Set the cursor and have the reaction daemon restore it after every config reload.
+    Set { theme: String, size: u16 },
+    /// Print the saved cursor.
+    Show,
+    /// Forget the saved cursor; the next config reload restores Hyprland's own.
+    Reset,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CtlAction {
+    /// Stop reactions and placement policies from running, until resumed or for a while.
+    Pause {
+        /// Resume on its own after this long, e.g. 10m or 1h
+        #[arg(long = "for", value_name = "DURATION")]
+        duration: Option<String>,
+    },
+    /// Let reactions run again.
+    Resume,
+    /// Show the running daemons and how often each reaction fired.
+    Status {
+        /// Print one `{"text", "tooltip", "class"}` object for a waybar custom module: active
+        /// reactions, per-reaction statistics and `ok`, `paused` or `error`
+        #[arg(long)]
+        waybar: bool,
+    },
+    /// Inspect the config the daemon runs.
+    Config {
+        #[command(subcommand)]
+        action: CtlConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CtlConfigAction {
+    /// Print the config as the daemon would run it.
+    Show {
+        /// Config file to show (defaults to the global config)
+        #[arg(short = 'c', long)]
+        config: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum HyprAction {
+    /// Save the window layout, then exit Hyprland (or reload it) once confirmed.
+    RestartSafe {
+        /// Reload the config instead of exiting, and restore the layout right after
+        #[arg(long)]
+        reload: bool,
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+    /// Put windows back where `restart-safe` saved them and start the missing ones.
+    Restore,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum VersionAction {
+    /// Print known incompatibilities with the running Hyprland and whether to upgrade hyde-ipc or
+    /// Hyprland. Exits with 1 if there are any.
+    Check,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum LiveCompletion {
+    /// Classes of open windows.
+    Windows,
+    /// Workspace identifiers accepted by workspace arguments.
+    Workspaces,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CompletionValues {
+    Dispatchers,
+    Events,
+    /// `<event> <subtype>` pairs.
+    Subtypes,
+    Corners,
+    Directions,
+    CycleDirections,
+    FullscreenModes,
 }
 
 #[derive(Parser, Debug, Clone)]
 #[command(group(
     ArgGroup::new("action")
         .required(true)
-        .args(["install", "uninstall", "start", "kill", "restart", "check", "watch"]),
+        .args(["install", "uninstall", "start", "kill", "restart", "reload", "check", "watch"]),
 ))]
 pub struct SetupCommand {
     /// Install the user service.
@@ -163,6 +628,10 @@ pub struct SetupCommand {
     #[arg(long)]
     pub restart: bool,
 
+    /// Make the user service re-read its config without restarting.
+    #[arg(long)]
+    pub reload: bool,
+
     /// Check the status of the user service.
     #[arg(short = 'c', long)]
     pub check: bool,
@@ -174,6 +643,17 @@ pub struct SetupCommand {
 
 #[derive(Parser, Debug, Clone)]
 pub struct QueryCommand {
+    /// Re-run the query every interval (in milliseconds) and print the result when it changes
+    #[arg(
+        short = 'w',
+        long = "watch",
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "500",
+        value_name = "INTERVAL"
+    )]
+    pub watch: Option<u64>,
+
     #[command(subcommand)]
     pub command: Query,
 }
@@ -181,13 +661,60 @@ pub struct QueryCommand {
 #[derive(Subcommand, Debug, Clone)]
 pub enum Query {
     /// Get the current cursor position.
-    CursorPos {
-        /// Watch for changes and log the position continuously.
-        #[arg(short = 'w', long = "watch")]
-        watch: bool,
+    CursorPos,
+    /// Get the active keybind submap (empty for the default submap).
+    Submap,
+    /// List keyboards with their active layout.
+    KeyboardLayout,
+    /// List monitors with their DPMS (power) state.
+    Monitors {
+        /// Keep running and print a line whenever a monitor is powered on or off
+        #[arg(long = "watch-dpms")]
+        watch_dpms: bool,
+    },
+    /// List all windows (clients).
+    Clients {
+        /// Group windows by workspace, monitor or class
+        #[arg(
+            short = 't',
+            long = "tree-by",
+            value_enum
+        )]
+        tree_by: Option<TreeBy>,
+
+        /// Print the result as JSON. Shorthand for `--output json`
+        #[arg(short = 'j', long = "json")]
+        json: bool,
+
+        /// Include windows matched by the config's `ignore` list
+        #[arg(short = 'a', long = "all")]
+        all: bool,
+    },
+    /// List workspaces.
+    Workspaces {
+        /// Print one compact entry (id, exists, windows, active, urgent) per workspace slot
+        /// With `--watch`, print it again on every relevant event instead of polling
+        #[arg(long = "occupancy-map")]
+        occupancy_map: bool,
+
+        /// Number of workspace slots the occupancy map always lists
+        #[arg(
+            long = "count",
+            default_value = "10",
+            requires = "occupancy_map"
+        )]
+        count: i32,
     },
 }
 
+/// Keys that `query clients` can group windows by.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeBy {
+    Workspace,
+    Monitor,
+    Class,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(
     help_template = "{before-help}{about-with-newline}{usage-heading}{usage}{options-heading}{options}"
@@ -201,11 +728,71 @@ pub struct DispatchCommand {
     #[arg(short = 'l', long = "list-dispatchers")]
     pub list_dispatchers: bool,
 
+    /// Print the dispatcher list as JSON, a shorthand for `--output json`
+    #[arg(
+        short = 'j',
+        long = "json",
+        requires = "list_dispatchers"
+    )]
+    pub json: bool,
+
+    /// Print the parsed dispatcher and the command sent to Hyprland's socket
+    #[arg(
+        short = 'x',
+        long = "explain",
+        global = true
+    )]
+    pub explain: bool,
+
+    /// Print the command that would be sent to Hyprland's socket instead of sending it
+    #[arg(
+        short = 'n',
+        long = "dry-run",
+        global = true
+    )]
+    pub dry_run: bool,
+
+    /// Run the dispatchers listed in a file, one per line
+    #[arg(
+        short = 'f',
+        long = "file",
+        value_name = "PATH",
+        conflicts_with = "stdin"
+    )]
+    pub file: Option<String>,
+
+    /// Run the dispatchers read from stdin, one per line
+    #[arg(long = "stdin")]
+    pub stdin: bool,
+
+    /// Retry with backoff until Hyprland accepts the dispatch, for exec-once scripts that start
+    /// before its socket is up
+    #[arg(long = "retry-until-success")]
+    pub retry_until_success: bool,
+
+    /// Give up retrying after this many attempts
+    #[arg(
+        long = "retry-attempts",
+        value_name = "N",
+        default_value_t = 10,
+        requires = "retry_until_success"
+    )]
+    pub retry_attempts: u32,
+
+    /// Give up retrying after this many seconds in total
+    #[arg(
+        long = "retry-timeout",
+        value_name = "SECONDS",
+        default_value_t = 30,
+        requires = "retry_until_success"
+    )]
+    pub retry_timeout: u64,
+
     #[command(subcommand)]
     pub command: Option<Dispatch>,
 }
 
-#[derive(clap::Args, Debug, Clone, Default, Deserialize)]
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Deserialize)]
 pub struct WindowId {
     #[arg(long, group = "winid")]
     pub class: Option<String>,
@@ -234,7 +821,48 @@ impl WindowId {
     }
 }
 
-#[derive(Subcommand, Debug, Clone)]
+/// Window rules applied to the windows an `exec` command opens, and how the command is started.
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct ExecRules {
+    /// A Hyprland window rule, e.g. "workspace 3 silent" (repeatable)
+    #[arg(long = "rule", value_name = "RULE")]
+    pub rules: Vec<String>,
+    /// Open the window floating
+    #[arg(long)]
+    pub float: bool,
+    /// Open the window fullscreen
+    #[arg(long)]
+    pub fullscreen: bool,
+    /// Set an environment variable for the command (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+    /// Run the command from hyde-ipc and wait for it to exit instead of having Hyprland start it
+    /// detached. Window rules can't be used then
+    #[arg(
+        long = "no-detach",
+        conflicts_with_all = ["rules", "float", "fullscreen"]
+    )]
+    pub no_detach: bool,
+}
+
+/// Restrictions on the windows `cycle-window` moves between.
+#[derive(clap::Args, Debug, Clone, Copy, Default, PartialEq)]
+pub struct CycleScopeArgs {
+    /// Only cycle through tiled windows
+    #[arg(long, conflicts_with = "floating")]
+    pub tiled: bool,
+    /// Only cycle through floating windows
+    #[arg(long)]
+    pub floating: bool,
+    /// Cycle through windows on every visible workspace, not just the active one
+    #[arg(long)]
+    pub visible: bool,
+    /// Only cycle through windows with the same class as the active window
+    #[arg(long)]
+    pub same_class: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
 pub enum ResizeCmd {
     Delta { dx: i16, dy: i16 },
     Exact { width: i16, height: i16 },
@@ -243,7 +871,20 @@ pub enum ResizeCmd {
 #[derive(Subcommand, Debug, Clone)]
 pub enum Dispatch {
     /// Execute a command
-    Exec { command: Vec<String> },
+    Exec {
+        #[command(flatten)]
+        rules: ExecRules,
+        command: Vec<String>,
+    },
+    /// Send a dispatcher string to Hyprland as is, e.g. `raw "layoutmsg swapnext"`
+    Raw {
+        #[arg(
+            required = true,
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        dispatcher: Vec<String>,
+    },
     /// Kill the active window
     KillActiveWindow,
     /// Toggle floating mode for a window
@@ -251,29 +892,64 @@ pub enum Dispatch {
     ToggleFloating {
         #[command(flatten)]
         window: WindowId,
+        /// Restore the window's last floating size and position when it floats again
+        #[arg(short = 'r', long)]
+        remember: bool,
     },
     /// Toggle the split orientation
     ToggleSplit,
+    /// Change the dwindle split ratio by an amount (e.g. -0.1), or set it with --exact
+    SplitRatio {
+        #[arg(allow_negative_numbers = true)]
+        value: f32,
+        /// Set the ratio to the value instead of changing it
+        #[arg(short = 'e', long)]
+        exact: bool,
+    },
     /// Toggle opacity for the active window
     ToggleOpaque,
+    /// Toggle swallowing for the active window
+    ToggleSwallow,
     /// Move cursor to a corner
     MoveCursorToCorner { corner: String },
     /// Move cursor to a specific position
     MoveCursor {
-        #[arg()]
-        x: i64,
-        #[arg()]
-        y: i64,
+        #[arg(
+            allow_negative_numbers = true,
+            required_unless_present = "to_window"
+        )]
+        x: Option<i64>,
+        #[arg(
+            allow_negative_numbers = true,
+            required_unless_present = "to_window"
+        )]
+        y: Option<i64>,
+        /// Treat x and y as a delta from the current cursor position
+        #[arg(short = 'r', long = "relative")]
+        relative: bool,
+        /// Move the cursor to the center of a window (e.g., "class:kitty")
+        #[arg(
+            long = "to-window",
+            conflicts_with_all = ["x", "y", "relative"]
+        )]
+        to_window: Option<String>,
     },
     /// Toggle fullscreen mode
     ToggleFullscreen { mode: String },
     /// Move window to workspace. Accepts a workspace number (e.g., '5'),
     /// relative movement (e.g., 'right:2', 'left:1'), or special keywords
-    /// ('previous', 'empty', 'name:<name>').
-    MoveToWorkspace { workspace: String },
+    /// ('previous', 'back', 'previous_per_monitor', 'empty', 'emptym', 'emptyn', 'name:<name>',
+    /// 'special', 'special:<name>').
+    MoveToWorkspace {
+        workspace: String,
+        /// If the workspace already has a window, send it back to this window's workspace
+        #[arg(long = "swap-if-occupied")]
+        swap_if_occupied: bool,
+    },
     /// Move window to workspace silently. Accepts a workspace number (e.g., '5'),
     /// relative movement (e.g., 'right:2', 'left:1'), or special keywords
-    /// ('previous', 'empty', 'name:<name>').
+    /// ('previous', 'back', 'previous_per_monitor', 'empty', 'emptym', 'emptyn', 'name:<name>',
+    /// 'special', 'special:<name>').
     #[command(group(ArgGroup::new("winid_movetoworkspacesilent").args(&["class", "title", "pid", "address"])))]
     MoveToWorkspaceSilent {
         workspace: String,
@@ -282,12 +958,23 @@ pub enum Dispatch {
     },
     /// Switch to a workspace. Accepts a workspace number (e.g., '5'),
     /// relative movement (e.g., 'right:2', 'left:1'), or special keywords
-    /// ('previous', 'empty', 'name:<name>').
+    /// ('previous', 'back', 'previous_per_monitor', 'empty', 'emptym', 'emptyn', 'name:<name>',
+    /// 'special', 'special:<name>').
     Workspace { workspace: String },
+    /// Switch to a workspace, or back to the previous one if it is already focused. Takes the same
+    /// workspaces as `workspace`.
+    WorkspaceToggle { workspace: String },
     /// Cycle through windows
     CycleWindow {
         #[arg()]
         direction: String,
+        #[command(flatten)]
+        scope: CycleScopeArgs,
+    },
+    /// Swap the active window with the next or previous window
+    SwapNext {
+        #[arg()]
+        direction: String,
     },
     /// Move focus in a direction (up, down, left, right)
     MoveFocus {
@@ -298,6 +985,9 @@ pub enum Dispatch {
     MoveWindow {
         #[arg()]
         target: String,
+        /// Swap with the window already at the target instead of stacking on it
+        #[arg(long = "swap-if-occupied")]
+        swap_if_occupied: bool,
     },
     /// Swap windows in a direction (up, down, left, right)
     SwapWindow {
@@ -309,6 +999,15 @@ pub enum Dispatch {
     FocusWindow {
         #[command(flatten)]
         window: WindowId,
+        /// Look the window up first and fail if none matches, instead of silently doing nothing
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Forward the triggering keybind to a specific window
+    #[command(group(ArgGroup::new("winid_pass").required(true).args(&["class", "title", "pid", "address"])))]
+    Pass {
+        #[command(flatten)]
+        window: WindowId,
     },
     /// Toggle fake fullscreen
     ToggleFakeFullscreen,
@@ -328,6 +1027,39 @@ pub enum Dispatch {
     ForceRendererReload,
     /// Exit Hyprland
     Exit,
+    /// Swap the active window with the master (master, child, auto)
+    SwapWithMaster {
+        #[arg()]
+        param: String,
+    },
+    /// Focus the master window (master, auto)
+    FocusMaster {
+        #[arg()]
+        param: String,
+    },
+    /// Add a master to the master side
+    AddMaster,
+    /// Remove a master from the master side
+    RemoveMaster,
+    /// Set the master orientation to left
+    OrientationLeft,
+    /// Set the master orientation to right
+    OrientationRight,
+    /// Set the master orientation to top
+    OrientationTop,
+    /// Set the master orientation to bottom
+    OrientationBottom,
+    /// Set the master orientation to center
+    OrientationCenter,
+    /// Cycle to the next master orientation
+    OrientationNext,
+    /// Cycle to the previous master orientation
+    OrientationPrev,
+    /// Move the active window back or forward inside its group
+    MoveGroupWindow {
+        /// `back` or `forward`
+        direction: String,
+    },
     /// Resize the active window
     ResizeActive {
         #[command(subcommand)]
@@ -341,4 +1073,78 @@ pub enum Dispatch {
         #[command(flatten)]
         window: WindowId,
     },
+    /// Switch a keyboard's layout. The device is a name from `query keyboard-layout`, `current` for
+    /// the main keyboard or `all`
+    SwitchLayout {
+        device: String,
+        /// `next`, `prev` or a layout index
+        layout: String,
+    },
+    /// Enter a keybind submap, or `reset` to go back to the default one
+    Submap { name: String },
+    /// Set the cursor theme and size (until the next config reload, see `hyde-ipc cursor`)
+    SetCursor { theme: String, size: u16 },
+    /// Replace a file's content in one step, e.g. a state file a bar reads. In reactions,
+    /// `{reaction}`, `{event}`, `{count}` and `{time}` are filled in
+    WriteFile { path: String, content: String },
+    /// Send a Unix signal (e.g. `SIGRTMIN+8`, `USR1`) to every process with a name, or to the pid
+    /// in a pidfile
+    #[command(group(ArgGroup::new("signal_target").required(true).args(&["process", "pidfile"])))]
+    Signal {
+        #[arg(long)]
+        process: Option<String>,
+        #[arg(long)]
+        pidfile: Option<String>,
+        signal: String,
+    },
+    /// Show a desktop notification with notify-send. In the reaction daemon, identical
+    /// notifications within a few seconds are merged into one with a count
+    Notify {
+        summary: String,
+        body: Option<String>,
+        /// low, normal or critical
+        #[arg(short = 'u', long)]
+        urgency: Option<String>,
+    },
+    /// Wait before running the next dispatcher of a batch or reaction
+    Sleep {
+        /// Milliseconds to wait
+        ms: u64,
+    },
+    /// Run a dispatcher once for every window matching all `--where` conditions. The dispatcher
+    /// can use {address}, {class}, {title}, {pid} and {workspace}
+    ForEach {
+        /// floating, tiled, fullscreen, pinned, xwayland, workspace:active, workspace:<id|name>,
+        /// class:<pattern>, title:<pattern>, pid:<pid> or address:<address>; prefix with ! to
+        /// negate
+        #[arg(long = "where", value_name = "CONDITION")]
+        conditions: Vec<String>,
+        #[arg(
+            required = true,
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        dispatcher: Vec<String>,
+    },
+    /// Toggle a tag on a window (the active one unless given). `+tag` adds it, `-tag` removes it
+    TagWindow {
+        #[arg(allow_hyphen_values = true)]
+        tag: String,
+        #[command(flatten)]
+        window: WindowId,
+    },
+    /// Set a window property such as opacity, rounding, forceopaque or nomaxsize, on the active
+    /// window unless one is given
+    SetProp {
+        #[command(flatten)]
+        window: WindowId,
+        /// The property, e.g. `opacity`
+        property: String,
+        /// The value, e.g. `0.8`
+        #[arg(allow_negative_numbers = true)]
+        value: String,
+        /// Keep window rules from overriding the property
+        #[arg(long)]
+        lock: bool,
+    },
 }
@@ -0,0 +1,78 @@
+//! Floating geometry remembered across `toggle-floating --remember` calls.
+//!
+//! Hyprland forgets a window's floating size and position once it is tiled. Before a floating
+//! window is tiled its geometry is stored under the state directory, keyed by window address, and
+//! put back the next time the window is floated.
+
+use crate::clients::{find_client, find_client_by_address};
+use crate::state_file;
+use hyprland::data::{Client, Clients};
+use hyprland::dispatch::{Dispatch, DispatchType, Position, WindowIdentifier};
+use hyprland::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Geometry {
+    at: (i16, i16),
+    size: (i16, i16),
+}
+
+const STATE_FILE: &str = "floating-geometry.toml";
+
+fn load() -> HashMap<String, Geometry> {
+    state_file::load(STATE_FILE).unwrap_or_default()
+}
+
+fn save(store: &HashMap<String, Geometry>) -> Result<(), String> {
+    state_file::save(STATE_FILE, store)
+}
+
+/// Toggle floating for `window` (or the active window), saving the geometry when it gets tiled and
+/// restoring it when it floats again.
+pub fn toggle_floating(window: Option<WindowIdentifier<'static>>) -> Result<(), String> {
+    let client = match &window {
+        Some(id) => find_client(id)?,
+        None => Client::get_active()
+            .map_err(|e| e.to_string())?
+            .ok_or("No active window")?,
+    };
+    let address = WindowIdentifier::Address(client.address.clone());
+    let key = client.address.to_string();
+    let mut store = load();
+    // Closed windows never come back with the same address, so drop their entries.
+    if let Ok(clients) = Clients::get() {
+        store.retain(|address, _| {
+            clients
+                .iter()
+                .any(|c| c.address.to_string() == *address)
+        });
+    }
+
+    if client.floating {
+        store.insert(key, Geometry { at: client.at, size: client.size });
+        save(&store)?;
+        return Dispatch::call(DispatchType::ToggleFloating(Some(address)))
+            .map_err(|e| e.to_string());
+    }
+
+    Dispatch::call(DispatchType::ToggleFloating(Some(address.clone())))
+        .map_err(|e| e.to_string())?;
+    let Some(geometry) = store.get(&key).copied() else {
+        return Ok(());
+    };
+    // Only restore if the toggle actually left the window floating.
+    if !find_client_by_address(&client.address)?.floating {
+        return Ok(());
+    }
+    Dispatch::call(DispatchType::ResizeWindowPixel(
+        Position::Exact(geometry.size.0, geometry.size.1),
+        address.clone(),
+    ))
+    .map_err(|e| e.to_string())?;
+    Dispatch::call(DispatchType::MoveWindowPixel(
+        Position::Exact(geometry.at.0, geometry.at.1),
+        address,
+    ))
+    .map_err(|e| e.to_string())
+}
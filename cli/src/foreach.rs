@@ -0,0 +1,167 @@
+//! The `foreach` dispatcher: run a dispatcher once for every window matching some conditions.
+//!
+//! The dispatcher is written as on the command line. Its words can use the window's placeholders
+//! (`{address}`, `{class}`, ...) on top of the reaction's own, so every run targets its window.
+
+use crate::flags::Dispatch as DispatchCmd;
+use crate::ignore::IgnoreList;
+use crate::parsers::ParsedWindowIdentifier;
+use crate::template::{self, Vars};
+use crate::{clients, dispatch, suggest};
+use hyprland::data::{Client, Clients, FullscreenMode, Workspace};
+use hyprland::dispatch::WindowIdentifier;
+use hyprland::prelude::*;
+use std::str::FromStr;
+
+const KEYWORDS: [&str; 6] = [
+    "floating",
+    "tiled",
+    "fullscreen",
+    "pinned",
+    "xwayland",
+    "workspace:active",
+];
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Floating,
+    Tiled,
+    Fullscreen,
+    Pinned,
+    XWayland,
+    ActiveWorkspace,
+    /// A workspace id or name.
+    Workspace(String),
+    Window(WindowIdentifier<'static>),
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    negated: bool,
+    condition: Condition,
+}
+
+fn parse_condition(condition: &str) -> Result<Term, String> {
+    let (negated, rest) = match condition.trim().strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, condition.trim()),
+    };
+    let condition = match rest {
+        "floating" => Condition::Floating,
+        "tiled" => Condition::Tiled,
+        "fullscreen" => Condition::Fullscreen,
+        "pinned" => Condition::Pinned,
+        "xwayland" => Condition::XWayland,
+        "workspace:active" => Condition::ActiveWorkspace,
+        _ => match rest.split_once(':') {
+            Some(("workspace", workspace)) if !workspace.is_empty() => {
+                Condition::Workspace(workspace.to_string())
+            },
+            Some(("class" | "title" | "pid" | "address", _)) => {
+                Condition::Window(ParsedWindowIdentifier::from_str(rest)?.0)
+            },
+            _ => {
+                return Err(format!(
+                    "Unknown condition {condition}{}",
+                    suggest::hint(rest, KEYWORDS)
+                ));
+            },
+        },
+    };
+    Ok(Term { negated, condition })
+}
+
+/// Check `--where` conditions without running anything.
+pub fn validate_conditions(conditions: &[String]) -> Result<(), String> {
+    conditions
+        .iter()
+        .try_for_each(|condition| parse_condition(condition).map(drop))
+}
+
+fn matches(term: &Term, client: &Client, active_workspace: Option<i32>) -> bool {
+    let matched = match &term.condition {
+        Condition::Floating => client.floating,
+        Condition::Tiled => !client.floating,
+        Condition::Fullscreen => client.fullscreen != FullscreenMode::None,
+        Condition::Pinned => client.pinned,
+        Condition::XWayland => client.xwayland,
+        Condition::ActiveWorkspace => active_workspace == Some(client.workspace.id),
+        Condition::Workspace(workspace) => {
+            client.workspace.id.to_string() == *workspace || client.workspace.name == *workspace
+        },
+        Condition::Window(id) => clients::client_matches(client, id),
+    };
+    matched != term.negated
+}
+
+fn window_vars(base: &Vars, client: &Client) -> Vars {
+    let mut vars = base.clone();
+    vars.set("address", &client.address)
+        .set("class", &client.class)
+        .set("title", &client.title)
+        .set("pid", client.pid)
+        .set("workspace", &client.workspace.name)
+        .set("workspace_id", client.workspace.id);
+    vars
+}
+
+fn render_command(command: &[String], vars: &Vars) -> Result<DispatchCmd, String> {
+    let words = command
+        .iter()
+        .map(|word| template::render_known(word, vars))
+        .collect::<Result<Vec<_>, _>>()?;
+    let dispatcher = dispatch::parse_words(words)?;
+    if matches!(dispatcher, DispatchCmd::ForEach { .. }) {
+        return Err("for-each can't run another for-each".to_string());
+    }
+    Ok(dispatcher)
+}
+
+/// Check that `command` parses for a made-up window, with the placeholders of `base` available.
+pub fn validate_command(command: &[String], base: &Vars) -> Result<(), String> {
+    let mut vars = base.clone();
+    vars.set("address", "0x0")
+        .set("class", "class")
+        .set("title", "title")
+        .set("pid", 0)
+        .set("workspace", "1")
+        .set("workspace_id", 1);
+    dispatch::validate(&render_command(command, &vars)?)
+}
+
+/// Run `command` for every window matching all `conditions`, skipping ignored windows. Windows are
+/// looked up once, before the first run. A failing run doesn't stop the others.
+pub fn run(
+    conditions: &[String],
+    command: &[String],
+    base: &Vars,
+    ignore: &IgnoreList,
+) -> Result<(), String> {
+    let terms = conditions
+        .iter()
+        .map(|condition| parse_condition(condition))
+        .collect::<Result<Vec<_>, _>>()?;
+    let active_workspace = terms
+        .iter()
+        .any(|term| matches!(term.condition, Condition::ActiveWorkspace))
+        .then(|| Workspace::get_active().map(|workspace| workspace.id))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let errors: Vec<String> = Clients::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|client| !ignore.is_client_ignored(client))
+        .filter(|client| {
+            terms
+                .iter()
+                .all(|term| matches(term, client, active_workspace))
+        })
+        .filter_map(|client| {
+            render_command(command, &window_vars(base, &client))
+                .and_then(|dispatcher| dispatch::run_dispatch(dispatcher, false))
+                .err()
+                .map(|e| format!("{}: {e}", client.address))
+        })
+        .collect();
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+}
@@ -0,0 +1,196 @@
+//! `hyde-ipc history`: past window and workspace activity from the database `listen --db` records.
+
+use crate::event_db::{EventDb, Query, StoredEvent};
+use crate::flags::OutputFormat;
+use crate::output::{Printer, Record};
+use crate::units;
+use hyde_ipc_lib::service;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where `history` looks when no `--db` is given.
+fn default_path() -> Result<PathBuf, String> {
+    let mut path = service::get_state_dir().map_err(|e| e.to_string())?;
+    path.push("events.sqlite");
+    Ok(path)
+}
+
+pub struct Filter {
+    pub event: Option<String>,
+    pub subtype: Option<String>,
+    pub class: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: usize,
+}
+
+/// The database at `db`, or at the default path, which has to exist already.
+fn open(db: Option<&str>) -> Result<EventDb, String> {
+    let path = match db {
+        Some(path) => PathBuf::from(path),
+        None => default_path()?,
+    };
+    if !path.exists() {
+        return Err(format!(
+            "No event database at {0}, record one with `hyde-ipc listen --db {0}`",
+            path.display()
+        ));
+    }
+    EventDb::open(&path.to_string_lossy())
+}
+
+/// The last `count` recorded events whose type contains `event` and that `keep` accepts, newest
+/// first, for `listen --history`.
+pub fn recent(
+    db: Option<&str>,
+    count: usize,
+    event: Option<&str>,
+    keep: impl Fn(&StoredEvent) -> bool,
+) -> Result<Vec<StoredEvent>, String> {
+    let query = Query {
+        event_containing: event
+            .filter(|event| !event.is_empty())
+            .map(str::to_string),
+        limit: count,
+        ..Query::default()
+    };
+    open(db)?.query_matching(&query, keep)
+}
+
+pub fn run(db: Option<String>, filter: Filter, format: OutputFormat) -> Result<(), String> {
+    let db = open(db.as_deref())?;
+    let query = Query {
+        event: filter.event,
+        subtype: filter.subtype,
+        class: filter.class,
+        since: filter
+            .since
+            .as_deref()
+            .map(parse_time)
+            .transpose()?,
+        until: filter
+            .until
+            .as_deref()
+            .map(parse_time)
+            .transpose()?,
+        limit: filter.limit,
+        ..Query::default()
+    };
+    let events = db.query(&query)?;
+    let lines: Vec<String> = events
+        .iter()
+        .map(|event| {
+            let class = event.class.as_deref().unwrap_or("-");
+            format!("{} {} {} {class}", format_time(event.time), event.event, event.subtype)
+        })
+        .collect();
+    Printer::new(format).print(&Record::new(&events, lines.join("\n"))?)
+}
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// A point in time as unix milliseconds: `today`, `yesterday`, a local `YYYY-MM-DD` date, a span
+/// back from now like `30m`, `2h`, `3d` or `1w`, or unix seconds.
+fn parse_time(s: &str) -> Result<i64, String> {
+    match s {
+        "now" => return Ok(now_millis()),
+        "today" => return local_midnight(None, 0),
+        "yesterday" => return local_midnight(None, 1),
+        _ => {},
+    }
+    if let Some((year, rest)) = s.split_once('-') {
+        let date = rest
+            .split_once('-')
+            .and_then(|(month, day)| {
+                Some((year.parse().ok()?, month.parse().ok()?, day.parse().ok()?))
+            })
+            .ok_or_else(|| format!("Invalid date: {s}, expected YYYY-MM-DD"))?;
+        return local_midnight(Some(date), 0);
+    }
+    if s.bytes().all(|b| b.is_ascii_digit()) {
+        return s
+            .parse::<i64>()
+            .ok()
+            .and_then(|seconds| seconds.checked_mul(1000))
+            .ok_or_else(|| format!("Invalid time: {s}"));
+    }
+    let span = units::parse_duration(s)?;
+    Ok(now_millis().saturating_sub(i64::try_from(span.as_millis()).unwrap_or(i64::MAX)))
+}
+
+/// Local midnight of `date` (year, month, day), or of today, `days_ago` days earlier.
+fn local_midnight(date: Option<(i32, i32, i32)>, days_ago: i32) -> Result<i64, String> {
+    // SAFETY: `tm` is plain data, and both calls only read and write the structs they are given.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        match date {
+            Some((year, month, day)) => {
+                tm.tm_year = year - 1900;
+                tm.tm_mon = month - 1;
+                tm.tm_mday = day;
+            },
+            None => {
+                let now = libc::time(std::ptr::null_mut());
+                libc::localtime_r(&now, &mut tm);
+            },
+        }
+        tm.tm_hour = 0;
+        tm.tm_min = 0;
+        tm.tm_sec = 0;
+        tm.tm_mday -= days_ago;
+        tm.tm_isdst = -1;
+        match libc::mktime(&mut tm) {
+            -1 => Err("Failed to work out local midnight".to_string()),
+            seconds => Ok(seconds * 1000),
+        }
+    }
+}
+
+/// Unix milliseconds as local RFC 3339 time with milliseconds and the UTC offset.
+pub fn format_rfc3339(millis: i64) -> String {
+    let seconds = (millis / 1000) as libc::time_t;
+    // SAFETY: as in `local_midnight`.
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&seconds, &mut tm);
+        tm
+    };
+    let offset = tm.tm_gmtoff / 60;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}{}{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        millis % 1000,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+/// Unix milliseconds as local `YYYY-MM-DD HH:MM:SS`.
+pub fn format_time(millis: i64) -> String {
+    let seconds = (millis / 1000) as libc::time_t;
+    // SAFETY: as in `local_midnight`.
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&seconds, &mut tm);
+        tm
+    };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
@@ -0,0 +1,119 @@
+//! Windows that reactions and queries skip, from the config's top-level `ignore` list.
+//!
+//! Helper windows such as `xwaylandvideobridge` open, close and grab focus constantly without the
+//! user ever seeing them. Close and move events only carry an address, so the addresses of ignored
+//! windows are remembered while they are open.
+
+use crate::parsers::ParsedWindowIdentifier;
+use crate::reaction_handler::is_window_match;
+use hyde_ipc_lib::service;
+use hyprland::data::{Client, Clients};
+use hyprland::dispatch::WindowIdentifier;
+use hyprland::prelude::*;
+use hyprland::shared::Address;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct IgnoreList {
+    filters: Vec<WindowIdentifier<'static>>,
+    addresses: Mutex<HashSet<Address>>,
+}
+
+impl TryFrom<Vec<String>> for IgnoreList {
+    type Error = String;
+
+    fn try_from(patterns: Vec<String>) -> Result<Self, Self::Error> {
+        let filters = patterns
+            .iter()
+            .map(|p| ParsedWindowIdentifier::from_str(p).map(|id| id.0))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { filters, addresses: Mutex::new(HashSet::new()) })
+    }
+}
+
+/// Just the `ignore` list of a config file.
+#[derive(Deserialize)]
+struct IgnoreConfig {
+    #[serde(default)]
+    ignore: IgnoreList,
+}
+
+impl IgnoreList {
+    /// The ignore list of the global config, or an empty list if there is none.
+    pub fn from_global_config() -> Self {
+        service::get_config_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str::<IgnoreConfig>(&content).ok())
+            .map(|config| config.ignore)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_ignored(&self, class: &str, title: &str) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| is_window_match(Some(filter), class, title))
+    }
+
+    pub fn is_client_ignored(&self, client: &Client) -> bool {
+        self.is_ignored(&client.class, &client.title)
+            || self
+                .filters
+                .iter()
+                .any(|filter| match filter {
+                    WindowIdentifier::Address(address) => *address == client.address,
+                    WindowIdentifier::ProcessId(pid) => client.pid == *pid as i32,
+                    _ => false,
+                })
+    }
+
+    pub fn is_address_ignored(&self, address: &Address) -> bool {
+        self.addresses
+            .lock()
+            .unwrap()
+            .contains(address)
+    }
+
+    /// Remember `address` if the window opening there is ignored.
+    pub fn track(&self, address: &Address, class: &str, title: &str) {
+        if self.is_ignored(class, title) {
+            self.addresses
+                .lock()
+                .unwrap()
+                .insert(address.clone());
+        }
+    }
+
+    pub fn forget(&self, address: &Address) {
+        self.addresses
+            .lock()
+            .unwrap()
+            .remove(address);
+    }
+
+    /// Pick up ignored windows that were already open before the daemon started.
+    pub fn track_existing(&self) {
+        let Ok(clients) = Clients::get() else {
+            return;
+        };
+        let mut addresses = self.addresses.lock().unwrap();
+        for client in clients.iter() {
+            if self.is_client_ignored(client) {
+                addresses.insert(client.address.clone());
+            }
+        }
+    }
+}
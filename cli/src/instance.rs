@@ -0,0 +1,60 @@
+//! Hyprland instance identification for `instance = "..."` reaction scoping.
+//!
+//! A daemon talks to the instance named by `HYPRLAND_INSTANCE_SIGNATURE`. Reactions scoped to a
+//! different instance are not registered, so the same config can be shared between a main session
+//! and nested development sessions.
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+/// The instance this process is connected to.
+pub fn current_signature() -> Option<String> {
+    env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()
+}
+
+/// The longest-running instance, i.e. the one a nested session was started from.
+pub fn primary_signature() -> Option<String> {
+    let mut dir = PathBuf::from(env::var("XDG_RUNTIME_DIR").ok()?);
+    dir.push("hypr");
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .path()
+                .join(".socket2.sock")
+                .exists()
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .min_by_key(|signature| start_time(signature))
+}
+
+/// Signatures look like `<commit>_<unix time>_<random>`.
+fn signature_time(signature: &str) -> Option<u64> {
+    signature
+        .split('_')
+        .nth(1)
+        .and_then(|time| time.parse().ok())
+}
+
+fn start_time(signature: &str) -> u64 {
+    signature_time(signature).unwrap_or(u64::MAX)
+}
+
+/// When the instance this process is connected to started, in unix seconds.
+pub fn started_at() -> Option<u64> {
+    current_signature().and_then(|signature| signature_time(&signature))
+}
+
+/// Whether `scope` refers to the instance this process is connected to. `primary` and `nested`
+/// are resolved against the running instances; anything else is a signature prefix.
+pub fn matches(scope: &str) -> bool {
+    let Some(current) = current_signature() else {
+        return false;
+    };
+    match scope {
+        "primary" => primary_signature().is_none_or(|primary| primary == current),
+        "nested" => primary_signature().is_some_and(|primary| primary != current),
+        prefix => current.starts_with(prefix),
+    }
+}
@@ -0,0 +1,81 @@
+//! On-disk journal of in-progress dispatcher chains.
+//!
+//! A journaled reaction records which dispatcher it is about to run before each step, along with
+//! the event's placeholder values, and removes the record once the chain completes. A dispatcher
+//! that fails keeps the record on it. Records left behind after a crash are picked up by the
+//! [`ReactionManager`](crate::reaction_handler::ReactionManager) on its next start.
+
+use hyde_ipc_lib::service;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// What to do with a chain that was interrupted by a daemon crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JournalMode {
+    /// Print which dispatchers never ran.
+    Report,
+    /// Run the remaining dispatchers, starting with the one that was interrupted.
+    Resume,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub reaction: String,
+    /// Index of the dispatcher that was running (or about to run) when the record was written.
+    pub next: usize,
+    pub total: usize,
+    /// The event's placeholder values.
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+fn journal_dir() -> Result<PathBuf, String> {
+    let mut path = service::get_state_dir().map_err(|e| e.to_string())?;
+    path.push("journal");
+    Ok(path)
+}
+
+fn entry_path(reaction: &str) -> Result<PathBuf, String> {
+    let file_name: String = reaction
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let mut path = journal_dir()?;
+    path.push(format!("{file_name}.toml"));
+    Ok(path)
+}
+
+pub fn record(entry: &JournalEntry) -> Result<(), String> {
+    let path = entry_path(&entry.reaction)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create journal dir: {e}"))?;
+    }
+    let content = toml::to_string(entry).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write journal: {e}"))
+}
+
+pub fn clear(reaction: &str) -> Result<(), String> {
+    let path = entry_path(reaction)?;
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear journal: {e}")),
+    }
+}
+
+/// All chains that were left unfinished by a previous run.
+pub fn pending() -> Vec<JournalEntry> {
+    let Ok(dir) = journal_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|content| toml::from_str(&content).ok())
+        .collect()
+}
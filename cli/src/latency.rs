@@ -0,0 +1,72 @@
+//! Latency tracing for reactions (`react --trace-latency`).
+//!
+//! Each executed reaction records the time from the event being read off the socket to its last
+//! dispatcher finishing. Percentiles per reaction are logged every [`REPORT_INTERVAL`].
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Tracer {
+    samples: BTreeMap<String, Vec<Duration>>,
+    last_report: Instant,
+}
+
+static TRACER: OnceLock<Mutex<Tracer>> = OnceLock::new();
+
+pub fn enable() {
+    let _ =
+        TRACER.set(Mutex::new(Tracer { samples: BTreeMap::new(), last_report: Instant::now() }));
+    println!("Tracing reaction latency, reporting every {}s", REPORT_INTERVAL.as_secs());
+}
+
+pub fn is_enabled() -> bool {
+    TRACER.get().is_some()
+}
+
+/// Record one reaction run. `received` is when its event was read from the socket.
+pub fn record(reaction: &str, received: Instant) {
+    let Some(tracer) = TRACER.get() else {
+        return;
+    };
+    let elapsed = received.elapsed();
+    let mut tracer = tracer.lock().unwrap();
+    tracer
+        .samples
+        .entry(reaction.to_string())
+        .or_default()
+        .push(elapsed);
+    if tracer.last_report.elapsed() >= REPORT_INTERVAL {
+        report(&mut tracer.samples);
+        tracer.last_report = Instant::now();
+    }
+}
+
+fn report(samples: &mut BTreeMap<String, Vec<Duration>>) {
+    println!("Reaction latency (event read to last dispatcher done):");
+    for (reaction, durations) in samples.iter_mut() {
+        durations.sort_unstable();
+        println!(
+            "  {reaction}: n={} p50={:?} p95={:?} max={:?}",
+            durations.len(),
+            percentile(durations, 50),
+            percentile(durations, 95),
+            durations
+                .last()
+                .copied()
+                .unwrap_or_default()
+        );
+    }
+    samples.clear();
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
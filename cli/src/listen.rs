@@ -1,169 +1,856 @@
-use hyprland::event_listener::EventListener;
-use std::sync::Arc;
+use crate::broadcast::Broadcaster;
+use crate::clients::{self, pattern_matches};
+use crate::event_db::{EventDb, StoredEvent};
+use crate::flags::{OutputFormat, TimestampFormat};
+use crate::history::{self, now_millis};
+use crate::output::{Printer, Record};
+use hyprland::data::Monitors;
+use hyprland::event_listener::{EventListener, is_twin_of_parsed_event};
+use hyprland::prelude::*;
+use hyprland::shared::Address;
+use serde_json::{Map, Value, json};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use std::{io, thread};
 
-pub fn listen(filter: Option<String>, max_events: usize) -> hyprland::Result<()> {
-    println!("Listening for Hyprland events...");
-    println!("Press Ctrl+C to stop");
+/// Conditions on an event's subtype and payload. Events without the field a condition looks at are
+/// skipped.
+#[derive(Debug, Clone, Default)]
+pub struct PayloadFilter {
+    /// Exact subtype, e.g. `opened`.
+    pub subtype: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub workspace: Option<String>,
+}
+
+impl PayloadFilter {
+    pub fn matches(&self, event: &str, change: &str, data: &Value) -> bool {
+        if self
+            .subtype
+            .as_deref()
+            .is_some_and(|subtype| subtype != change)
+        {
+            return false;
+        }
+        let field = |name: &str| -> Option<String> {
+            match data.get(name)? {
+                Value::String(s) => Some(s.clone()),
+                Value::Null => None,
+                other => Some(other.to_string()),
+            }
+        };
+        let text_matches = |name: &str, pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .is_none_or(|pattern| {
+                    field(name).is_some_and(|text| pattern_matches(&text, pattern))
+                })
+        };
+        // Workspace events carry the workspace itself as `name` and `id`.
+        let workspace_fields: &[&str] =
+            if event == "workspace" { &["name", "id"] } else { &["workspace", "workspace_id"] };
+        text_matches("class", &self.class)
+            && text_matches("title", &self.title)
+            && self
+                .workspace
+                .as_deref()
+                .is_none_or(|workspace| {
+                    workspace_fields
+                        .iter()
+                        .any(|name| field(name).as_deref() == Some(workspace))
+                })
+    }
+}
+
+/// How much of the event stream gets through, and for how long.
+#[derive(Debug, Clone, Default)]
+pub struct Pacing {
+    /// Stop after this many events, 0 for no limit.
+    pub max_events: usize,
+    pub timeout: Option<Duration>,
+    /// Exit status once `timeout` runs out.
+    pub timeout_status: i32,
+    /// Hold events back until none of the same type and subtype came for this long, then print
+    /// only the last one.
+    pub debounce: Option<Duration>,
+    /// Skip an event identical to the one printed just before it.
+    pub dedup: bool,
+}
+
+/// What gets added to the stream: fields on each event, and events from before it started.
+#[derive(Debug, Default)]
+pub struct Extras {
+    pub timestamps: Option<TimestampFormat>,
+    /// Look up the window of events that carry an address and add its pid, workspace, monitor and
+    /// floating state.
+    pub enrich: bool,
+    pub backfill: Backfill,
+    /// Put the payload fields next to `type` and `subtype` instead of under `data`. CSV output is
+    /// always flat.
+    pub flat: bool,
+}
+
+/// Recorded events printed before the live ones, for `--history`.
+#[derive(Debug, Default)]
+pub struct Backfill {
+    /// Recorded events that get through the filters, newest first.
+    pub events: Vec<StoredEvent>,
+}
+
+/// Where events that get through go.
+pub enum Output {
+    Print(OutputFormat),
+    /// Hand each event to another thread, like the `--tui` monitor.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    Channel(Sender<Record>),
+    /// Only count them, for `max_events`.
+    Discard,
+}
+
+pub fn listen(
+    filter: Option<String>,
+    payload: PayloadFilter,
+    db: Option<EventDb>,
+    broadcaster: Option<Broadcaster>,
+    pacing: Pacing,
+    Extras { timestamps, enrich, backfill, flat }: Extras,
+    output: Output,
+) -> hyprland::Result<()> {
+    let flat = flat || matches!(output, Output::Print(OutputFormat::Csv));
+    let sink = match output {
+        Output::Print(format) => Sink::Printer(Arc::new(Mutex::new(
+            Printer::streaming(format).with_csv_columns(
+                CSV_COLUMNS
+                    .iter()
+                    .filter(|&&column| column != "time" || timestamps.is_some())
+                    .map(|column| column.to_string())
+                    .collect(),
+            ),
+        ))),
+        Output::Channel(sender) => Sink::Channel(sender),
+        Output::Discard => Sink::Discard,
+    };
+
+    if let Some(timeout) = pacing.timeout {
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            // Holding stdout waits for an event being printed, so the last line is complete.
+            let _stdout = io::stdout().lock();
+            std::process::exit(pacing.timeout_status);
+        });
+    }
 
     let mut event_listener = EventListener::new();
-    let count = Arc::new(AtomicUsize::new(0));
+    let mut log = EventLog {
+        filter,
+        payload: Arc::new(payload),
+        db: db.map(|db| Arc::new(Mutex::new(db))),
+        broadcaster,
+        max_events: pacing.max_events,
+        count: Arc::new(AtomicUsize::new(0)),
+        sink,
+        last: pacing
+            .dedup
+            .then(|| Arc::new(Mutex::new(None))),
+        debounce: None,
+        timestamps: timestamps.map(|format| (format, Instant::now())),
+        enrich,
+        flat,
+    };
+    log.replay(backfill);
+    if let Some(delay) = pacing.debounce {
+        log.debounce = Some(Debounce::start(delay, log.clone()));
+    }
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_active_window_changed_handler(move |data| {
-        if should_log_event("window", &filter_clone) {
-            println!("[WINDOW] Active window changed - {data:?}");
-            increment_count(&count_clone, max_events);
-        }
+        let value = data.as_ref().map_or(
+            Value::Null,
+            |d| json!({ "class": d.class, "title": d.title, "address": d.address.to_string() }),
+        );
+        log_clone.log(
+            "window",
+            "active",
+            value,
+            format!("[WINDOW] Active window changed - {data:?}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_window_opened_handler(move |data| {
-        if should_log_event("window", &filter_clone) {
-            println!("[WINDOW] Window opened - {data:?}");
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "window",
+            "opened",
+            json!({
+                "address": data.window_address.to_string(),
+                "workspace": data.workspace_name,
+                "class": data.window_class,
+                "title": data.window_title,
+            }),
+            format!("[WINDOW] Window opened - {data:?}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_window_closed_handler(move |data| {
-        if should_log_event("window", &filter_clone) {
-            println!("[WINDOW] Window closed - {data:?}");
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "window",
+            "closed",
+            json!({ "address": data.to_string() }),
+            format!("[WINDOW] Window closed - {data:?}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_window_moved_handler(move |data| {
-        if should_log_event("window", &filter_clone) {
-            println!(
+        log_clone.log(
+            "window",
+            "moved",
+            json!({
+                "address": data.window_address.to_string(),
+                "workspace": data.workspace_name.to_string(),
+                "workspace_id": data.workspace_id,
+            }),
+            format!(
                 "[WINDOW] Window moved - workspace: {}, address: {}",
                 data.workspace_name, data.window_address
-            );
-            increment_count(&count_clone, max_events);
-        }
+            ),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_float_state_changed_handler(move |data| {
-        if should_log_event("float", &filter_clone) {
-            println!(
+        log_clone.log(
+            "float",
+            "changed",
+            json!({ "address": data.address.to_string(), "floating": data.floating }),
+            format!(
                 "[FLOAT] Float state changed - address: {}, floating: {}",
                 data.address, data.floating
-            );
-            increment_count(&count_clone, max_events);
-        }
+            ),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_fullscreen_state_changed_handler(move |state| {
-        if should_log_event("fullscreen", &filter_clone) {
-            let state_str = if state { "enabled" } else { "disabled" };
-            println!("[FULLSCREEN] Fullscreen {state_str}");
-            increment_count(&count_clone, max_events);
-        }
+        let state_str = if state { "enabled" } else { "disabled" };
+        log_clone.log(
+            "fullscreen",
+            "changed",
+            json!({ "fullscreen": state }),
+            format!("[FULLSCREEN] Fullscreen {state_str}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_workspace_changed_handler(move |id| {
-        if should_log_event("workspace", &filter_clone) {
-            println!("[WORKSPACE] Changed workspace - {id:?}");
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "workspace",
+            "changed",
+            json!({ "name": id.name.to_string(), "id": id.id }),
+            format!("[WORKSPACE] Changed workspace - {id:?}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_workspace_added_handler(move |data| {
-        if should_log_event("workspace", &filter_clone) {
-            println!("[WORKSPACE] Workspace added - name: {}, id: {}", data.name, data.id);
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "workspace",
+            "added",
+            json!({ "name": data.name.to_string(), "id": data.id }),
+            format!("[WORKSPACE] Workspace added - name: {}, id: {}", data.name, data.id),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_workspace_deleted_handler(move |data| {
-        if should_log_event("workspace", &filter_clone) {
-            println!("[WORKSPACE] Workspace deleted - name: {}, id: {}", data.name, data.id);
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "workspace",
+            "deleted",
+            json!({ "name": data.name.to_string(), "id": data.id }),
+            format!("[WORKSPACE] Workspace deleted - name: {}, id: {}", data.name, data.id),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_active_monitor_changed_handler(move |data| {
-        if should_log_event("monitor", &filter_clone) {
-            println!(
+        log_clone.log(
+            "monitor",
+            "active",
+            json!({
+                "monitor": data.monitor_name,
+                "workspace": data.workspace_name.as_ref().map(ToString::to_string),
+            }),
+            format!(
                 "[MONITOR] Active monitor changed - monitor: {}, workspace: {:?}",
                 data.monitor_name, data.workspace_name
-            );
-            increment_count(&count_clone, max_events);
-        }
+            ),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_layout_changed_handler(move |data| {
-        if should_log_event("layout", &filter_clone) {
-            println!(
+        log_clone.log(
+            "layout",
+            "changed",
+            json!({ "keyboard": data.keyboard_name, "layout": data.layout_name }),
+            format!(
                 "[LAYOUT] Layout changed - keyboard: {}, layout: {}",
                 data.keyboard_name, data.layout_name
-            );
-            increment_count(&count_clone, max_events);
-        }
+            ),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_group_toggled_handler(move |data| {
-        if should_log_event("group", &filter_clone) {
-            println!(
+        let addresses: Vec<String> = data
+            .window_addresses
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        log_clone.log(
+            "group",
+            "toggled",
+            json!({ "toggled": data.toggled, "windows": addresses }),
+            format!(
                 "[GROUP] Group toggled - toggled: {}, window count: {}",
                 data.toggled,
                 data.window_addresses.len()
-            );
-            increment_count(&count_clone, max_events);
-        }
+            ),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_window_moved_into_group_handler(move |addr| {
-        if should_log_event("group", &filter_clone) {
-            println!("[GROUP] Window moved into group - address: {addr}");
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log(
+            "group",
+            "moved-in",
+            json!({ "address": addr.to_string() }),
+            format!("[GROUP] Window moved into group - address: {addr}"),
+        );
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_window_moved_out_of_group_handler(move |addr| {
-        if should_log_event("group", &filter_clone) {
-            println!("[GROUP] Window moved out of group - address: {addr}");
-            increment_count(&count_clone, max_events);
+        log_clone.log(
+            "group",
+            "moved-out",
+            json!({ "address": addr.to_string() }),
+            format!("[GROUP] Window moved out of group - address: {addr}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_window_title_changed_handler(move |data| {
+        log_clone.log(
+            "window",
+            "title",
+            json!({ "address": data.address.to_string(), "title": data.title }),
+            format!("[WINDOW] Title changed - address: {}, title: {}", data.address, data.title),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_urgent_state_changed_handler(move |addr| {
+        log_clone.log(
+            "window",
+            "urgent",
+            json!({ "address": addr.to_string() }),
+            format!("[WINDOW] Window urgent - address: {addr}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_window_pinned_handler(move |data| {
+        log_clone.log(
+            "window",
+            "pinned",
+            json!({ "address": data.address.to_string(), "pinned": data.pinned }),
+            format!(
+                "[WINDOW] Pin state changed - address: {}, pinned: {}",
+                data.address, data.pinned
+            ),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_screencast_handler(move |data| {
+        let owner = if data.monitor { "monitor" } else { "window" };
+        log_clone.log(
+            "screencast",
+            "changed",
+            json!({ "active": data.turning_on, "owner": owner }),
+            format!("[SCREENCAST] Screencast active: {}, owner: {owner}", data.turning_on),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_workspace_moved_handler(move |data| {
+        log_clone.log(
+            "workspace",
+            "moved",
+            json!({ "name": data.name.to_string(), "id": data.id, "monitor": data.monitor }),
+            format!(
+                "[WORKSPACE] Workspace moved - name: {}, id: {}, monitor: {}",
+                data.name, data.id, data.monitor
+            ),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_workspace_renamed_handler(move |data| {
+        log_clone.log(
+            "workspace",
+            "renamed",
+            json!({ "name": data.name, "id": data.id }),
+            format!("[WORKSPACE] Workspace renamed - name: {}, id: {}", data.name, data.id),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_changed_special_handler(move |data| {
+        log_clone.log(
+            "special",
+            "changed",
+            json!({ "monitor": data.monitor_name, "workspace": data.workspace_name }),
+            format!(
+                "[SPECIAL] Special workspace changed - monitor: {}, workspace: {}",
+                data.monitor_name, data.workspace_name
+            ),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_special_removed_handler(move |monitor| {
+        log_clone.log(
+            "special",
+            "removed",
+            json!({ "monitor": monitor }),
+            format!("[SPECIAL] Special workspace removed - monitor: {monitor}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_monitor_added_handler(move |data| {
+        log_clone.log(
+            "monitor",
+            "added",
+            json!({ "id": data.id, "monitor": data.name, "description": data.description }),
+            format!("[MONITOR] Monitor added - name: {}, id: {}", data.name, data.id),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_monitor_removed_handler(move |name| {
+        log_clone.log(
+            "monitor",
+            "removed",
+            json!({ "monitor": name }),
+            format!("[MONITOR] Monitor removed - name: {name}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_sub_map_changed_handler(move |submap| {
+        log_clone.log(
+            "submap",
+            "changed",
+            json!({ "submap": submap }),
+            format!("[SUBMAP] Submap changed - {submap:?}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_layer_opened_handler(move |namespace| {
+        log_clone.log(
+            "layer",
+            "opened",
+            json!({ "namespace": namespace }),
+            format!("[LAYER] Layer opened - {namespace}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_layer_closed_handler(move |namespace| {
+        log_clone.log(
+            "layer",
+            "closed",
+            json!({ "namespace": namespace }),
+            format!("[LAYER] Layer closed - {namespace}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_lock_groups_state_changed_handler(move |locked| {
+        log_clone.log(
+            "group",
+            "locked",
+            json!({ "locked": locked }),
+            format!("[GROUP] Lock groups: {locked}"),
+        );
+    });
+
+    let log_clone = log.clone();
+    event_listener.add_ignore_group_lock_state_changed_handler(move |ignored| {
+        log_clone.log(
+            "group",
+            "ignore-lock",
+            json!({ "ignored": ignored }),
+            format!("[GROUP] Ignore group lock: {ignored}"),
+        );
+    });
+
+    // Hyprland events hyprland-rs doesn't parse, like `minimized`, arrive here, along with the
+    // other version of events that come in two, which are already logged.
+    let log_clone = log.clone();
+    event_listener.add_unknown_handler(move |data| {
+        if is_twin_of_parsed_event(&data.name) {
+            return;
+        }
+        if data.name == "minimized" {
+            let (address, state) = data
+                .args
+                .split_once(',')
+                .unwrap_or((&data.args, "0"));
+            let minimized = state == "1";
+            let address = Address::new(address);
+            log_clone.log(
+                "window",
+                "minimized",
+                json!({ "address": address.to_string(), "minimized": minimized }),
+                format!(
+                    "[WINDOW] Minimize state changed - address: {address}, minimized: {minimized}"
+                ),
+            );
+        } else {
+            log_clone.log(
+                "unknown",
+                &data.name,
+                json!({ "args": data.args }),
+                format!("[UNKNOWN] {}>>{}", data.name, data.args),
+            );
         }
     });
 
-    let count_clone = Arc::clone(&count);
-    let filter_clone = filter.clone();
+    let log_clone = log.clone();
     event_listener.add_config_reloaded_handler(move || {
-        if should_log_event("config", &filter_clone) {
-            println!("[CONFIG] Config reloaded");
-            increment_count(&count_clone, max_events);
-        }
+        log_clone.log("config", "reloaded", Value::Null, "[CONFIG] Config reloaded".to_string());
     });
 
     event_listener.start_listener()
 }
 
+/// Shared state of the event handlers: the filters, the event budget and the output printer.
+/// Structured output is one object per event with its `type` and `subtype`, and the payload
+/// either under `data` or, when flat, as fields of its own.
+#[derive(Clone)]
+struct EventLog {
+    filter: Option<String>,
+    payload: Arc<PayloadFilter>,
+    db: Option<Arc<Mutex<EventDb>>>,
+    broadcaster: Option<Broadcaster>,
+    max_events: usize,
+    count: Arc<AtomicUsize>,
+    sink: Sink,
+    /// The last event printed, kept with `--dedup`.
+    last: Option<Arc<Mutex<Option<Value>>>>,
+    debounce: Option<Arc<Debounce>>,
+    /// How to stamp events, and when listening started for `mono`.
+    timestamps: Option<(TimestampFormat, Instant)>,
+    enrich: bool,
+    flat: bool,
+}
+
+impl EventLog {
+    fn log(&self, event: &str, change: &str, mut data: Value, mut plain: String) {
+        if !should_log_event(event, &self.filter) {
+            return;
+        }
+        // Enrich before the payload filter, so `--workspace` also matches events that only
+        // carried an address.
+        if self.enrich && change != "closed" {
+            if let Some(summary) = enrich(&mut data) {
+                plain = format!("{plain} [{summary}]");
+            }
+        }
+        if !self
+            .payload
+            .matches(event, change, &data)
+        {
+            return;
+        }
+        let time = self
+            .timestamps
+            .map(|(format, started)| match format {
+                TimestampFormat::Rfc3339 => Value::String(history::format_rfc3339(now_millis())),
+                TimestampFormat::Mono => (started.elapsed().as_millis() as u64).into(),
+            });
+        let pending = Pending {
+            event: event.to_string(),
+            change: change.to_string(),
+            data,
+            plain,
+            time,
+            replayed: false,
+        };
+        match &self.debounce {
+            Some(debounce) => debounce.push(pending),
+            None => self.emit(pending),
+        }
+    }
+
+    /// Print the recorded events, oldest first. They are not enriched, since their windows may be
+    /// long gone, and skip the database, the broadcast and `--max-events`.
+    fn replay(&self, Backfill { events }: Backfill) {
+        for stored in events.into_iter().rev() {
+            let time = self
+                .timestamps
+                .map(|(format, started)| match format {
+                    TimestampFormat::Rfc3339 => Value::String(history::format_rfc3339(stored.time)),
+                    // Before listening started, so negative.
+                    TimestampFormat::Mono => {
+                        (stored.time - now_millis() + started.elapsed().as_millis() as i64).into()
+                    },
+                });
+            let plain = format!(
+                "[{}] {} (recorded) - {}",
+                stored.event.to_uppercase(),
+                stored.subtype,
+                stored.data
+            );
+            self.emit(Pending {
+                event: stored.event,
+                change: stored.subtype,
+                data: stored.data,
+                plain,
+                time,
+                replayed: true,
+            });
+        }
+    }
+
+    /// Store, broadcast and print an event that made it through the filters.
+    fn emit(&self, Pending { event, change, data, plain, time, replayed }: Pending) {
+        let (event, change) = (event.as_str(), change.as_str());
+        if let Some(last) = &self.last {
+            let mut last = last.lock().unwrap();
+            let value = json!({ "type": event, "subtype": change, "data": data });
+            if last.as_ref() == Some(&value) {
+                return;
+            }
+            *last = Some(value);
+        }
+        if let Some(db) = self.db.as_ref().filter(|_| !replayed) {
+            if let Err(e) = db
+                .lock()
+                .unwrap()
+                .insert(event, change, &data)
+            {
+                eprintln!("Error: {e}");
+            }
+        }
+        let mut value = Map::new();
+        match data {
+            Value::Object(fields) if self.flat => value.extend(fields),
+            data => {
+                value.insert("data".to_string(), data);
+            },
+        }
+        // The envelope goes in after the payload, so a payload field can't replace it.
+        value.insert("type".to_string(), event.into());
+        value.insert("subtype".to_string(), change.into());
+        let plain = match &time {
+            Some(Value::String(time)) => format!("{time} {plain}"),
+            Some(time) => format!("{time} {plain}"),
+            None => plain,
+        };
+        if let Some(time) = time {
+            value.insert("time".to_string(), time);
+        }
+        let mut record = Record { value: Value::Object(value), plain };
+        if let Some(broadcaster) = self
+            .broadcaster
+            .as_ref()
+            .filter(|_| !replayed)
+        {
+            broadcaster.send(&record.value.to_string());
+        }
+        match &self.sink {
+            Sink::Printer(printer) => {
+                let mut printer = printer.lock().unwrap();
+                if printer.format() == OutputFormat::Csv {
+                    record.value = csv_row(record.value);
+                }
+                if let Err(e) = printer.print(&record) {
+                    eprintln!("Error: {e}");
+                }
+            },
+            // The receiver only goes away when the process is exiting.
+            Sink::Channel(sender) => {
+                let _ = sender.send(record);
+            },
+            Sink::Discard => {},
+        }
+        // Recorded events are extra, the budget is for live ones.
+        if !replayed {
+            increment_count(&self.count, self.max_events);
+        }
+    }
+}
+
+/// Add what a clients query knows about the event's window to its payload, keeping the fields the
+/// event already had, and return a summary for plain output. Windows that are gone by the time of
+/// the query are left as they are.
+fn enrich(data: &mut Value) -> Option<String> {
+    let address = data.get("address")?.as_str()?;
+    let client = clients::find_client_by_address(&Address::new(address)).ok()?;
+    let monitor = client.monitor.and_then(|id| {
+        Monitors::get()
+            .ok()?
+            .into_iter()
+            .find(|monitor| monitor.id == id)
+            .map(|monitor| monitor.name)
+    });
+    let summary = format!(
+        "pid: {}, workspace: {}, monitor: {}, floating: {}",
+        client.pid,
+        client.workspace.name,
+        monitor.as_deref().unwrap_or("?"),
+        client.floating
+    );
+    let Value::Object(fields) = data else {
+        return None;
+    };
+    let extra = json!({
+        "class": client.class,
+        "title": client.title,
+        "pid": client.pid,
+        "workspace": client.workspace.name,
+        "workspace_id": client.workspace.id,
+        "monitor": monitor,
+        "floating": client.floating,
+    });
+    if let Value::Object(extra) = extra {
+        for (key, value) in extra {
+            fields.entry(key).or_insert(value);
+        }
+    }
+    Some(summary)
+}
+
+/// [`Output`] as the event handlers share it.
+#[derive(Clone)]
+enum Sink {
+    Printer(Arc<Mutex<Printer>>),
+    Channel(Sender<Record>),
+    Discard,
+}
+
+/// An event waiting to be printed.
+struct Pending {
+    event: String,
+    change: String,
+    data: Value,
+    plain: String,
+    /// When it arrived, with `--timestamps`.
+    time: Option<Value>,
+    /// Recorded before listening started, from `--history`.
+    replayed: bool,
+}
+
+/// CSV columns, the same for every event so a log loads as one table. `time` is only there with
+/// `--timestamps`.
+const CSV_COLUMNS: [&str; 9] = [
+    "time",
+    "type",
+    "subtype",
+    "address",
+    "class",
+    "title",
+    "workspace",
+    "monitor",
+    "data",
+];
+
+/// Move the payload fields without a column of their own into `data`, as JSON.
+fn csv_row(value: Value) -> Value {
+    let Value::Object(mut fields) = value else {
+        return value;
+    };
+    let mut row = Map::new();
+    for column in CSV_COLUMNS {
+        if let Some(field) = fields.remove(column) {
+            row.insert(column.to_string(), field);
+        }
+    }
+    if !fields.is_empty() {
+        row.insert("data".to_string(), Value::String(Value::Object(fields).to_string()));
+    }
+    Value::Object(row)
+}
+
+/// Holds events back per type and subtype until they stop coming, so a burst prints once.
+struct Debounce {
+    delay: Duration,
+    /// Held events with the time they are due, in arrival order.
+    pending: Mutex<Vec<(Instant, Pending)>>,
+    wake: Condvar,
+}
+
+impl Debounce {
+    fn start(delay: Duration, log: EventLog) -> Arc<Self> {
+        let debounce =
+            Arc::new(Self { delay, pending: Mutex::new(Vec::new()), wake: Condvar::new() });
+        let worker = Arc::clone(&debounce);
+        thread::spawn(move || worker.run(&log));
+        debounce
+    }
+
+    /// Hold `event`, replacing an earlier one of the same type and subtype that is still held.
+    fn push(&self, event: Pending) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|(_, held)| (&held.event, &held.change) != (&event.event, &event.change));
+        pending.push((Instant::now() + self.delay, event));
+        self.wake.notify_one();
+    }
+
+    /// Print held events as they come due.
+    fn run(&self, log: &EventLog) {
+        let mut pending = self.pending.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let next_due = pending
+                .iter()
+                .map(|(due, _)| *due)
+                .min();
+            match next_due {
+                None => pending = self.wake.wait(pending).unwrap(),
+                Some(due) if due > now => {
+                    pending = self
+                        .wake
+                        .wait_timeout(pending, due - now)
+                        .unwrap()
+                        .0;
+                },
+                Some(_) => {
+                    let (ready, held) = pending
+                        .drain(..)
+                        .partition(|(due, _)| *due <= now);
+                    *pending = held;
+                    drop(pending);
+                    for (_, event) in ready {
+                        log.emit(event);
+                    }
+                    pending = self.pending.lock().unwrap();
+                },
+            }
+        }
+    }
+}
+
 fn should_log_event(event_type: &str, filter: &Option<String>) -> bool {
     match filter {
         Some(f) if !f.is_empty() => event_type
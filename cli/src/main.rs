@@ -2,33 +2,102 @@
 //!
 //! This module parses CLI arguments and delegates to the appropriate subcommand logic.
 
+mod app_modes;
+mod broadcast;
+mod clients;
+mod completions;
+mod crash;
+mod ctl;
+mod cursor;
+mod cycle;
+mod daemon_lock;
 mod dispatch;
+mod dispatcher_list;
+mod dnd;
+mod dpms;
+mod environment;
+mod event_db;
+mod expose;
+mod failure;
 mod flags;
+mod float_memory;
+mod foreach;
+mod history;
+mod ignore;
+mod instance;
+mod journal;
 mod keyword;
+mod latency;
 mod listen;
+#[cfg(feature = "mock-server")]
+mod mock_server;
+mod notify;
+mod occupancy;
+mod output;
 mod parsers;
+mod placement;
 mod query;
+mod query_cache;
 mod react;
 mod react_config;
 mod reaction_handler;
+mod reaction_state;
+mod reassert;
+mod reload;
+mod report;
+mod sandbox;
+mod scene;
+mod schedule;
+mod send_signal;
+mod session;
+mod signals;
+mod state_file;
+mod status;
+mod suggest;
+mod swap;
+mod template;
+mod tui;
+mod units;
+mod version_check;
+mod wait;
+mod window_cache;
 
+use broadcast::Broadcaster;
 use clap::{CommandFactory, Parser};
-use flags::{Cli, Commands, DispatchCommand};
+use event_db::EventDb;
+use flags::{Cli, Commands, DispatchCommand, OutputFormat};
 use hyde_ipc_lib::service;
+use parsers::ParsedDuration;
+use std::time::Duration;
 use std::{fs, process};
 
 /// Main entry point for the hyde-ipc CLI.
 ///
 /// Parses command-line arguments and dispatches to the appropriate subcommand handler.
 pub fn main() {
-    let cli = Cli::parse();
+    let (args, batch) = dispatch::split_batch(std::env::args().collect());
+    let cli = Cli::parse_from(args);
 
     match cli.command {
-        Commands::Keyword { r#async, get, set, keyword, value } => {
+        Commands::Keyword { r#async, get, set, watch_config_reload, unwatch, keyword, value } => {
             if set && value.is_none() {
                 eprintln!("Error: --set requires a value");
                 print_usage_and_exit();
             }
+            let watched = if unwatch {
+                reassert::unwatch(&keyword)
+            } else if watch_config_reload {
+                reassert::watch(&keyword, value.as_deref().unwrap_or_default())
+            } else {
+                Ok(())
+            };
+            if let Err(e) = watched {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+            if unwatch {
+                return;
+            }
             if r#async {
                 let rt = tokio::runtime::Builder::new_current_thread()
                     .enable_all()
@@ -41,27 +110,297 @@ pub fn main() {
         },
         Commands::Dispatch(dispatch_command) => {
             if dispatch_command.list_dispatchers {
-                print_dispatchers_list();
+                let format = output::resolve(
+                    cli.output,
+                    dispatch_command
+                        .json
+                        .then_some(OutputFormat::Json),
+                );
+                if let Err(e) = dispatcher_list::print(format) {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+                return;
+            }
+
+            let mut options = dispatch::RunOptions::from(&dispatch_command);
+            if dispatch_command.stdin || dispatch_command.file.is_some() {
+                let failed = match &dispatch_command.file {
+                    Some(path) => match fs::File::open(path) {
+                        Ok(file) => dispatch::run_lines(std::io::BufReader::new(file), options),
+                        Err(e) => {
+                            eprintln!("Error: Failed to open {path}: {e}");
+                            process::exit(1);
+                        },
+                    },
+                    None => dispatch::run_lines(std::io::stdin().lock(), options),
+                };
+                if failed > 0 {
+                    process::exit(1);
+                }
                 return;
             }
 
             if let Some(command) = dispatch_command.command {
-                dispatch::handle_dispatch(command, dispatch_command.r#async);
+                let mut commands = vec![command];
+                for args in batch {
+                    let parsed = DispatchCommand::try_parse_from(
+                        std::iter::once("dispatch".to_string()).chain(args),
+                    )
+                    .unwrap_or_else(|e| e.exit());
+                    let Some(command) = parsed.command else {
+                        eprintln!("Error: expected a dispatcher after --then");
+                        process::exit(1);
+                    };
+                    if parsed.r#async
+                        || parsed.list_dispatchers
+                        || parsed.stdin
+                        || parsed.file.is_some()
+                        || parsed.retry_until_success
+                    {
+                        eprintln!(
+                            "Error: dispatch options go before the first dispatcher, not after \
+                             --then"
+                        );
+                        process::exit(1);
+                    }
+                    // --dry-run and --explain may come anywhere and apply to the whole batch.
+                    options.dry_run |= parsed.dry_run;
+                    options.explain |= parsed.explain;
+                    commands.push(command);
+                }
+                if options.dry_run {
+                    for command in &commands {
+                        if let Err(e) = dispatch::dry_run(command) {
+                            eprintln!("Error: {e}");
+                            process::exit(1);
+                        }
+                    }
+                    return;
+                }
+                if options.explain {
+                    for command in &commands {
+                        if let Err(e) = dispatch::explain(command) {
+                            eprintln!("Error: {e}");
+                            process::exit(1);
+                        }
+                    }
+                }
+                if options.retry.is_some() {
+                    // Only retry what Hyprland could accept once it is up, not a typo.
+                    let result = commands
+                        .iter()
+                        .try_for_each(dispatch::validate)
+                        .and_then(|()| {
+                            // Commands go one at a time, and a retry picks up at the one that
+                            // failed, so nothing that already ran is run twice.
+                            let mut next = 0;
+                            dispatch::retrying(options.retry, || {
+                                while let Some(command) = commands.get(next) {
+                                    dispatch::run_dispatch(command.clone(), options.is_async)?;
+                                    next += 1;
+                                }
+                                Ok(())
+                            })
+                        });
+                    if let Err(e) = result {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    }
+                } else if commands.iter().any(dispatch::is_strict) {
+                    let result = if commands.len() == 1 {
+                        dispatch::run_dispatch(commands.remove(0), dispatch_command.r#async)
+                    } else {
+                        dispatch::run_batch(commands, dispatch_command.r#async)
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    }
+                } else if commands.len() == 1 {
+                    dispatch::handle_dispatch(commands.remove(0), dispatch_command.r#async);
+                } else {
+                    dispatch::handle_batch(commands, dispatch_command.r#async);
+                }
             } else {
                 DispatchCommand::command()
                     .print_help()
                     .unwrap();
             }
         },
-        Commands::Listen { filter, max_events } => {
-            if let Err(e) = listen::listen(filter, max_events) {
+        Commands::Listen {
+            filter,
+            max_events,
+            timeout,
+            debounce,
+            dedup,
+            class,
+            title,
+            workspace,
+            db,
+            broadcast,
+            json,
+            ndjson,
+            csv,
+            timestamps,
+            enrich,
+            history,
+            tui,
+        } => {
+            let command_format = match (csv, json || ndjson) {
+                (true, _) => Some(OutputFormat::Csv),
+                (_, true) => Some(OutputFormat::Json),
+                _ => None,
+            };
+            let format = output::resolve(cli.output, command_format);
+            let payload = listen::PayloadFilter { subtype: None, class, title, workspace };
+            let backfill = match history {
+                Some(count) => match history::recent(db.as_deref(), count, filter.as_deref(), |e| {
+                    payload.matches(&e.event, &e.subtype, &e.data)
+                }) {
+                    Ok(events) => listen::Backfill { events },
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    },
+                },
+                None => listen::Backfill::default(),
+            };
+            let db = match db
+                .as_deref()
+                .map(EventDb::open)
+                .transpose()
+            {
+                Ok(db) => db,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                },
+            };
+            let timeout = match timeout
+                .as_deref()
+                .map(str::parse::<ParsedDuration>)
+                .transpose()
+            {
+                Ok(timeout) => timeout.map(|timeout| timeout.0),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                },
+            };
+            let broadcaster = match broadcast
+                .as_deref()
+                .map(Broadcaster::start)
+                .transpose()
+            {
+                Ok(broadcaster) => broadcaster,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                },
+            };
+            let pacing = listen::Pacing {
+                max_events,
+                timeout,
+                timeout_status: 0,
+                debounce: debounce.map(Duration::from_millis),
+                dedup,
+            };
+            let extras =
+                listen::Extras { timestamps, enrich, backfill, flat: csv || json || ndjson };
+            if tui {
+                let listen = move |output| {
+                    listen::listen(filter, payload, db, broadcaster, pacing, extras, output)
+                };
+                if let Err(e) = tui::run(listen) {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+                return;
+            }
+            if format == OutputFormat::Plain {
+                println!("Listening for Hyprland events...");
+                println!("Press Ctrl+C to stop");
+            }
+            let output = listen::Output::Print(format);
+            if let Err(e) = listen::listen(filter, payload, db, broadcaster, pacing, extras, output)
+            {
+                failure::listener_failed(e);
+            }
+        },
+        #[cfg(feature = "mock-server")]
+        Commands::MockServer { script, signature } => {
+            if let Err(e) = mock_server::run(&script, &signature) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Wait { event, subtype, filter, timeout, print, json } => {
+            let format = (print || json)
+                .then(|| output::resolve(cli.output, json.then_some(OutputFormat::Json)));
+            if let Err(e) = wait::run(event, subtype, &filter, timeout.as_deref(), format, json) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::History { db, event, subtype, class, since, until, limit, json } => {
+            let format = output::resolve(cli.output, json.then_some(OutputFormat::Json));
+            let filter = history::Filter { event, subtype, class, since, until, limit };
+            if let Err(e) = history::run(db, filter, format) {
                 eprintln!("Error: {e}");
                 process::exit(1);
             }
         },
-        Commands::React { config, inline: _, event, subtype, filter, dispatch, max_reactions } => {
+        Commands::React {
+            config,
+            inline: _,
+            event,
+            subtype,
+            filter,
+            device,
+            dispatch,
+            max_reactions,
+            trace_latency,
+            once,
+            print_effective_config,
+            check,
+            replace,
+        } => {
+            if check {
+                let path = config.unwrap_or_default();
+                match react_config::ReactConfig::from_file(&path) {
+                    Ok(config) => {
+                        for warning in config.warnings() {
+                            println!("Warning: {warning}");
+                        }
+                        println!("{path} is valid");
+                    },
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    },
+                }
+                return;
+            }
+            if print_effective_config {
+                let path = config.unwrap_or_default();
+                match react_config::ReactConfig::effective_toml(&path) {
+                    Ok(content) => print!("{content}"),
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    },
+                }
+                return;
+            }
+            if trace_latency {
+                latency::enable();
+            }
+            if once {
+                reaction_handler::exit_after_first_reaction();
+            }
             if let Some(config_path) = config {
-                if let Err(e) = react_config::run_from_config(&config_path) {
+                if let Err(e) = react_config::run_from_config(&config_path, replace) {
                     eprintln!("Error running from config: {e}");
                     process::exit(1);
                 }
@@ -80,7 +419,9 @@ pub fn main() {
                     unreachable!();
                 },
             };
-            if let Err(e) = react::sync_react(event, subtype, filter, dispatch, max_reactions) {
+            if let Err(e) =
+                react::sync_react(event, subtype, filter, device, dispatch, max_reactions)
+            {
                 eprintln!("Error: {e}");
                 process::exit(1);
             }
@@ -96,6 +437,8 @@ pub fn main() {
                 service::stop()
             } else if setup_command.restart {
                 service::restart()
+            } else if setup_command.reload {
+                service::reload()
             } else if setup_command.check {
                 service::status()
             } else if setup_command.watch {
@@ -110,7 +453,18 @@ pub fn main() {
                 process::exit(1);
             }
         },
-        Commands::Global { config_path } => {
+        Commands::Global { config_path, dry_run } => {
+            if dry_run {
+                match react_config::ReactConfig::from_file(&config_path) {
+                    Ok(config) => config.print_preview(),
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        process::exit(1);
+                    },
+                }
+                return;
+            }
+
             let dest_path = match service::get_config_path() {
                 Ok(path) => path,
                 Err(e) => {
@@ -145,8 +499,67 @@ pub fn main() {
                 process::exit(1);
             }
         },
+        Commands::Scene { config, action } => {
+            if let Err(e) = scene::run(config, action) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Dnd { action, notifier } => {
+            if let Err(e) = dnd::run(action, notifier, output::resolve(cli.output, None)) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Cycle { action } => {
+            if let Err(e) = cycle::run(action, output::resolve(cli.output, None)) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Ctl { action } => {
+            if let Err(e) = ctl::run(action, output::resolve(cli.output, None)) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Reload { config, renderer } => {
+            if let Err(e) = reload::run(config, renderer) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Hypr { action } => {
+            if let Err(e) = session::run(action) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Cursor { action } => {
+            if let Err(e) = cursor::run(action) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Report { config, file, minutes, no_redact } => {
+            if let Err(e) = report::run(config, file, minutes, !no_redact) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Version { action } => {
+            if let Err(e) = version_check::run(action) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        },
+        Commands::Complete { kind } => completions::print_live_values(kind),
+        Commands::Completions { list_dispatcher_values } => {
+            completions::print_values(list_dispatcher_values);
+        },
         Commands::Query(query_command) => {
-            if let Err(e) = query::run_query(query_command.command) {
+            if let Err(e) = query::run_query(query_command.command, query_command.watch, cli.output)
+            {
                 eprintln!("Error: {e}");
                 process::exit(1);
             }
@@ -158,101 +571,3 @@ fn print_usage_and_exit() {
     Cli::command().print_help().unwrap();
     process::exit(1);
 }
-
-fn print_dispatchers_list() {
-    // FIX: macro it into the dispatcher, or juse use clap stuff!
-    let list = r#"Available dispatchers:
-  Basic commands:
-  exec <command>                                    - Execute a command
-  kill-active-window                                  - Kill the active window
-  exit                                              - Exit Hyprland
-  force-renderer-reload                               - Force the renderer to reload
-
-  Window management:
-  toggle-floating [window]                           - Toggle floating mode for a window
-  toggle-fullscreen <type>                           - Toggle fullscreen mode (Real, Maximize, NoParam)
-  toggle-fake-fullscreen                              - Toggle fake fullscreen for the active window
-  toggle-pseudo                                      - Toggle pseudo tiling for the active window
-  toggle-pin                                         - Pin the active window to all workspaces
-  toggle-opaque                                      - Toggle opacity for the active window
-  center-window                                      - Center the active window
-  bring-active-to-top                                  - Bring the active window to the top of the stack
-
-  Focus control:
-  move-focus <direction>                             - Move focus in a direction (Up, Down, Left, Right)
-  focus-window <window>                              - Focus a specific window
-  focus-monitor <identifier>                         - Focus a specific monitor
-  focus-urgent-or-last                                 - Focus the urgent window or the last one
-  focus-current-or-last                                - Switch focus between current and last window
-
-  Window movement:
-  move-window <direction>                            - Move window in a direction
-  move-active <position>                             - Move the active window to a position
-  move-window-pixel <position> <win>                  - Move a specific window to a position
-  resize-active <position>                           - Resize the active window
-  resize-window-pixel <pos> <win>                     - Resize a specific window
-
-  Workspace management:
-  workspace <workspace>                             - Switch to workspace (number, previous, empty, name:NAME)
-  move-to-workspace <workspace>                       - Move window to workspace
-  move-to-workspace-silent <workspace>                 - Move window to workspace without switching to it
-  rename-workspace <id> <name>                       - Rename a workspace
-
-  Cycling and swapping:
-  cycle-window <direction>                           - Cycle windows (Next, Previous)
-  swap-next <direction>                              - Swap with next window (Next, Previous)
-  swap-window <direction>                            - Swap windows in a direction (Up, Down, Left, Right)
-
-  Cursor control:
-  move-cursor-to-corner <corner>                       - Move cursor to a corner (TopLeft, TopRight, BottomLeft, BottomRight)
-  move-cursor <x> <y>                                - Move cursor to a position
-  set-cursor <theme> <size>                          - Set cursor theme and size
-
-  Monitor management:
-  move-current-workspace-to-monitor <mon>               - Move current workspace to a monitor
-  move-workspace-to-monitor <ws> <mon>                 - Move a workspace to a monitor
-  swap-active-workspaces <mon1> <mon2>                - Swap active workspaces of two monitors
-  toggle-dpms <on/off> [monitor]                     - Toggle DPMS status for monitors
-
-  Layout-specific commands (Dwindle):
-  toggle-split                                       - Toggle the split orientation
-
-  Layout-specific commands (Master):
-  swap-with-master <param>                            - Swap with master window (Master, Child, Auto)
-  focus-master <param>                               - Focus the master window (Master, Auto)
-  add-master                                         - Add a master to the master side
-  remove-master                                      - Remove a master from the master side
-  orientation-left                                   - Set orientation to left
-  orientation-right                                  - Set orientation to right
-  orientation-top                                    - Set orientation to top
-  orientation-bottom                                 - Set orientation to bottom
-  orientation-center                                 - Set orientation to center
-  orientation-next                                   - Cycle to next orientation
-  orientation-prev                                   - Cycle to previous orientation
-
-  Window grouping:
-  toggle-group                                       - Toggle the current window into a group
-  change-group-active <direction>                     - Switch to next window in group (Forward, Back)
-  lock-groups <action>                               - Lock groups (Lock, Unlock, ToggleLock)
-  move-into-group <direction>                         - Move window into group in direction
-  move-out-of-group                                    - Move window out of group
-
-Window identifiers (can be used with toggle-floating, focus-window, etc.):
-  class:REGEX                               - Match window by class regex
-  title:REGEX                               - Match window by title regex
-  pid:PID                                   - Match window by process ID
-  address:ADDR                              - Match window by address (hex value, with or without 0x prefix)
-
-Examples:
-  hypr-rs dispatch exec "kitty"
-  hypr-rs dispatch move-cursor-to-corner TopLeft
-  hypr-rs dispatch workspace 1
-  hypr-rs dispatch --async toggle-fullscreen Maximize
-  hypr-rs dispatch cycle-window Next
-  hypr-rs dispatch move-focus Right
-  hypr-rs dispatch toggle-floating "class:^(Google-chrome)$"
-  hypr-rs dispatch focus-window "title:^(.*Terminal.*)$"
-  hypr-rs dispatch toggle-floating address:5934277460f0
-"#;
-    println!("{list}");
-}
@@ -0,0 +1,168 @@
+//! `hyde-ipc mock-server`: a stand-in for Hyprland's two sockets, for exercising the CLI without a
+//! compositor (in CI containers, or to reproduce a bug report). Needs the `mock-server` feature.
+//!
+//! It serves `.socket.sock` and `.socket2.sock` under `$XDG_RUNTIME_DIR/hypr/<signature>`, so any
+//! hyde-ipc started with `HYPRLAND_INSTANCE_SIGNATURE=<signature>` talks to it. A TOML script says
+//! what to answer and which events to send:
+//!
+//! ```toml
+//! [[reply]]
+//! request = "j/activewindow"       # a trailing `*` matches by prefix
+//! body = '{"class": "kitty", ...}'
+//!
+//! [[event]]
+//! delay = "500ms"                  # after the previous event
+//! line = "openwindow>>55d1e0,3,kitty,~"
+//! ```
+//!
+//! Dispatchers and keywords without a reply of their own get `ok`, anything else `unknown request`.
+//! Every connection to the event socket is sent the whole event list from the start. Requests are
+//! printed as they arrive, so a test can check what the CLI asked for.
+
+use crate::parsers::ParsedDuration;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs, thread};
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    request: String,
+    body: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    #[serde(default)]
+    delay: Option<String>,
+    line: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Script {
+    #[serde(default, rename = "reply")]
+    replies: Vec<Reply>,
+    #[serde(default, rename = "event")]
+    events: Vec<Event>,
+}
+
+impl Script {
+    fn load(path: &str) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid script {path}: {e}"))
+    }
+
+    /// The answer to one request, without any `j/` or `/` flag prefix in the fallbacks.
+    fn answer(&self, request: &str) -> String {
+        let scripted = self
+            .replies
+            .iter()
+            .find(|reply| match reply.request.strip_suffix('*') {
+                Some(prefix) => request.starts_with(prefix),
+                None => request == reply.request,
+            });
+        if let Some(reply) = scripted {
+            return reply.body.clone();
+        }
+        let command = request
+            .split_once('/')
+            .map_or(request, |(_, command)| command);
+        if command.starts_with("dispatch ") || command.starts_with("keyword ") {
+            "ok".to_string()
+        } else {
+            "unknown request".to_string()
+        }
+    }
+
+    fn delays(&self) -> Result<Vec<Duration>, String> {
+        self.events
+            .iter()
+            .map(|event| {
+                event
+                    .delay
+                    .as_deref()
+                    .map_or(Ok(Duration::ZERO), |delay| {
+                        delay
+                            .parse::<ParsedDuration>()
+                            .map(|delay| delay.0)
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Listen on `path`, replacing a socket left behind by a mock server that is gone.
+fn bind(path: &Path) -> Result<UnixListener, String> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("Something is already serving {}", path.display()));
+        }
+        fs::remove_file(path).map_err(|e| format!("Failed to remove stale socket: {e}"))?;
+    }
+    UnixListener::bind(path).map_err(|e| format!("Failed to listen on {}: {e}", path.display()))
+}
+
+fn instance_dir(signature: &str) -> Result<PathBuf, String> {
+    let runtime = env::var("XDG_RUNTIME_DIR").map_err(|_| "XDG_RUNTIME_DIR is not set")?;
+    Ok(PathBuf::from(runtime)
+        .join("hypr")
+        .join(signature))
+}
+
+/// Answer one request. Batches get one reply per command, separated by blank lines.
+fn serve_command(mut stream: UnixStream, script: &Script) -> std::io::Result<()> {
+    let mut buf = [0; 8192];
+    let read = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    println!("> {request}");
+    let reply = match request.strip_prefix("j/[[BATCH]]") {
+        Some(batch) => batch
+            .split(';')
+            .map(|command| script.answer(&format!("/{command}")))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        None => script.answer(&request),
+    };
+    stream.write_all(reply.as_bytes())
+}
+
+fn serve_events(mut stream: UnixStream, script: &Script, delays: &[Duration]) {
+    for (event, delay) in script.events.iter().zip(delays) {
+        thread::sleep(*delay);
+        if writeln!(stream, "{}", event.line).is_err() {
+            return;
+        }
+    }
+    // Keep the connection open like Hyprland does, until the client goes away.
+    let _ = stream.read(&mut [0; 1]);
+}
+
+pub fn run(script: &str, signature: &str) -> Result<(), String> {
+    let script = Arc::new(Script::load(script)?);
+    let delays = Arc::new(script.delays()?);
+    let dir = instance_dir(signature)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let commands = bind(&dir.join(".socket.sock"))?;
+    let events = bind(&dir.join(".socket2.sock"))?;
+    eprintln!("Serving a mock Hyprland in {}", dir.display());
+    println!("export HYPRLAND_INSTANCE_SIGNATURE={signature}");
+
+    let event_script = Arc::clone(&script);
+    thread::spawn(move || {
+        for stream in events.incoming().flatten() {
+            let (script, delays) = (Arc::clone(&event_script), Arc::clone(&delays));
+            thread::spawn(move || serve_events(stream, &script, &delays));
+        }
+    });
+    for stream in commands.incoming() {
+        let result = stream.and_then(|stream| serve_command(stream, &script));
+        if let Err(e) = result {
+            eprintln!("Error serving a request: {e}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! The `notify` dispatcher: desktop notifications through `notify-send`.
+//!
+//! Identical notifications (same summary and body) sent within a few seconds of each other are
+//! coalesced: the first one is replaced in place and gets a count suffix, e.g. `Battery low (×3)`,
+//! instead of stacking copies when several reactions fire for one burst of events. This only
+//! works within one process, so it is the reaction daemon that benefits.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long after a notification an identical one is folded into it.
+const COALESCE_WINDOW: Duration = Duration::from_secs(5);
+
+const URGENCIES: [&str; 3] = ["low", "normal", "critical"];
+
+/// A notification recently shown, keyed by its summary and body.
+struct Recent {
+    /// Id from `notify-send --print-id`, used to replace it.
+    id: Option<u32>,
+    count: usize,
+    last: Instant,
+}
+
+static RECENT: LazyLock<Mutex<HashMap<(String, String), Recent>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn validate_urgency(urgency: Option<&str>) -> Result<(), String> {
+    match urgency {
+        Some(urgency) if !URGENCIES.contains(&urgency) => {
+            Err(format!("Unknown urgency: {urgency}, expected {}", URGENCIES.join(", ")))
+        },
+        _ => Ok(()),
+    }
+}
+
+pub fn send(summary: &str, body: Option<&str>, urgency: Option<&str>) -> Result<(), String> {
+    validate_urgency(urgency)?;
+    let key = (summary.to_string(), body.unwrap_or_default().to_string());
+    let mut recent = RECENT.lock().unwrap();
+    let now = Instant::now();
+    recent.retain(|_, r| now.duration_since(r.last) < COALESCE_WINDOW);
+
+    let (replace_id, count) = match recent.get(&key) {
+        Some(r) => (r.id, r.count + 1),
+        None => (None, 1),
+    };
+    let title = if count > 1 { format!("{summary} (×{count})") } else { summary.to_string() };
+
+    let mut command = Command::new("notify-send");
+    command.arg("--print-id");
+    if let Some(id) = replace_id {
+        command.arg(format!("--replace-id={id}"));
+    }
+    if let Some(urgency) = urgency {
+        command.arg(format!("--urgency={urgency}"));
+    }
+    command.arg("--").arg(&title);
+    if let Some(body) = body {
+        command.arg(body);
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run notify-send: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "notify-send failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    // Older notify-send prints nothing, then repeats are counted but can't replace the first.
+    let id = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()
+        .or(replace_id);
+    recent.insert(key, Recent { id, count, last: now });
+    Ok(())
+}
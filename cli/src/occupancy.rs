@@ -0,0 +1,159 @@
+//! `query workspaces --occupancy-map`: one compact entry per workspace slot, for bar widgets.
+//!
+//! Each entry has the workspace `id`, whether it `exists`, how many `windows` it holds and whether
+//! it is `active` or `urgent`. Slots `1..=count` are always listed so indicators keep their
+//! layout, along with any other numbered workspace that exists. Hyprland only reports urgency as
+//! an event, so `urgent` is only ever set while watching.
+
+use crate::flags::OutputFormat;
+use crate::output::{Printer, Record};
+use hyprland::data::{Clients, Workspace, Workspaces};
+use hyprland::event_listener::EventListener;
+use hyprland::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Serialize)]
+struct Slot {
+    id: i32,
+    exists: bool,
+    windows: u16,
+    active: bool,
+    urgent: bool,
+}
+
+/// The occupancy map, with `urgent` holding the addresses of windows that asked for attention.
+pub fn record(count: i32, urgent: &HashSet<String>) -> Result<Record, String> {
+    let workspaces: BTreeMap<i32, u16> = Workspaces::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|workspace| workspace.id > 0)
+        .map(|workspace| (workspace.id, workspace.windows))
+        .collect();
+    let active = Workspace::get_active()
+        .map(|workspace| workspace.id)
+        .ok();
+    let urgent_ids: HashSet<i32> = if urgent.is_empty() {
+        HashSet::new()
+    } else {
+        Clients::get()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|client| urgent.contains(&client.address.to_string()))
+            .map(|client| client.workspace.id)
+            .collect()
+    };
+
+    let ids: BTreeSet<i32> = (1..=count)
+        .chain(workspaces.keys().copied())
+        .collect();
+    let slots: Vec<Slot> = ids
+        .into_iter()
+        .map(|id| Slot {
+            id,
+            exists: workspaces.contains_key(&id),
+            windows: workspaces
+                .get(&id)
+                .copied()
+                .unwrap_or(0),
+            active: active == Some(id),
+            urgent: urgent_ids.contains(&id),
+        })
+        .collect();
+    let plain: Vec<String> = slots
+        .iter()
+        .map(|slot| {
+            let marker = if slot.active {
+                "*"
+            } else if slot.urgent {
+                "!"
+            } else if slot.windows > 0 {
+                "+"
+            } else {
+                ""
+            };
+            format!("{}{marker}", slot.id)
+        })
+        .collect();
+    Record::new(&slots, plain.join(" "))
+}
+
+/// Print the map again after every event that can change it, skipping unchanged output.
+pub fn watch(count: i32, format: OutputFormat) -> Result<(), String> {
+    let state = Arc::new(Mutex::new(Watch {
+        count,
+        urgent: HashSet::new(),
+        printer: Printer::streaming(format),
+        last: None,
+    }));
+    state.lock().unwrap().refresh();
+
+    let mut event_listener = EventListener::new();
+    macro_rules! refresh_on {
+        ($($add:ident),*) => {$(
+            let state_clone = Arc::clone(&state);
+            event_listener.$add(move |_| state_clone.lock().unwrap().refresh());
+        )*};
+    }
+    refresh_on!(
+        add_workspace_changed_handler,
+        add_workspace_added_handler,
+        add_workspace_deleted_handler,
+        add_window_opened_handler,
+        add_window_moved_handler,
+        add_active_monitor_changed_handler
+    );
+
+    let state_clone = Arc::clone(&state);
+    event_listener.add_urgent_state_changed_handler(move |address| {
+        let mut state = state_clone.lock().unwrap();
+        state.urgent.insert(address.to_string());
+        state.refresh();
+    });
+
+    // Focusing or closing a window settles its urgency.
+    let state_clone = Arc::clone(&state);
+    event_listener.add_active_window_changed_handler(move |data| {
+        let mut state = state_clone.lock().unwrap();
+        if let Some(window) = data {
+            state
+                .urgent
+                .remove(&window.address.to_string());
+        }
+        state.refresh();
+    });
+    let state_clone = Arc::clone(&state);
+    event_listener.add_window_closed_handler(move |address| {
+        let mut state = state_clone.lock().unwrap();
+        state
+            .urgent
+            .remove(&address.to_string());
+        state.refresh();
+    });
+
+    event_listener
+        .start_listener()
+        .map_err(|e| e.to_string())
+}
+
+struct Watch {
+    count: i32,
+    urgent: HashSet<String>,
+    printer: Printer,
+    last: Option<String>,
+}
+
+impl Watch {
+    fn refresh(&mut self) {
+        let rendered = record(self.count, &self.urgent).and_then(|r| self.printer.render(&r));
+        match rendered {
+            Ok(rendered) if self.last.as_ref() != Some(&rendered) => {
+                println!("{rendered}");
+                self.last = Some(rendered);
+            },
+            Ok(_) => {},
+            Err(e) => eprintln!("Error: {e}"),
+        }
+    }
+}
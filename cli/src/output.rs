@@ -0,0 +1,181 @@
+//! Output formats shared by every command that prints data.
+//!
+//! Commands build a [`Record`] (the data as JSON plus its human-readable form) and hand it to a
+//! [`Printer`], which takes care of the selected [`OutputFormat`].
+
+use crate::flags::OutputFormat;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::io::{self, Write};
+use std::process;
+
+/// Pick the format for a command. A command-specific flag wins over the global `--output`.
+pub fn resolve(global: Option<OutputFormat>, command: Option<OutputFormat>) -> OutputFormat {
+    command.or(global).unwrap_or_default()
+}
+
+/// A single piece of output in both machine- and human-readable form.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub value: Value,
+    pub plain: String,
+}
+
+impl Record {
+    pub fn new<T: Serialize>(value: &T, plain: String) -> Result<Self, String> {
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        Ok(Self { value, plain })
+    }
+}
+
+#[derive(Debug)]
+pub struct Printer {
+    format: OutputFormat,
+    /// Streams print one compact record per line and a single CSV header.
+    streaming: bool,
+    csv_header: Option<Vec<String>>,
+    /// Fixed CSV columns, instead of the fields of the first record.
+    csv_columns: Option<Vec<String>>,
+}
+
+impl Printer {
+    pub fn new(format: OutputFormat) -> Self {
+        Self { format, streaming: false, csv_header: None, csv_columns: None }
+    }
+
+    pub fn streaming(format: OutputFormat) -> Self {
+        Self { format, streaming: true, csv_header: None, csv_columns: None }
+    }
+
+    pub fn with_csv_columns(mut self, columns: Vec<String>) -> Self {
+        self.csv_columns = Some(columns);
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn render(&mut self, record: &Record) -> Result<String, String> {
+        match self.format {
+            OutputFormat::Plain => Ok(record.plain.clone()),
+            OutputFormat::Json if self.streaming => {
+                serde_json::to_string(&record.value).map_err(|e| e.to_string())
+            },
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&record.value).map_err(|e| e.to_string())
+            },
+            OutputFormat::Csv => Ok(self.render_csv(&record.value)),
+            OutputFormat::Waybar => {
+                let text = record
+                    .plain
+                    .lines()
+                    .next()
+                    .unwrap_or_default();
+                Ok(json!({ "text": text, "tooltip": record.plain }).to_string())
+            },
+        }
+    }
+
+    pub fn print(&mut self, record: &Record) -> Result<(), String> {
+        let rendered = self.render(record)?;
+        if !self.streaming {
+            println!("{rendered}");
+            return Ok(());
+        }
+        // Streams are read by other programs as records arrive: write each one in a single locked
+        // write and flush it, so a record is never split or held back when stdout is a pipe.
+        let mut stdout = io::stdout().lock();
+        match writeln!(stdout, "{rendered}").and_then(|()| stdout.flush()) {
+            Ok(()) => Ok(()),
+            // The reader is gone, so nobody is left to stream to.
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+            Err(e) => Err(format!("Failed to write output: {e}")),
+        }
+    }
+
+    fn render_csv(&mut self, value: &Value) -> String {
+        let rows = csv_rows(value);
+        let mut lines = Vec::with_capacity(rows.len() + 1);
+        let header = match &self.csv_header {
+            Some(header) if self.streaming => header.clone(),
+            _ => {
+                let header: Vec<String> = self
+                    .csv_columns
+                    .clone()
+                    .or_else(|| {
+                        rows.first()
+                            .map(|row| row.keys().cloned().collect())
+                    })
+                    .unwrap_or_default();
+                lines.push(csv_line(header.iter().map(String::as_str)));
+                header
+            },
+        };
+        for row in &rows {
+            let cells: Vec<String> = header
+                .iter()
+                .map(|key| {
+                    row.get(key)
+                        .map(csv_cell)
+                        .unwrap_or_default()
+                })
+                .collect();
+            lines.push(csv_line(cells.iter().map(String::as_str)));
+        }
+        self.csv_header = Some(header);
+        lines.join("\n")
+    }
+}
+
+/// Flatten a value into table rows. Lists become one row per element, and a map of lists (the
+/// shape of grouped output) gets a leading `group` column.
+fn csv_rows(value: &Value) -> Vec<Map<String, Value>> {
+    match value {
+        Value::Array(items) => items.iter().map(as_row).collect(),
+        Value::Object(map) if !map.is_empty() && map.values().all(Value::is_array) => map
+            .iter()
+            .flat_map(|(group, items)| {
+                items
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(move |item| {
+                        let mut row = Map::new();
+                        row.insert("group".to_string(), Value::String(group.clone()));
+                        row.extend(as_row(item));
+                        row
+                    })
+            })
+            .collect(),
+        other => vec![as_row(other)],
+    }
+}
+
+fn as_row(value: &Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map.clone(),
+        other => Map::from_iter([("value".to_string(), other.clone())]),
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_line<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
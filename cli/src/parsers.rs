@@ -1,10 +1,35 @@
+use hyprland::ctl::switch_xkb_layout::SwitchXKBLayoutCmdTypes;
 use hyprland::dispatch::{
-    Corner, CycleDirection, Direction, FullscreenType, MonitorIdentifier, WindowIdentifier,
-    WindowMove, WorkspaceIdentifierWithSpecial,
+    Corner, CycleDirection, Direction, FocusMasterParam, FullscreenType, MonitorIdentifier,
+    SwapWithMasterParam, WindowIdentifier, WindowMove, WindowSwitchDirection,
+    WorkspaceIdentifierWithSpecial,
 };
 use hyprland::shared::Address;
 use phf::phf_map;
 use std::str::FromStr;
+use std::time::Duration;
+
+fn sorted_keys<V>(map: &phf::Map<&'static str, V>) -> Vec<&'static str> {
+    let mut keys: Vec<_> = map.keys().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+pub fn direction_names() -> Vec<&'static str> {
+    sorted_keys(&DIRECTIONS)
+}
+
+pub fn corner_names() -> Vec<&'static str> {
+    sorted_keys(&CORNERS)
+}
+
+pub fn fullscreen_type_names() -> Vec<&'static str> {
+    sorted_keys(&FULLSCREEN_TYPES)
+}
+
+pub fn cycle_direction_names() -> Vec<&'static str> {
+    sorted_keys(&CYCLE_DIRECTIONS)
+}
 
 #[derive(Debug, Clone)]
 pub struct ParsedWindowIdentifier(pub WindowIdentifier<'static>);
@@ -32,6 +57,18 @@ impl FromStr for ParsedWindowIdentifier {
     }
 }
 
+/// Workspace targets Hyprland resolves itself. `back` goes back and forth like `previous`.
+static WORKSPACE_TARGETS: phf::Map<&'static str, WorkspaceIdentifierWithSpecial<'static>> = phf_map! {
+    "previous" => WorkspaceIdentifierWithSpecial::Previous,
+    "back" => WorkspaceIdentifierWithSpecial::Previous,
+    "previous_per_monitor" => WorkspaceIdentifierWithSpecial::PreviousPerMonitor,
+    "empty" => WorkspaceIdentifierWithSpecial::Empty,
+    "emptym" => WorkspaceIdentifierWithSpecial::EmptyOnMonitor,
+    "emptyn" => WorkspaceIdentifierWithSpecial::EmptyNext,
+    "emptynm" => WorkspaceIdentifierWithSpecial::EmptyNextOnMonitor,
+    "emptymn" => WorkspaceIdentifierWithSpecial::EmptyNextOnMonitor,
+};
+
 #[derive(Debug, Clone)]
 pub struct ParsedWorkspaceIdentifier(pub WorkspaceIdentifierWithSpecial<'static>);
 impl FromStr for ParsedWorkspaceIdentifier {
@@ -54,13 +91,19 @@ impl FromStr for ParsedWorkspaceIdentifier {
                 .parse::<i32>()
                 .map_err(|_| format!("Invalid number for left: {num_str}"))?;
             Ok(Self(WorkspaceIdentifierWithSpecial::Relative(-num)))
-        } else if s == "previous" {
-            Ok(Self(WorkspaceIdentifierWithSpecial::Previous))
-        } else if s == "empty" {
-            Ok(Self(WorkspaceIdentifierWithSpecial::Empty))
+        } else if let Some(target) = WORKSPACE_TARGETS.get(s) {
+            Ok(Self(*target))
         } else if let Some(name) = s.strip_prefix("name:") {
             let name_static = Box::leak(name.to_string().into_boxed_str());
             Ok(Self(WorkspaceIdentifierWithSpecial::Name(name_static)))
+        } else if s == "special" {
+            Ok(Self(WorkspaceIdentifierWithSpecial::Special(None)))
+        } else if let Some(name) = s.strip_prefix("special:") {
+            if name.is_empty() {
+                return Err("Missing special workspace name after special:".to_string());
+            }
+            let name_static = Box::leak(name.to_string().into_boxed_str());
+            Ok(Self(WorkspaceIdentifierWithSpecial::Special(Some(name_static))))
         } else {
             Err(format!("Unknown workspace identifier: {s}"))
         }
@@ -175,3 +218,122 @@ impl FromStr for ParsedCycleDirection {
             .ok_or_else(|| format!("Unknown cycle direction: {s}"))
     }
 }
+
+static GROUP_DIRECTIONS: phf::Map<&'static str, WindowSwitchDirection> = phf_map! {
+    "back" => WindowSwitchDirection::Back,
+    "b" => WindowSwitchDirection::Back,
+    "forward" => WindowSwitchDirection::Forward,
+    "f" => WindowSwitchDirection::Forward,
+};
+
+#[derive(Debug, Clone)]
+pub struct ParsedGroupDirection(pub WindowSwitchDirection);
+impl FromStr for ParsedGroupDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GROUP_DIRECTIONS
+            .get(s.to_lowercase().as_str())
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| format!("Unknown group direction: {s}, expected back or forward"))
+    }
+}
+
+static SWAP_WITH_MASTER_PARAMS: phf::Map<&'static str, SwapWithMasterParam> = phf_map! {
+    "master" => SwapWithMasterParam::Master,
+    "child" => SwapWithMasterParam::Child,
+    "auto" => SwapWithMasterParam::Auto,
+};
+
+#[derive(Debug, Clone)]
+pub struct ParsedSwapWithMasterParam(pub SwapWithMasterParam);
+impl FromStr for ParsedSwapWithMasterParam {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SWAP_WITH_MASTER_PARAMS
+            .get(s.to_lowercase().as_str())
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| format!("Unknown swap-with-master param: {s}"))
+    }
+}
+
+static FOCUS_MASTER_PARAMS: phf::Map<&'static str, FocusMasterParam> = phf_map! {
+    "master" => FocusMasterParam::Master,
+    "auto" => FocusMasterParam::Auto,
+};
+
+#[derive(Debug, Clone)]
+pub struct ParsedFocusMasterParam(pub FocusMasterParam);
+impl FromStr for ParsedFocusMasterParam {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FOCUS_MASTER_PARAMS
+            .get(s.to_lowercase().as_str())
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| format!("Unknown focus-master param: {s}"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedLayoutCmd(pub SwitchXKBLayoutCmdTypes);
+impl FromStr for ParsedLayoutCmd {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "next" => Ok(Self(SwitchXKBLayoutCmdTypes::Next)),
+            "prev" => Ok(Self(SwitchXKBLayoutCmdTypes::Previous)),
+            other => other
+                .parse()
+                .map(|index| Self(SwitchXKBLayoutCmdTypes::Id(index)))
+                .map_err(|_| format!("Unknown layout: {s}, expected next, prev or a layout index")),
+        }
+    }
+}
+
+static DURATION_UNITS: phf::Map<&'static str, u64> = phf_map! {
+    "ms" => 1,
+    "s" => 1000,
+    "m" => 60 * 1000,
+    "h" => 60 * 60 * 1000,
+    "d" => 24 * 60 * 60 * 1000,
+    "w" => 7 * 24 * 60 * 60 * 1000,
+};
+
+/// A duration like `500ms`, `30s`, `5m`, `1h` or `2d`. A bare number is milliseconds, the same as
+/// in every config option that takes a duration.
+#[derive(Debug, Clone)]
+pub struct ParsedDuration(pub Duration);
+impl FromStr for ParsedDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(s.len());
+        let (count, unit) = s.split_at(split);
+        let count: u64 = count
+            .parse()
+            .map_err(|_| format!("Invalid duration: {s}, expected e.g. 30s"))?;
+        let millis = match unit {
+            "" => 1,
+            unit => *DURATION_UNITS
+                .get(unit)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown duration unit '{unit}' in {s}, expected {}",
+                        sorted_keys(&DURATION_UNITS).join(", ")
+                    )
+                })?,
+        };
+        count
+            .checked_mul(millis)
+            .map(|millis| Self(Duration::from_millis(millis)))
+            .ok_or_else(|| format!("Duration too long: {s}"))
+    }
+}
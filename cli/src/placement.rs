@@ -0,0 +1,163 @@
+//! Placement policies for newly opened floating windows.
+//!
+//! Policies are declared in the reaction config under `[[placements]]` and are turned into
+//! internal window-opened handlers by the [`ReactionManager`](crate::reaction_handler).
+
+use crate::clients::find_client_by_address;
+use crate::parsers::ParsedCorner;
+use crate::reaction_handler::{deserialize_window_identifier, is_window_match};
+use crate::units::Length;
+use hyprland::data::{CursorPosition, Monitor, Monitors};
+use hyprland::dispatch::{Corner, Dispatch, DispatchType, Position, WindowIdentifier};
+use hyprland::prelude::*;
+use hyprland::shared::Address;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Where a newly opened floating window should be placed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlacementPolicy {
+    /// Center the window on its monitor.
+    Center,
+    /// Center the window under the cursor, clamped to the monitor.
+    UnderCursor,
+    /// Snap the window into a monitor corner.
+    Corner(#[serde(deserialize_with = "deserialize_corner")] Corner),
+    /// Restore the last position a window of the same class was left at.
+    Remembered,
+}
+
+fn deserialize_corner<'de, D>(deserializer: D) -> Result<Corner, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    ParsedCorner::from_str(&s)
+        .map(|p| p.0)
+        .map_err(serde::de::Error::custom)
+}
+
+/// A placement rule: a policy applied to windows matching an optional filter.
+#[derive(Debug, Deserialize)]
+pub struct Placement {
+    pub policy: PlacementPolicy,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_window_identifier"
+    )]
+    pub window_filter: Option<WindowIdentifier<'static>>,
+    /// Gap kept between the window and the monitor edges, in pixels or as a percentage of the
+    /// monitor's width and height.
+    #[serde(default)]
+    pub margin: Length,
+    pub name: Option<String>,
+    #[serde(skip)]
+    remembered: Mutex<HashMap<String, (i16, i16)>>,
+}
+
+impl Placement {
+    pub fn matches(&self, class: &str, title: &str) -> bool {
+        is_window_match(self.window_filter.as_ref(), class, title)
+    }
+
+    pub fn is_remembered(&self) -> bool {
+        matches!(self.policy, PlacementPolicy::Remembered)
+    }
+
+    /// Apply this policy to the window at `address`. Tiled windows are left untouched.
+    pub fn apply(&self, address: &Address) -> Result<(), String> {
+        let client = find_client_by_address(address)?;
+        if !client.floating {
+            return Ok(());
+        }
+        let monitor = find_monitor(client.monitor)?;
+        let target = match &self.policy {
+            PlacementPolicy::Center => {
+                let (mx, my, mw, mh) = usable_area(&monitor);
+                (mx + (mw - client.size.0) / 2, my + (mh - client.size.1) / 2)
+            },
+            PlacementPolicy::UnderCursor => {
+                let cursor = CursorPosition::get().map_err(|e| e.to_string())?;
+                let x = cursor.x as i16 - client.size.0 / 2;
+                let y = cursor.y as i16 - client.size.1 / 2;
+                self.clamp(&monitor, client.size, (x, y))
+            },
+            PlacementPolicy::Corner(corner) => {
+                let (mx, my, mw, mh) = usable_area(&monitor);
+                let (w, h) = client.size;
+                let (gx, gy) = self.gaps(mw, mh);
+                match corner {
+                    Corner::TopLeft => (mx + gx, my + gy),
+                    Corner::TopRight => (mx + mw - w - gx, my + gy),
+                    Corner::BottomLeft => (mx + gx, my + mh - h - gy),
+                    Corner::BottomRight => (mx + mw - w - gx, my + mh - h - gy),
+                }
+            },
+            PlacementPolicy::Remembered => {
+                let remembered = self.remembered.lock().unwrap();
+                match remembered.get(&client.class) {
+                    Some(&pos) => self.clamp(&monitor, client.size, pos),
+                    None => return Ok(()),
+                }
+            },
+        };
+
+        Dispatch::call(DispatchType::MoveWindowPixel(
+            Position::Exact(target.0, target.1),
+            WindowIdentifier::Address(address.clone()),
+        ))
+        .map_err(|e| e.to_string())
+    }
+
+    /// Record the current position of the window at `address` for the `remembered` policy.
+    pub fn remember(&self, address: &Address) -> Result<(), String> {
+        let client = find_client_by_address(address)?;
+        if client.floating && self.matches(&client.class, &client.title) {
+            self.remembered
+                .lock()
+                .unwrap()
+                .insert(client.class, client.at);
+        }
+        Ok(())
+    }
+
+    fn clamp(&self, monitor: &Monitor, size: (i16, i16), pos: (i16, i16)) -> (i16, i16) {
+        let (mx, my, mw, mh) = usable_area(monitor);
+        let (gx, gy) = self.gaps(mw, mh);
+        let x = pos
+            .0
+            .clamp(mx + gx, (mx + mw - size.0 - gx).max(mx + gx));
+        let y = pos
+            .1
+            .clamp(my + gy, (my + mh - size.1 - gy).max(my + gy));
+        (x, y)
+    }
+
+    /// The margin in pixels horizontally and vertically, for a usable area of `width` by `height`.
+    fn gaps(&self, width: i16, height: i16) -> (i16, i16) {
+        (self.margin.resolve(width.into()) as i16, self.margin.resolve(height.into()) as i16)
+    }
+}
+
+/// The monitor area (x, y, width, height) in layout coordinates, minus reserved space.
+fn usable_area(monitor: &Monitor) -> (i16, i16, i16, i16) {
+    let scale = if monitor.scale > 0.0 { monitor.scale } else { 1.0 };
+    let (left, top, right, bottom) = monitor.reserved;
+    let width = (monitor.width as f32 / scale) as i16 - left as i16 - right as i16;
+    let height = (monitor.height as f32 / scale) as i16 - top as i16 - bottom as i16;
+    (monitor.x as i16 + left as i16, monitor.y as i16 + top as i16, width, height)
+}
+
+fn find_monitor(id: Option<hyprland::shared::MonitorId>) -> Result<Monitor, String> {
+    match id {
+        Some(id) => Monitors::get()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| format!("No monitor found with id {id}")),
+        None => Monitor::get_active().map_err(|e| e.to_string()),
+    }
+}
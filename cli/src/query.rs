@@ -1,31 +1,189 @@
-use crate::flags::Query;
-// use hyprland::data::CursorPosition;
-// use hyprland::prelude::*;
+use crate::flags::{OutputFormat, Query, TreeBy};
+use crate::ignore::IgnoreList;
+use crate::output::{self, Printer, Record};
+use crate::{dpms, occupancy};
+use hyprland::data::{Client, Clients, CursorPosition, Devices, Monitors, Submap, Workspaces};
+use hyprland::prelude::*;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
 
-use hyprland::dispatch;
-use hyprland::dispatch::{Dispatch, DispatchType, FullscreenType};
+pub fn run_query(
+    command: Query,
+    watch: Option<u64>,
+    output: Option<OutputFormat>,
+) -> Result<(), String> {
+    let json_flag =
+        matches!(command, Query::Clients { json: true, .. }).then_some(OutputFormat::Json);
+    let format = output::resolve(output, json_flag);
 
-pub fn run_query(command: Query) -> hyprland::Result<()> {
-    match command {
-        Query::CursorPos { watch } => {
-            if watch {
-                println!("who cares");
-                Ok(())
-            } else {
-                dispatch!(ToggleFullscreen, FullscreenType::Maximize)?;
-                Ok(())
-
-                // .expect("failed foe some reason")
-
-                // match CursorPosition::get() {
-                //     Ok(pos) => {
-                //         println!("x: {}, y: {}", pos.x, pos.y);
-                //     },
-                //     Err(e) => {
-                //         eprintln!("Error: {e}");
-                //     },
-                // }
+    if let Query::Monitors { watch_dpms: true } = command {
+        let mut printer = Printer::streaming(format);
+        dpms::watch(true, |monitor, on| {
+            let state = if on { "on" } else { "off" };
+            let record = Record {
+                value: json!({ "monitor": monitor, "dpms": on }),
+                plain: format!("{monitor}: {state}"),
+            };
+            if let Err(e) = printer.print(&record) {
+                eprintln!("Error: {e}");
             }
+        });
+    }
+
+    if let Query::Workspaces { occupancy_map: true, count } = command {
+        if watch.is_some() {
+            return occupancy::watch(count, format);
+        }
+    }
+
+    let Some(interval) = watch else {
+        return Printer::new(format).print(&query_record(&command)?);
+    };
+
+    let mut printer = Printer::streaming(format);
+    let interval = Duration::from_millis(interval.max(1));
+    let mut last = None;
+    loop {
+        let rendered = printer.render(&query_record(&command)?)?;
+        if last.as_ref() != Some(&rendered) {
+            println!("{rendered}");
+            last = Some(rendered);
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Run a query once.
+fn query_record(command: &Query) -> Result<Record, String> {
+    match command {
+        Query::CursorPos => {
+            let pos = CursorPosition::get().map_err(|e| e.to_string())?;
+            Record::new(&pos, format!("x: {}, y: {}", pos.x, pos.y))
+        },
+        Query::Submap => {
+            let submap = Submap::get().map_err(|e| e.to_string())?;
+            Record::new(&submap, submap.to_string())
+        },
+        Query::KeyboardLayout => {
+            let keyboards = Devices::get()
+                .map_err(|e| e.to_string())?
+                .keyboards;
+            let value: Vec<_> = keyboards
+                .iter()
+                .map(|k| {
+                    json!({
+                        "name": k.name,
+                        "layout": k.layout,
+                        "variant": k.variant,
+                        "active_keymap": k.active_keymap,
+                        "main": k.main,
+                    })
+                })
+                .collect();
+            let lines: Vec<String> = keyboards
+                .iter()
+                .map(|k| {
+                    let main = if k.main { " (main)" } else { "" };
+                    format!("{}{main}: {}", k.name, k.active_keymap)
+                })
+                .collect();
+            Record::new(&value, lines.join("\n"))
+        },
+        Query::Monitors { .. } => {
+            let monitors = Monitors::get()
+                .map_err(|e| e.to_string())?
+                .to_vec();
+            let lines: Vec<String> = monitors
+                .iter()
+                .map(|m| {
+                    let dpms = if m.dpms_status { "on" } else { "off" };
+                    format!(
+                        "{} {}x{}@{:.2} dpms: {dpms}",
+                        m.name, m.width, m.height, m.refresh_rate
+                    )
+                })
+                .collect();
+            Record::new(&monitors, lines.join("\n"))
+        },
+        Query::Clients { tree_by, all, .. } => clients_record(*tree_by, *all),
+        Query::Workspaces { occupancy_map: true, count } => {
+            occupancy::record(*count, &HashSet::new())
         },
+        Query::Workspaces { occupancy_map: false, .. } => {
+            let mut workspaces = Workspaces::get()
+                .map_err(|e| e.to_string())?
+                .to_vec();
+            workspaces.sort_by_key(|w| w.id);
+            let lines: Vec<String> = workspaces
+                .iter()
+                .map(|w| format!("{} {} on {} ({} windows)", w.id, w.name, w.monitor, w.windows))
+                .collect();
+            Record::new(&workspaces, lines.join("\n"))
+        },
+    }
+}
+
+fn clients_record(tree_by: Option<TreeBy>, all: bool) -> Result<Record, String> {
+    let mut clients = Clients::get()
+        .map_err(|e| e.to_string())?
+        .to_vec();
+    if !all {
+        let ignore = IgnoreList::from_global_config();
+        clients.retain(|c| !ignore.is_client_ignored(c));
+    }
+
+    let Some(tree_by) = tree_by else {
+        let lines: Vec<String> = clients
+            .iter()
+            .map(|c| format!("{} [{}] {}", c.address, c.workspace.name, client_label(c)))
+            .collect();
+        return Record::new(&clients, lines.join("\n"));
+    };
+
+    let groups = group_clients(clients, tree_by).map_err(|e| e.to_string())?;
+    let mut lines = Vec::new();
+    for (group, clients) in &groups {
+        lines.push(group.clone());
+        for c in clients {
+            lines.push(format!("  {} {}", c.address, client_label(c)));
+        }
     }
+    Record::new(&groups, lines.join("\n"))
+}
+
+fn group_clients(
+    clients: Vec<Client>,
+    tree_by: TreeBy,
+) -> hyprland::Result<BTreeMap<String, Vec<Client>>> {
+    let monitor_names: HashMap<_, _> = if tree_by == TreeBy::Monitor {
+        Monitors::get()?
+            .into_iter()
+            .map(|m| (m.id, m.name))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut groups: BTreeMap<String, Vec<Client>> = BTreeMap::new();
+    for client in clients {
+        let key = match tree_by {
+            TreeBy::Workspace => client.workspace.name.clone(),
+            TreeBy::Monitor => client
+                .monitor
+                .and_then(|id| monitor_names.get(&id).cloned())
+                .unwrap_or_else(|| "unknown".to_string()),
+            TreeBy::Class => client.class.clone(),
+        };
+        groups
+            .entry(key)
+            .or_default()
+            .push(client);
+    }
+    Ok(groups)
+}
+
+fn client_label(client: &Client) -> String {
+    format!("{} - {}", client.class, client.title)
 }
@@ -0,0 +1,56 @@
+//! Compositor state shared by every reaction handling the same event.
+//!
+//! `min_windows`/`max_windows`, `when.keyword` and the built-in variables query Hyprland each time
+//! a reaction is triggered. Handlers run one event at a time on the listener's thread, so answers
+//! are kept for as long as that event is being handled: ten reactions sharing a condition make one
+//! query instead of ten. A reaction that runs its dispatchers may have changed the answers, so it
+//! clears them, and the next event starts from scratch. Outside the listener's thread, e.g. for
+//! timers, every call queries.
+
+use hyprland::event_listener::current_event;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct Cache {
+    event: Option<u64>,
+    values: HashMap<String, Box<dyn Any>>,
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache::default());
+}
+
+/// The value cached under `key` for the event being handled, or what `query` returns.
+pub fn get<T: Clone + 'static>(key: &str, query: impl FnOnce() -> T) -> T {
+    let Some(event) = current_event() else {
+        return query();
+    };
+    let cached = CACHE.with_borrow_mut(|cache| {
+        if cache.event != Some(event) {
+            cache.values.clear();
+            cache.event = Some(event);
+        }
+        cache
+            .values
+            .get(key)
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    });
+    if let Some(value) = cached {
+        return value;
+    }
+    let value = query();
+    CACHE.with_borrow_mut(|cache| {
+        cache
+            .values
+            .insert(key.to_string(), Box::new(value.clone()))
+    });
+    value
+}
+
+/// Forget what was cached for the event being handled.
+pub fn clear() {
+    CACHE.with_borrow_mut(|cache| cache.values.clear());
+}
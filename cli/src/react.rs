@@ -10,6 +10,7 @@ pub fn sync_react(
     event: String,
     subtype: Option<String>,
     filter: Option<String>,
+    device: Option<String>,
     dispatch: DispatchCmd,
     max_reactions: usize,
 ) -> hyprland::Result<()> {
@@ -17,6 +18,9 @@ pub fn sync_react(
     if let Some(filter) = &filter {
         println!("Using window filter: {filter}");
     }
+    if let Some(device) = &device {
+        println!("Using device filter: {device}");
+    }
     println!("Press Ctrl+C to stop");
 
     let event_type =
@@ -33,10 +37,22 @@ pub fn sync_react(
         event_type,
         dispatchers: vec![dispatch.into()],
         window_filter,
+        device,
         max_count: if max_reactions > 0 { Some(max_reactions) } else { None },
         name: None,
         description: None,
+        min_windows: None,
+        max_windows: None,
+        when: None,
+        disabled: false,
+        instance: None,
+        journal: None,
+        cooldown: None,
+        after: Vec::new(),
+        each_monitor: false,
         counter: Arc::new(AtomicUsize::new(0)),
+        stats: Arc::default(),
+        ignore: Arc::default(),
     };
 
     let mut manager = ReactionManager::new();
@@ -1,5 +1,14 @@
-use crate::reaction_handler::{Reaction, ReactionManager};
+use crate::app_modes::{AppMode, AppModes};
+use crate::flags::Dispatch;
+use crate::ignore::IgnoreList;
+use crate::placement::Placement;
+use crate::reaction_handler::{Dispatcher, EventType, Reaction, ReactionManager};
+use crate::sandbox::ExecSandbox;
+use crate::scene::Scene;
+use crate::template::Vars;
+use crate::{daemon_lock, dispatch, expose};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -7,8 +16,27 @@ use std::sync::atomic::AtomicUsize;
 
 #[derive(Debug, Deserialize)]
 pub struct ReactConfig {
-    #[serde(rename = "reactions")]
+    #[serde(rename = "reactions", default)]
     pub reactions_config: Vec<ReactionConfig>,
+    #[serde(default)]
+    pub placements: Vec<Placement>,
+    /// Keyword sets keyed by window class, applied while that class is focused.
+    #[serde(default)]
+    pub apps: HashMap<String, AppMode>,
+    /// How reaction `exec` commands are spawned.
+    #[serde(default)]
+    pub exec: ExecSandbox,
+    #[serde(default)]
+    pub scenes: HashMap<String, Scene>,
+    /// Windows that reactions, placements and app modes never see.
+    #[serde(default)]
+    pub ignore: IgnoreList,
+    /// State mirrored into Hyprland's environment for binds to read.
+    #[serde(default)]
+    pub expose: Vec<expose::State>,
+    /// Keywords set again after every Hyprland config reload.
+    #[serde(default)]
+    pub reassert: AppMode,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,25 +49,337 @@ impl ReactConfig {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
         let content = fs::read_to_string(path.as_ref())
             .map_err(|e| format!("Failed to read config file: {e}"))?;
-        toml::from_str(&content).map_err(|e| format!("Failed to parse TOML config file: {e}"))
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse TOML config file: {e}"))?;
+        config.validate()?;
+        config.run_order()?;
+        Ok(config)
+    }
+
+    /// Reaction indices in the order their handlers are registered: config order, except that a
+    /// reaction comes after every reaction its `after` names. Handlers for one event run in
+    /// registration order, one after the other, so this is the order they run in.
+    fn run_order(&self) -> Result<Vec<usize>, String> {
+        let reactions: Vec<&Reaction> = self
+            .reactions_config
+            .iter()
+            .map(|config| &config.reaction)
+            .collect();
+        let mut errors = Vec::new();
+        for (index, reaction) in reactions.iter().enumerate() {
+            for name in &reaction.after {
+                let named: Vec<&&Reaction> = reactions
+                    .iter()
+                    .filter(|other| other.name.as_ref() == Some(name))
+                    .collect();
+                if named.is_empty() {
+                    errors.push(format!(
+                        "Reaction {} runs after '{name}', but no reaction has that name",
+                        self.label(index)
+                    ));
+                } else if let Some(other) = named
+                    .iter()
+                    .find(|other| other.event_type != reaction.event_type)
+                {
+                    errors.push(format!(
+                        "Reaction {} runs after '{name}', which reacts to {} rather than {}",
+                        self.label(index),
+                        other.event_type,
+                        reaction.event_type
+                    ));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(format!("Invalid reaction order:\n  {}", errors.join("\n  ")));
+        }
+
+        // Repeatedly take the first reaction whose predecessors are all placed.
+        let mut order = Vec::with_capacity(reactions.len());
+        let mut placed = vec![false; reactions.len()];
+        while order.len() < reactions.len() {
+            let next = (0..reactions.len()).find(|&index| {
+                !placed[index]
+                    && reactions[index]
+                        .after
+                        .iter()
+                        .all(|name| {
+                            reactions
+                                .iter()
+                                .zip(&placed)
+                                .all(|(other, &placed)| placed || other.name.as_ref() != Some(name))
+                        })
+            });
+            let Some(next) = next else {
+                let waiting: Vec<String> = (0..reactions.len())
+                    .filter(|&index| !placed[index])
+                    .map(|index| self.label(index))
+                    .collect();
+                return Err(format!(
+                    "Reactions {} wait for each other through `after` and can never run",
+                    waiting.join(", ")
+                ));
+            };
+            placed[next] = true;
+            order.push(next);
+        }
+        Ok(order)
+    }
+
+    /// Reject dispatchers whose arguments would only fail once their event fires.
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        // Scene dispatchers run outside any reaction, so they have no placeholders to fill in.
+        let mut check = |owner: &str, dispatchers: &[Dispatcher], vars: Option<&Vars>| {
+            for (index, dispatcher) in dispatchers.iter().enumerate() {
+                // Arguments with placeholders are only complete once the event filled them in.
+                let result = match (dispatcher, vars) {
+                    (Dispatcher::Template(raw), Some(vars)) => raw.fill(vars).map(drop),
+                    (Dispatcher::Template(_), None) => {
+                        Err("placeholders only work in reactions".to_string())
+                    },
+                    _ => Dispatch::try_from(dispatcher.clone())
+                        .and_then(|command| dispatch::validate(&command))
+                        .and_then(|()| {
+                            vars.map_or(Ok(()), |vars| dispatcher.render(vars).map(drop))
+                        }),
+                };
+                if let Err(e) = result {
+                    errors.push(format!("{owner}, dispatcher {} ({dispatcher:?}): {e}", index + 1));
+                }
+            }
+        };
+        for (index, config) in self.reactions_config.iter().enumerate() {
+            let reaction = &config.reaction;
+            check(
+                &format!("Reaction {}", self.label(index)),
+                &reaction.dispatchers,
+                Some(&reaction.template_vars()),
+            );
+        }
+        let mut scenes: Vec<_> = self.scenes.iter().collect();
+        scenes.sort_unstable_by_key(|(name, _)| name.as_str());
+        for (name, scene) in scenes {
+            check(&format!("Scene {name}"), &scene.dispatchers, None);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid dispatcher arguments:\n  {}", errors.join("\n  ")))
+        }
+    }
+
+    /// The config at `path` as normalized TOML, without the duplicate reactions that would be
+    /// coalesced at load. Comments and formatting are not preserved.
+    pub fn effective_toml<P: AsRef<Path>>(path: P) -> Result<String, String> {
+        let config = Self::from_file(path.as_ref())?;
+        let content = fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Failed to read config file: {e}"))?;
+        let mut table: toml::Table = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse TOML config file: {e}"))?;
+        if let Some(toml::Value::Array(reactions)) = table.get_mut("reactions") {
+            let mut index = 0;
+            reactions.retain(|_| {
+                let keep = config.duplicate_of(index).is_none();
+                index += 1;
+                keep
+            });
+        }
+        toml::to_string_pretty(&table).map_err(|e| e.to_string())
+    }
+
+    /// The index of an earlier reaction that `index` duplicates, if any.
+    fn duplicate_of(&self, index: usize) -> Option<usize> {
+        let reaction = &self.reactions_config[index].reaction;
+        self.reactions_config[..index]
+            .iter()
+            .position(|earlier| reaction.is_duplicate_of(&earlier.reaction))
+    }
+
+    fn label(&self, index: usize) -> String {
+        self.reactions_config[index]
+            .reaction
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", index + 1))
+    }
+
+    /// Problems that don't stop the config from loading but likely aren't what the user wants.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (index, config) in self.reactions_config.iter().enumerate() {
+            let reaction = &config.reaction;
+            let label = self.label(index);
+            if let Some(original) = self.duplicate_of(index) {
+                warnings.push(format!(
+                    "Reaction {label} duplicates reaction {} and will only run once",
+                    self.label(original)
+                ));
+                continue;
+            }
+            if reaction.device.is_some() && reaction.event_type != EventType::Layout {
+                warnings.push(format!(
+                    "Reaction {label} has a device filter, which only applies to layout events"
+                ));
+            }
+            if reaction.dispatchers.is_empty() {
+                warnings.push(format!("Reaction {label} has no dispatchers and will do nothing"));
+            }
+            if let (Some(min), Some(max)) = (reaction.min_windows, reaction.max_windows) {
+                if min > max {
+                    warnings.push(format!(
+                        "Reaction {label} has min_windows greater than max_windows and will never \
+                         fire"
+                    ));
+                }
+            }
+            if reaction.disabled && reaction.name.is_none() {
+                warnings.push(format!(
+                    "Reaction {label} is disabled but has no name, so no scene can enable it"
+                ));
+            }
+            if reaction.journal.is_some() && reaction.name.is_none() {
+                warnings.push(format!(
+                    "Reaction {label} is journaled but has no name, so it can't be recovered \
+                     after a crash"
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Print what a service running this config would do, without starting anything.
+    pub fn print_preview(&self) {
+        println!("{} reactions would be loaded:", self.reactions_config.len());
+        for (index, config) in self.reactions_config.iter().enumerate() {
+            let reaction = &config.reaction;
+            let name = reaction
+                .name
+                .as_deref()
+                .unwrap_or("unnamed");
+            println!("  {}. {name} on {} events", index + 1, reaction.event_type);
+            if let Some(filter) = &reaction.window_filter {
+                println!("     window filter: {filter}");
+            }
+            if let Some(device) = &reaction.device {
+                println!("     device filter: {device}");
+            }
+            match (reaction.min_windows, reaction.max_windows) {
+                (None, None) => {},
+                (min, max) => println!(
+                    "     windows on workspace: {}..{}",
+                    min.map(|n| n.to_string())
+                        .unwrap_or_default(),
+                    max.map(|n| n.to_string())
+                        .unwrap_or_default()
+                ),
+            }
+            if let Some(when) = &reaction.when {
+                if let Some(dnd) = when.dnd {
+                    println!("     when dnd == {dnd}");
+                }
+                for (keyword, value) in &when.keyword {
+                    println!("     when {keyword} == {value}");
+                }
+            }
+            if let Some(max_count) = reaction.max_count.filter(|&n| n > 0) {
+                println!("     max count: {max_count}");
+            }
+            if let Some(cooldown) = reaction.cooldown {
+                println!("     cooldown: {cooldown:?}");
+            }
+            if !reaction.after.is_empty() {
+                println!("     runs after: {}", reaction.after.join(", "));
+            }
+            if reaction.each_monitor {
+                println!("     once per monitor");
+            }
+            for dispatcher in &reaction.dispatchers {
+                println!("     - {dispatcher:?}");
+            }
+        }
+        if !self.placements.is_empty() {
+            println!("{} placement policies", self.placements.len());
+        }
+        if !self.apps.is_empty() {
+            println!("{} app modes: {}", self.apps.len(), {
+                let mut classes: Vec<_> = self
+                    .apps
+                    .keys()
+                    .map(String::as_str)
+                    .collect();
+                classes.sort_unstable();
+                classes.join(", ")
+            });
+        }
+        if self.exec.systemd_scope {
+            println!("exec commands run in their own systemd scope");
+        }
+        for warning in self.warnings() {
+            println!("Warning: {warning}");
+        }
     }
 
     pub fn into_manager(self) -> ReactionManager {
+        for warning in self.warnings() {
+            eprintln!("Warning: {warning}");
+        }
+        let duplicates: Vec<bool> = (0..self.reactions_config.len())
+            .map(|index| self.duplicate_of(index).is_some())
+            .collect();
+        // `from_file` already rejected configs without a valid order.
+        let order = self
+            .run_order()
+            .unwrap_or_else(|_| (0..self.reactions_config.len()).collect());
+        let mut configs: Vec<Option<ReactionConfig>> = self
+            .reactions_config
+            .into_iter()
+            .map(Some)
+            .collect();
+        let ignore = Arc::new(self.ignore);
         let mut manager = ReactionManager::new();
-        for config in self.reactions_config {
+        for index in order {
+            let Some(config) = configs[index].take() else {
+                continue;
+            };
+            if duplicates[index] {
+                continue;
+            }
+            let dispatchers = config
+                .reaction
+                .dispatchers
+                .into_iter()
+                .map(|dispatcher| match dispatcher {
+                    Dispatcher::Exec(command, rules) => {
+                        Dispatcher::Exec(self.exec.wrap(command), rules)
+                    },
+                    other => other,
+                })
+                .collect();
             manager.add_reaction(Reaction {
+                dispatchers,
                 counter: Arc::new(AtomicUsize::new(0)),
+                ignore: Arc::clone(&ignore),
                 ..config.reaction
             });
         }
+        for placement in self.placements {
+            manager.add_placement(placement);
+        }
+        manager.set_app_modes(AppModes::new(self.apps));
+        manager.set_ignore_list(ignore);
+        manager.set_exposed(self.expose);
+        manager.set_reasserted(self.reassert);
         manager
     }
 }
 
-pub fn run_from_config<P: AsRef<Path>>(path: P) -> Result<(), String> {
+pub fn run_from_config<P: AsRef<Path>>(path: P, replace: bool) -> Result<(), String> {
+    let _lock = daemon_lock::acquire(path.as_ref(), replace)?;
     println!("Loading reactions from {}", path.as_ref().display());
-    let config = ReactConfig::from_file(path)?;
+    let config = ReactConfig::from_file(&path)?;
     println!("Loaded {} reactions", config.reactions_config.len());
-    let manager = config.into_manager();
+    let mut manager = config.into_manager();
+    manager.set_config_path(path.as_ref().to_path_buf());
     manager.start()
 }
@@ -1,14 +1,33 @@
-use crate::dispatch::handle_dispatch;
-use crate::flags::{Dispatch, ResizeCmd, WindowId};
+use crate::app_modes::{AppMode, AppModes, KeywordValue};
+use crate::clients::pattern_matches;
+use crate::dispatch::run_dispatch;
+use crate::flags::{CycleScopeArgs, Dispatch, ExecRules, ResizeCmd, WindowId};
+use crate::ignore::IgnoreList;
+use crate::journal::{self, JournalEntry, JournalMode};
 use crate::parsers::ParsedWindowIdentifier;
+use crate::placement::Placement;
+use crate::schedule::Schedule;
+use crate::send_signal::Target;
+use crate::template::{self, Vars};
+use crate::window_cache::{Window, WindowCache};
+use crate::{
+    crash, ctl, cursor, cycle, dnd, dpms, environment, expose, failure, foreach, history, instance,
+    latency, notify, query_cache, reaction_state, reassert, scene, signals, status, suggest, units,
+};
+use hyprland::data::{Monitors, Workspace};
 use hyprland::dispatch::WindowIdentifier;
-use hyprland::event_listener::EventListener;
+use hyprland::event_listener::{EventListener, WindowEventData, WorkspaceEventData, last_read_at};
+use hyprland::keyword::{Keyword, OptionValue};
+use hyprland::prelude::*;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use std::fmt;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{fmt, thread};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -21,6 +40,19 @@ pub enum WindowEventType {
     Moved,
     #[serde(alias = "Active")]
     Active,
+    /// Like `active`, but only fires when the focused window's class differs from the last one.
+    #[serde(alias = "ClassChanged")]
+    ClassChanged,
+}
+
+impl WindowEventType {
+    pub const ALL: [Self; 5] = [
+        Self::Opened,
+        Self::Closed,
+        Self::Moved,
+        Self::Active,
+        Self::ClassChanged,
+    ];
 }
 
 impl fmt::Display for WindowEventType {
@@ -30,6 +62,7 @@ impl fmt::Display for WindowEventType {
             WindowEventType::Closed => write!(f, "closed"),
             WindowEventType::Moved => write!(f, "moved"),
             WindowEventType::Active => write!(f, "active"),
+            WindowEventType::ClassChanged => write!(f, "class-changed"),
         }
     }
 }
@@ -45,6 +78,10 @@ pub enum WorkspaceEventType {
     Deleted,
 }
 
+impl WorkspaceEventType {
+    pub const ALL: [Self; 3] = [Self::Changed, Self::Added, Self::Deleted];
+}
+
 impl fmt::Display for WorkspaceEventType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -66,6 +103,10 @@ pub enum GroupEventType {
     MovedOut,
 }
 
+impl GroupEventType {
+    pub const ALL: [Self; 3] = [Self::Toggled, Self::MovedIn, Self::MovedOut];
+}
+
 impl fmt::Display for GroupEventType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -76,6 +117,39 @@ impl fmt::Display for GroupEventType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DpmsEventType {
+    #[serde(alias = "On")]
+    On,
+    #[serde(alias = "Off")]
+    Off,
+    #[serde(alias = "Changed")]
+    Changed,
+}
+
+impl DpmsEventType {
+    pub const ALL: [Self; 3] = [Self::On, Self::Off, Self::Changed];
+
+    pub fn matches(self, on: bool) -> bool {
+        match self {
+            DpmsEventType::On => on,
+            DpmsEventType::Off => !on,
+            DpmsEventType::Changed => true,
+        }
+    }
+}
+
+impl fmt::Display for DpmsEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DpmsEventType::On => write!(f, "on"),
+            DpmsEventType::Off => write!(f, "off"),
+            DpmsEventType::Changed => write!(f, "changed"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EventType {
     Window(WindowEventType),
@@ -86,6 +160,10 @@ pub enum EventType {
     Layout,
     Group(GroupEventType),
     Config,
+    /// A monitor was powered on or off. Polled, since Hyprland has no event for it.
+    Dpms(DpmsEventType),
+    /// A time of day or an interval, run by a timer thread instead of an event.
+    Schedule(Schedule),
 }
 
 impl<'de> Deserialize<'de> for EventType {
@@ -112,13 +190,10 @@ impl<'de> Deserialize<'de> for EventType {
                     "fullscreen" => Ok(EventType::Fullscreen),
                     "layout" => Ok(EventType::Layout),
                     "config" => Ok(EventType::Config),
-                    _ => Err(de::Error::unknown_variant(value, &[
-                        "monitor",
-                        "float",
-                        "fullscreen",
-                        "layout",
-                        "config",
-                    ])),
+                    _ => Err(de::Error::custom(format!(
+                        "unknown event type `{value}`{}",
+                        suggest::hint(value, EventType::NAMES)
+                    ))),
                 }
             }
 
@@ -143,7 +218,27 @@ impl<'de> Deserialize<'de> for EventType {
                         let subtype: GroupEventType = map.next_value()?;
                         Ok(EventType::Group(subtype))
                     },
-                    _ => Err(de::Error::unknown_field(&key, &["window", "workspace", "group"])),
+                    "dpms" => {
+                        let subtype: DpmsEventType = map.next_value()?;
+                        Ok(EventType::Dpms(subtype))
+                    },
+                    "schedule" => {
+                        let schedule: String = map.next_value()?;
+                        schedule
+                            .parse()
+                            .map(EventType::Schedule)
+                            .map_err(de::Error::custom)
+                    },
+                    _ => Err(de::Error::custom(format!(
+                        "unknown event type `{key}`{}",
+                        suggest::hint(&key, [
+                            "window",
+                            "workspace",
+                            "group",
+                            "dpms",
+                            "schedule"
+                        ])
+                    ))),
                 }
             }
         }
@@ -153,27 +248,83 @@ impl<'de> Deserialize<'de> for EventType {
 }
 
 impl EventType {
+    /// Event names accepted by [`EventType::from_event_and_subtype`].
+    pub const NAMES: [&str; 10] = [
+        "window",
+        "workspace",
+        "monitor",
+        "float",
+        "fullscreen",
+        "layout",
+        "group",
+        "config",
+        "dpms",
+        "schedule",
+    ];
+
+    /// Placeholders filled in from the event's payload. Fields an event doesn't carry, like the
+    /// class of a focus change to an empty workspace, are left empty.
+    pub fn payload_fields(&self) -> &'static [&'static str] {
+        match self {
+            EventType::Window(WindowEventType::Opened) => {
+                &["address", "class", "title", "workspace"]
+            },
+            EventType::Window(WindowEventType::Active | WindowEventType::ClassChanged) => {
+                &["address", "class", "title"]
+            },
+            EventType::Window(WindowEventType::Closed) => {
+                &["address", "class", "title", "workspace"]
+            },
+            EventType::Window(WindowEventType::Moved) => &[
+                "address",
+                "class",
+                "title",
+                "workspace",
+                "workspace_id",
+            ],
+            EventType::Workspace(_) => &["workspace", "workspace_id"],
+            EventType::Float => &["address"],
+            EventType::Group(GroupEventType::MovedIn | GroupEventType::MovedOut) => &["address"],
+            EventType::Dpms(_) => &["monitor", "state"],
+            _ => &[],
+        }
+    }
+
+    /// Subtypes for each event that takes one, by their config/CLI names.
+    pub fn subtypes() -> Vec<(&'static str, String)> {
+        let window = WindowEventType::ALL
+            .iter()
+            .map(|t| ("window", t.to_string()));
+        let workspace = WorkspaceEventType::ALL
+            .iter()
+            .map(|t| ("workspace", t.to_string()));
+        let group = GroupEventType::ALL
+            .iter()
+            .map(|t| ("group", t.to_string()));
+        let dpms = DpmsEventType::ALL
+            .iter()
+            .map(|t| ("dpms", t.to_string()));
+        window
+            .chain(workspace)
+            .chain(group)
+            .chain(dpms)
+            .collect()
+    }
+
     pub fn from_event_and_subtype(event: &str, subtype: Option<&str>) -> Result<Self, String> {
         match event.to_lowercase().as_str() {
             "window" => {
                 let subtype = subtype.ok_or("Window event requires a subtype")?;
-                let window_event_type = match subtype.to_lowercase().as_str() {
-                    "opened" => WindowEventType::Opened,
-                    "closed" => WindowEventType::Closed,
-                    "moved" => WindowEventType::Moved,
-                    "active" => WindowEventType::Active,
-                    _ => return Err(format!("Unknown window subtype: {subtype}")),
-                };
+                let window_event_type = find_subtype(&WindowEventType::ALL, subtype)
+                    .ok_or_else(|| unknown_subtype("window", &WindowEventType::ALL, subtype))?;
                 Ok(EventType::Window(window_event_type))
             },
             "workspace" => {
                 let subtype = subtype.ok_or("Workspace event requires a subtype")?;
-                let workspace_event_type = match subtype.to_lowercase().as_str() {
-                    "changed" => WorkspaceEventType::Changed,
-                    "added" => WorkspaceEventType::Added,
-                    "deleted" => WorkspaceEventType::Deleted,
-                    _ => return Err(format!("Unknown workspace subtype: {subtype}")),
-                };
+                let workspace_event_type = find_subtype(&WorkspaceEventType::ALL, subtype)
+                    .ok_or_else(|| {
+                        unknown_subtype("workspace", &WorkspaceEventType::ALL, subtype)
+                    })?;
                 Ok(EventType::Workspace(workspace_event_type))
             },
             "monitor" => Ok(EventType::Monitor),
@@ -182,20 +333,51 @@ impl EventType {
             "layout" => Ok(EventType::Layout),
             "group" => {
                 let subtype = subtype.ok_or("Group event requires a subtype")?;
-                let group_event_type = match subtype.to_lowercase().as_str() {
-                    "toggled" => GroupEventType::Toggled,
-                    "moved-in" => GroupEventType::MovedIn,
-                    "moved-out" => GroupEventType::MovedOut,
-                    _ => return Err(format!("Unknown group subtype: {subtype}")),
-                };
+                let group_event_type = find_subtype(&GroupEventType::ALL, subtype)
+                    .ok_or_else(|| unknown_subtype("group", &GroupEventType::ALL, subtype))?;
                 Ok(EventType::Group(group_event_type))
             },
             "config" => Ok(EventType::Config),
-            _ => Err(format!("Unknown event type: {event}")),
+            "dpms" => {
+                let subtype = subtype.unwrap_or("changed");
+                let dpms_event_type = find_subtype(&DpmsEventType::ALL, subtype)
+                    .ok_or_else(|| unknown_subtype("dpms", &DpmsEventType::ALL, subtype))?;
+                Ok(EventType::Dpms(dpms_event_type))
+            },
+            "schedule" => {
+                let schedule =
+                    subtype.ok_or("Schedule event requires a schedule as its subtype")?;
+                schedule
+                    .parse()
+                    .map(EventType::Schedule)
+            },
+            _ => Err(format!(
+                "Unknown event type: {event}{}",
+                suggest::hint(event, EventType::NAMES)
+            )),
         }
     }
 }
 
+fn unknown_subtype<T: fmt::Display>(event: &str, all: &[T], subtype: &str) -> String {
+    let names: Vec<String> = all
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    format!(
+        "Unknown {event} subtype: {subtype}{}",
+        suggest::hint(subtype, names.iter().map(String::as_str))
+    )
+}
+
+/// Match a subtype by its display name, case-insensitively.
+fn find_subtype<T: Copy + fmt::Display>(all: &[T], subtype: &str) -> Option<T> {
+    all.iter().copied().find(|t| {
+        t.to_string()
+            .eq_ignore_ascii_case(subtype)
+    })
+}
+
 impl fmt::Display for EventType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -207,10 +389,70 @@ impl fmt::Display for EventType {
             EventType::Layout => write!(f, "layout"),
             EventType::Group(subtype) => write!(f, "group {subtype}"),
             EventType::Config => write!(f, "config"),
+            EventType::Dpms(subtype) => write!(f, "dpms {subtype}"),
+            EventType::Schedule(schedule) => write!(f, "schedule {schedule}"),
         }
     }
 }
 
+/// Conditions checked against the compositor's state when a reaction is triggered.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct When {
+    /// Keywords that must currently hold the given values, e.g. `"general:layout" = "master"`.
+    #[serde(default)]
+    pub keyword: BTreeMap<String, KeywordValue>,
+    /// Whether `hyde-ipc dnd` must be on or off.
+    #[serde(default)]
+    pub dnd: Option<bool>,
+    /// Built-in variables that must have the given values, e.g. `host = "laptop"`.
+    #[serde(default)]
+    pub env: BTreeMap<String, KeywordValue>,
+}
+
+impl When {
+    pub fn holds(&self) -> Result<bool, String> {
+        if self
+            .dnd
+            .is_some_and(|dnd| dnd != dnd::is_enabled())
+        {
+            return Ok(false);
+        }
+        for (keyword, expected) in &self.keyword {
+            let current = query_cache::get(&format!("keyword:{keyword}"), || {
+                Keyword::get(keyword)
+                    .map(|keyword| keyword.value)
+                    .map_err(|e| format!("{keyword}: {e}"))
+            })?;
+            if !expected.matches(&current) {
+                return Ok(false);
+            }
+        }
+        if !self.env.is_empty() {
+            let vars = environment::vars();
+            for (name, expected) in &self.env {
+                let value = vars.get(name).ok_or_else(|| {
+                    format!(
+                        "Unknown variable {name} in when.env, available: {}",
+                        environment::NAMES.join(", ")
+                    )
+                })?;
+                if !expected.matches(&OptionValue::String(value.to_string())) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Set by `react --once`.
+static EXIT_AFTER_FIRST: AtomicBool = AtomicBool::new(false);
+
+/// Make the process exit as soon as any reaction has run its dispatchers.
+pub fn exit_after_first_reaction() {
+    EXIT_AFTER_FIRST.store(true, Ordering::SeqCst);
+}
+
 /// A reaction to a Hyprland event, which can dispatch one or more commands when triggered.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Reaction {
@@ -222,18 +464,117 @@ pub struct Reaction {
         deserialize_with = "deserialize_window_identifier"
     )]
     pub window_filter: Option<WindowIdentifier<'static>>,
+    /// Keyboard name pattern that `layout` events must come from.
+    #[serde(default)]
+    pub device: Option<String>,
     #[serde(default)]
     pub max_count: Option<usize>,
+    /// Only fire while the active workspace has at least this many windows.
+    #[serde(default)]
+    pub min_windows: Option<u16>,
+    /// Only fire while the active workspace has at most this many windows.
+    #[serde(default)]
+    pub max_windows: Option<u16>,
+    #[serde(default)]
+    pub when: Option<When>,
+    /// Only fire while a scene enables this reaction.
+    #[serde(default)]
+    pub disabled: bool,
+    /// Hyprland instance this reaction belongs to: `primary`, `nested`, or a signature prefix.
+    #[serde(default)]
+    pub instance: Option<String>,
     pub name: Option<String>,
     #[allow(dead_code)]
     pub description: Option<String>,
+    /// Journal chain progress so a chain interrupted by a crash can be reported or resumed.
+    #[serde(default)]
+    pub journal: Option<JournalMode>,
+    /// Don't fire again until this long after the last time, e.g. `"2s"`.
+    #[serde(
+        default,
+        deserialize_with = "units::deserialize_optional_duration"
+    )]
+    pub cooldown: Option<Duration>,
+    /// Names of reactions to the same event that must finish before this one runs.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_names"
+    )]
+    pub after: Vec<String>,
+    /// Run the chain once per connected monitor, with `{monitor}` set to its name.
+    #[serde(default)]
+    pub each_monitor: bool,
     #[serde(skip)]
     pub counter: Arc<AtomicUsize>,
+    #[serde(skip)]
+    pub stats: Arc<ReactionStats>,
+    /// The daemon's ignore list, for `foreach`.
+    #[serde(skip)]
+    pub ignore: Arc<IgnoreList>,
+}
+
+/// Counts reported on `SIGUSR1`.
+#[derive(Debug, Default)]
+pub struct ReactionStats {
+    /// Events that reached the reaction, whether or not it fired.
+    pub triggered: AtomicUsize,
+    pub fired: AtomicUsize,
+    /// Runs that failed, and dispatchers in a chain that couldn't be run.
+    pub errors: AtomicUsize,
+    pub last_fired: Mutex<Option<Instant>>,
+    pub last_error: Mutex<Option<Instant>>,
+}
+
+impl ReactionStats {
+    pub fn record_error(&self) {
+        self.errors
+            .fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap() = Some(Instant::now());
+    }
 }
 
 impl Reaction {
-    /// Execute this reaction and all chained dispatchers.
-    pub fn execute(&self) -> Result<bool, String> {
+    /// Execute this reaction and all chained dispatchers. `event` holds the payload fields of the
+    /// event that triggered it.
+    pub fn execute(&self, event: &Vars) -> Result<bool, String> {
+        let result = self.try_execute(event);
+        if result.is_err() {
+            self.stats.record_error();
+        }
+        result
+    }
+
+    fn try_execute(&self, event: &Vars) -> Result<bool, String> {
+        let received = latency::is_enabled().then(|| last_read_at().unwrap_or_else(Instant::now));
+        let reaction_name = self
+            .name
+            .as_deref()
+            .unwrap_or("unnamed");
+        crash::record_event(format!("{} {event:?} for '{reaction_name}'", self.event_type));
+        let _running = crash::running(reaction_name);
+        self.stats
+            .triggered
+            .fetch_add(1, Ordering::Relaxed);
+        if ctl::is_paused() || !scene::is_reaction_enabled(self.name.as_deref(), self.disabled) {
+            return Ok(false);
+        }
+
+        if !self.window_count_matches()?
+            || !self
+                .when
+                .as_ref()
+                .map_or(Ok(true), When::holds)?
+        {
+            return Ok(false);
+        }
+
+        if let Some(cooldown) = self.cooldown {
+            let last_fired = *self.stats.last_fired.lock().unwrap();
+            if last_fired.is_some_and(|fired| fired.elapsed() < cooldown) {
+                return Ok(false);
+            }
+        }
+
         let max_count = self.max_count.unwrap_or(0);
         if max_count > 0 {
             let current = self
@@ -244,28 +585,224 @@ impl Reaction {
                 println!("Reached maximum reaction count ({max_count})");
                 return Ok(false);
             }
+            if let Some(name) = &self.name {
+                if let Err(e) = reaction_state::save_counter(name, current) {
+                    eprintln!("Error saving reaction state: {e}");
+                }
+            }
         }
 
         if self.dispatchers.is_empty() {
             return Err("No dispatchers defined for this reaction".to_string());
         }
 
-        let reaction_name = self
-            .name
-            .as_deref()
-            .unwrap_or("unnamed");
         println!(
             "Executing reaction '{reaction_name}' for event '{}': {} dispatchers",
             self.event_type,
             self.dispatchers.len()
         );
 
-        for (index, dispatcher) in self.dispatchers.iter().enumerate() {
-            println!("  - Dispatcher {}/{}: {:?}", index + 1, self.dispatchers.len(), dispatcher);
-            handle_dispatch(dispatcher.clone().into(), false);
+        if self.each_monitor {
+            for monitor in monitor_names()? {
+                let mut vars = event.clone();
+                println!("  On monitor {monitor}:");
+                vars.set("monitor", monitor);
+                self.run_chain(0, &vars);
+            }
+        } else {
+            self.run_chain(0, event);
+        }
+        self.stats
+            .fired
+            .fetch_add(1, Ordering::Relaxed);
+        *self.stats.last_fired.lock().unwrap() = Some(Instant::now());
+        if let Some(name) = self
+            .name
+            .as_deref()
+            .filter(|_| self.cooldown.is_some())
+        {
+            if let Err(e) = reaction_state::save_fired(name) {
+                eprintln!("Error saving reaction state: {e}");
+            }
+        }
+        if let Some(received) = received {
+            latency::record(reaction_name, received);
+        }
+        if EXIT_AFTER_FIRST.load(Ordering::SeqCst) {
+            std::process::exit(0);
         }
         Ok(true)
     }
+
+    /// Whether `other` reacts to the same event under the same conditions with the same chain, so
+    /// that loading both would run every dispatcher twice. Names and descriptions are ignored.
+    pub fn is_duplicate_of(&self, other: &Reaction) -> bool {
+        self.event_type == other.event_type
+            && self
+                .window_filter
+                .as_ref()
+                .map(ToString::to_string)
+                == other
+                    .window_filter
+                    .as_ref()
+                    .map(ToString::to_string)
+            && self.device == other.device
+            && self.max_count == other.max_count
+            && self.min_windows == other.min_windows
+            && self.max_windows == other.max_windows
+            && self.cooldown == other.cooldown
+            && self.when == other.when
+            && self.disabled == other.disabled
+            && self.instance == other.instance
+            && self.journal == other.journal
+            && self.after == other.after
+            && self.each_monitor == other.each_monitor
+            && self.dispatchers == other.dispatchers
+    }
+
+    /// Whether the window filter lets a window through. An `address:` filter matches the window's
+    /// address exactly; class and title filters match as patterns.
+    fn window_matches(&self, address: &str, class: &str, title: &str) -> bool {
+        match &self.window_filter {
+            Some(WindowIdentifier::Address(filter)) => filter.to_string() == address,
+            filter => is_window_match(filter.as_ref(), class, title),
+        }
+    }
+
+    /// Execute for an event that only identifies its window by address, like `closed`, with its
+    /// class and title looked up in `windows`. A window the cache doesn't know has neither, so
+    /// only an `address:` filter can match it.
+    fn execute_for_address(&self, windows: &WindowCache, mut event: Vars) {
+        let address = event
+            .get("address")
+            .unwrap_or_default()
+            .to_string();
+        let window = windows
+            .get(&address)
+            .unwrap_or_default();
+        if !self.window_matches(&address, &window.class, &window.title) {
+            return;
+        }
+        event
+            .set("class", window.class)
+            .set("title", window.title);
+        if let Err(e) = self.execute(&event) {
+            eprintln!("Error executing reaction: {e}");
+        }
+    }
+
+    fn window_count_matches(&self) -> Result<bool, String> {
+        if self.min_windows.is_none() && self.max_windows.is_none() {
+            return Ok(true);
+        }
+        let windows = query_cache::get("activeworkspace", || {
+            Workspace::get_active()
+                .map(|workspace| workspace.windows)
+                .map_err(|e| e.to_string())
+        })?;
+        Ok(self
+            .min_windows
+            .is_none_or(|min| windows >= min)
+            && self
+                .max_windows
+                .is_none_or(|max| windows <= max))
+    }
+
+    /// Values for the `{placeholder}`s in this reaction's dispatchers, with the event's payload
+    /// fields empty.
+    pub fn template_vars(&self) -> Vars {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut vars = Vars::new();
+        vars.set(
+            "reaction",
+            self.name
+                .as_deref()
+                .unwrap_or("unnamed"),
+        )
+        .set("event", &self.event_type)
+        .set("count", self.stats.fired.load(Ordering::Relaxed) + 1)
+        .set("time", time);
+        vars.extend(&environment::vars());
+        for field in self.event_type.payload_fields() {
+            vars.set(field, "");
+        }
+        if self.each_monitor {
+            vars.set("monitor", "");
+        }
+        vars
+    }
+
+    /// Run the dispatcher chain starting at index `from`.
+    pub fn run_chain(&self, from: usize, event: &Vars) {
+        let reaction_name = self
+            .name
+            .as_deref()
+            .unwrap_or("unnamed");
+        let total = self.dispatchers.len();
+        let mut vars = self.template_vars();
+        vars.extend(event);
+        // The event's values go into the journal, so a resumed chain fills in the same
+        // placeholders.
+        let saved_vars = if self.journal.is_some() { event.to_strings() } else { BTreeMap::new() };
+        let mut failed_at = None;
+        for (index, dispatcher) in self
+            .dispatchers
+            .iter()
+            .enumerate()
+            .skip(from)
+        {
+            // Once a dispatcher failed the journal stays on it, so it is reported or run again.
+            if self.journal.is_some() && failed_at.is_none() {
+                let entry = JournalEntry {
+                    reaction: reaction_name.to_string(),
+                    next: index,
+                    total,
+                    vars: saved_vars.clone(),
+                };
+                if let Err(e) = journal::record(&entry) {
+                    eprintln!("Error journaling reaction '{reaction_name}': {e}");
+                }
+            }
+            if let Err(e) = self.run_step(index, dispatcher, &vars) {
+                eprintln!("Error: {e}");
+                self.stats.record_error();
+                failed_at.get_or_insert(index);
+            }
+        }
+        query_cache::clear();
+        if self.journal.is_some() && failed_at.is_none() {
+            if let Err(e) = journal::clear(reaction_name) {
+                eprintln!("Error journaling reaction '{reaction_name}': {e}");
+            }
+        }
+    }
+
+    /// Fill in and run dispatcher `index` of the chain.
+    fn run_step(&self, index: usize, dispatcher: &Dispatcher, vars: &Vars) -> Result<(), String> {
+        let dispatcher = dispatcher.render(vars)?;
+        println!("  - Dispatcher {}/{}: {:?}", index + 1, self.dispatchers.len(), dispatcher);
+        // Its windows' placeholders come on top of the reaction's.
+        if let Dispatcher::ForEach(conditions, command) = &dispatcher {
+            return foreach::run(conditions, command, vars, &self.ignore);
+        }
+        run_dispatch(Dispatch::try_from(dispatcher)?, false)
+    }
+}
+
+/// Names of the enabled monitors, for `each_monitor`.
+fn monitor_names() -> Result<Vec<String>, String> {
+    Monitors::get()
+        .map(|monitors| {
+            monitors
+                .into_iter()
+                .filter(|monitor| !monitor.disabled)
+                .map(|monitor| monitor.name)
+                .collect()
+        })
+        .map_err(|e| e.to_string())
 }
 
 pub fn deserialize_window_identifier<'de, D>(
@@ -283,9 +820,36 @@ where
     .transpose()
 }
 
+/// A single name or a list of names.
+fn deserialize_names<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Names {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Names::deserialize(deserializer)? {
+        Names::One(name) => vec![name],
+        Names::Many(names) => names,
+    })
+}
+
 #[derive(Default, Debug)]
 pub struct ReactionManager {
     reactions: Vec<Arc<Reaction>>,
+    placements: Vec<Arc<Placement>>,
+    app_modes: Arc<AppModes>,
+    ignore: Arc<IgnoreList>,
+    /// Open windows, for `closed` and `moved` reactions with a window filter.
+    windows: Arc<WindowCache>,
+    exposed: Arc<Vec<expose::State>>,
+    /// Keywords from the config's `[reassert]` table.
+    reasserted: Arc<AppMode>,
+    /// Config file the reactions came from, re-read on `SIGHUP`.
+    config_path: Option<PathBuf>,
 }
 
 impl ReactionManager {
@@ -297,23 +861,127 @@ impl ReactionManager {
         self.reactions.push(Arc::new(reaction));
     }
 
-    pub fn start(self) -> Result<(), String> {
+    pub fn add_placement(&mut self, placement: Placement) {
+        self.placements
+            .push(Arc::new(placement));
+    }
+
+    pub fn set_app_modes(&mut self, app_modes: AppModes) {
+        self.app_modes = Arc::new(app_modes);
+    }
+
+    /// The list shared with the reactions, so it tracks the same open windows.
+    pub fn set_ignore_list(&mut self, ignore: Arc<IgnoreList>) {
+        self.ignore = ignore;
+    }
+
+    pub fn set_exposed(&mut self, exposed: Vec<expose::State>) {
+        self.exposed = Arc::new(exposed);
+    }
+
+    pub fn set_reasserted(&mut self, keywords: AppMode) {
+        self.reasserted = Arc::new(keywords);
+    }
+
+    pub fn set_config_path(&mut self, path: PathBuf) {
+        self.config_path = Some(path);
+    }
+
+    pub fn start(mut self) -> Result<(), String> {
+        crash::install_hook();
+        self.reactions.retain(|reaction| {
+            let Some(scope) = &reaction.instance else {
+                return true;
+            };
+            let in_scope = instance::matches(scope);
+            if !in_scope {
+                println!(
+                    "Skipping reaction '{}' scoped to instance '{scope}'",
+                    reaction
+                        .name
+                        .as_deref()
+                        .unwrap_or("unnamed")
+                );
+            }
+            in_scope
+        });
         println!("Starting reaction manager with {} reactions", self.reactions.len());
+        self.restore_state();
+        self.recover_journals();
         let mut event_listener = EventListener::new();
 
+        // Handlers run in registration order, so ignored windows are tracked before any reaction
+        // sees them and forgotten only after every reaction has seen them close.
+        if !self.ignore.is_empty() {
+            println!("Ignoring windows matching {} patterns", self.ignore.len());
+            self.ignore.track_existing();
+            let ignore = Arc::clone(&self.ignore);
+            event_listener.add_window_opened_handler(move |data| {
+                ignore.track(&data.window_address, &data.window_class, &data.window_title);
+            });
+        }
+
+        if self.needs_window_cache() {
+            self.track_windows(&mut event_listener);
+        }
+        cycle::track_focus(&mut event_listener);
+
+        // Hyprland puts its configured cursor back on reload.
+        cursor::reassert();
+        event_listener.add_config_reloaded_handler(cursor::reassert);
+        let reasserted = Arc::clone(&self.reasserted);
+        event_listener.add_config_reloaded_handler(move || reassert::apply(&reasserted));
+
+        if !self.exposed.is_empty() {
+            expose::publish(&self.exposed, None);
+            let exposed = Arc::clone(&self.exposed);
+            let app_modes = Arc::clone(&self.app_modes);
+            event_listener.add_config_reloaded_handler(move || {
+                expose::publish(&exposed, app_modes.active_mode().as_deref());
+            });
+        }
+
         for reaction in &self.reactions {
             self.setup_handler(&mut event_listener, Arc::clone(reaction));
         }
 
-        event_listener
-            .start_listener()
-            .map_err(|e| e.to_string())
+        if !self.placements.is_empty() {
+            println!("Applying {} placement policies", self.placements.len());
+        }
+        for placement in &self.placements {
+            self.setup_placement_handler(&mut event_listener, Arc::clone(placement));
+        }
+
+        if !self.app_modes.is_empty() {
+            println!("Loaded {} app modes", self.app_modes.len());
+            self.setup_app_modes_handler(&mut event_listener);
+        }
+
+        if !self.ignore.is_empty() {
+            let ignore = Arc::clone(&self.ignore);
+            event_listener.add_window_closed_handler(move |address| ignore.forget(&address));
+        }
+        if self.needs_window_cache() {
+            let windows = Arc::clone(&self.windows);
+            event_listener
+                .add_window_closed_handler(move |address| windows.forget(&address.to_string()));
+        }
+
+        self.spawn_dpms_watcher();
+        self.spawn_schedulers();
+        signals::spawn_handler(self.config_path.clone(), self.reactions.clone());
+        status::spawn_publisher(self.config_path.clone(), self.reactions.clone());
+
+        if let Err(e) = event_listener.start_listener() {
+            failure::listener_failed(e);
+        }
+        Ok(())
     }
 
     fn setup_handler(&self, event_listener: &mut EventListener, reaction: Arc<Reaction>) {
         let handler_reaction = Arc::clone(&reaction);
-        let handler = move || {
-            if let Err(e) = handler_reaction.execute() {
+        let handler = move |event: Vars| {
+            if let Err(e) = handler_reaction.execute(&event) {
                 eprintln!("Error executing reaction: {e}");
             }
         };
@@ -326,18 +994,269 @@ impl ReactionManager {
                 self.setup_workspace_handler(event_listener, subtype, handler)
             },
             EventType::Monitor => {
-                event_listener.add_active_monitor_changed_handler(move |_| handler())
+                event_listener.add_active_monitor_changed_handler(move |_| handler(Vars::new()))
             },
-            EventType::Float => event_listener.add_float_state_changed_handler(move |_| handler()),
+            EventType::Float => event_listener.add_float_state_changed_handler(move |data| {
+                handler(Vars::from([("address", data.address.to_string())]))
+            }),
             EventType::Fullscreen => {
-                event_listener.add_fullscreen_state_changed_handler(move |_| handler())
+                event_listener.add_fullscreen_state_changed_handler(move |_| handler(Vars::new()))
+            },
+            EventType::Layout => {
+                let device = reaction.device.clone();
+                event_listener.add_layout_changed_handler(move |event| {
+                    if device
+                        .as_deref()
+                        .is_none_or(|device| pattern_matches(&event.keyboard_name, device))
+                    {
+                        handler(Vars::new());
+                    }
+                })
             },
-            EventType::Layout => event_listener.add_layout_changed_handler(move |_| handler()),
             EventType::Group(subtype) => self.setup_group_handler(event_listener, subtype, handler),
-            EventType::Config => event_listener.add_config_reloaded_handler(handler),
+            EventType::Config => {
+                event_listener.add_config_reloaded_handler(move || handler(Vars::new()))
+            },
+            // Driven by the poller spawned in `start`.
+            EventType::Dpms(_) => {},
+            // Driven by the timers spawned in `start`.
+            EventType::Schedule(_) => {},
+        }
+    }
+
+    /// Whether any reaction needs the class or title of a window that closed or moved.
+    fn needs_window_cache(&self) -> bool {
+        self.reactions.iter().any(|r| {
+            matches!(
+                r.event_type,
+                EventType::Window(WindowEventType::Closed | WindowEventType::Moved)
+            )
+        })
+    }
+
+    /// Keep the window cache current. Registered before any reaction, so reactions see a window
+    /// as it is after the event; it is forgotten on close only after every reaction has run.
+    fn track_windows(&self, event_listener: &mut EventListener) {
+        self.windows.track_existing();
+        let windows = Arc::clone(&self.windows);
+        event_listener.add_window_opened_handler(move |data| {
+            windows.opened(data.window_address.to_string(), Window {
+                class: data.window_class.clone(),
+                title: data.window_title.clone(),
+                workspace: data.workspace_name.clone(),
+            });
+        });
+        let windows = Arc::clone(&self.windows);
+        event_listener.add_active_window_changed_handler(move |data| {
+            if let Some(data) = data {
+                windows.renamed(&data.address.to_string(), Some(&data.class), &data.title);
+            }
+        });
+        let windows = Arc::clone(&self.windows);
+        event_listener.add_window_title_changed_handler(move |data| {
+            windows.renamed(&data.address.to_string(), None, &data.title);
+        });
+        let windows = Arc::clone(&self.windows);
+        event_listener.add_window_moved_handler(move |data| {
+            windows.moved(&data.window_address.to_string(), data.workspace_name.to_string());
+        });
+    }
+
+    fn spawn_dpms_watcher(&self) {
+        let reactions: Vec<(DpmsEventType, Arc<Reaction>)> = self
+            .reactions
+            .iter()
+            .filter_map(|r| match r.event_type {
+                EventType::Dpms(subtype) => Some((subtype, Arc::clone(r))),
+                _ => None,
+            })
+            .collect();
+        if reactions.is_empty() {
+            return;
+        }
+        thread::spawn(move || {
+            dpms::watch(false, |monitor, on| {
+                let state = if on { "on" } else { "off" };
+                println!("Monitor {monitor} DPMS {state}");
+                let event = Vars::from([
+                    ("monitor", monitor.to_string()),
+                    ("state", state.to_string()),
+                ]);
+                for (subtype, reaction) in &reactions {
+                    if subtype.matches(on) {
+                        if let Err(e) = reaction.execute(&event) {
+                            eprintln!("Error executing reaction: {e}");
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn spawn_schedulers(&self) {
+        for reaction in &self.reactions {
+            let EventType::Schedule(schedule) = &reaction.event_type else {
+                continue;
+            };
+            let name = reaction
+                .name
+                .clone()
+                .unwrap_or_else(|| schedule.to_string());
+            match schedule.next_at() {
+                Some(at) => println!(
+                    "Reaction '{name}' runs {schedule}, next at {}",
+                    history::format_time(at)
+                ),
+                None => println!("Reaction '{name}' runs {schedule}"),
+            }
+            let schedule = schedule.clone();
+            let reaction = Arc::clone(reaction);
+            thread::spawn(move || {
+                schedule.run(|| {
+                    if let Err(e) = reaction.execute(&Vars::new()) {
+                        eprintln!("Error executing reaction: {e}");
+                    }
+                })
+            });
+        }
+    }
+
+    /// Report or resume chains left unfinished by a previous run.
+    fn recover_journals(&self) {
+        for entry in journal::pending() {
+            let reaction = self.reactions.iter().find(|r| {
+                r.journal.is_some() && r.name.as_deref() == Some(entry.reaction.as_str())
+            });
+            match reaction {
+                Some(reaction) if reaction.journal == Some(JournalMode::Resume) => {
+                    println!(
+                        "Resuming reaction '{}' at dispatcher {}/{}",
+                        entry.reaction,
+                        entry.next + 1,
+                        entry.total
+                    );
+                    let mut event = Vars::new();
+                    for name in reaction.template_vars().names() {
+                        if let Some(value) = entry.vars.get(name) {
+                            event.set(name, value);
+                        }
+                    }
+                    // The chain clears the journal itself, or leaves it on a dispatcher that
+                    // failed again.
+                    reaction.run_chain(entry.next, &event);
+                    continue;
+                },
+                _ => println!(
+                    "Reaction '{}' stopped at dispatcher {}/{}, {} dispatchers did not complete",
+                    entry.reaction,
+                    entry.next + 1,
+                    entry.total,
+                    entry.total - entry.next
+                ),
+            }
+            if let Err(e) = journal::clear(&entry.reaction) {
+                eprintln!("Error journaling reaction '{}': {e}", entry.reaction);
+            }
+        }
+    }
+
+    /// Pick up the counts and cooldowns of reactions that already fired before the daemon was
+    /// restarted.
+    fn restore_state(&self) {
+        reaction_state::set_config_path(self.config_path.as_deref());
+        let kept = || {
+            self.reactions
+                .iter()
+                .filter(|r| r.max_count.is_some_and(|n| n > 0) || r.cooldown.is_some())
+        };
+        let saved = reaction_state::restore(kept().filter_map(|r| r.name.as_deref()));
+        for reaction in kept() {
+            let Some((name, saved)) = reaction
+                .name
+                .as_ref()
+                .and_then(|name| Some((name, saved.get(name)?)))
+            else {
+                continue;
+            };
+            if reaction
+                .max_count
+                .is_some_and(|n| n > 0)
+                && saved.count > 0
+            {
+                println!("Reaction '{name}' already fired {} times in this session", saved.count);
+                reaction
+                    .counter
+                    .store(saved.count, Ordering::SeqCst);
+            }
+            if reaction.cooldown.is_some() {
+                *reaction
+                    .stats
+                    .last_fired
+                    .lock()
+                    .unwrap() = saved.last_fired();
+            }
+        }
+    }
+
+    fn setup_placement_handler(
+        &self,
+        event_listener: &mut EventListener,
+        placement: Arc<Placement>,
+    ) {
+        let opened_placement = Arc::clone(&placement);
+        let ignore = Arc::clone(&self.ignore);
+        event_listener.add_window_opened_handler(move |data| {
+            if ignore.is_ignored(&data.window_class, &data.window_title) || ctl::is_paused() {
+                return;
+            }
+            if opened_placement.matches(&data.window_class, &data.window_title) {
+                if let Err(e) = opened_placement.apply(&data.window_address) {
+                    let name = opened_placement
+                        .name
+                        .as_deref()
+                        .unwrap_or("unnamed");
+                    eprintln!("Error applying placement '{name}': {e}");
+                }
+            }
+        });
+
+        if placement.is_remembered() {
+            // Record where a window was left each time focus moves away from it.
+            let last_active = std::sync::Mutex::new(None);
+            event_listener.add_active_window_changed_handler(move |data| {
+                let mut last_active = last_active.lock().unwrap();
+                if let Some(address) = last_active.take() {
+                    // The window may already be gone, which is not an error here.
+                    let _ = placement.remember(&address);
+                }
+                *last_active = data.map(|win| win.address);
+            });
         }
     }
 
+    fn setup_app_modes_handler(&self, event_listener: &mut EventListener) {
+        let focus_modes = Arc::clone(&self.app_modes);
+        let ignore = Arc::clone(&self.ignore);
+        let exposed = Arc::clone(&self.exposed);
+        event_listener.add_active_window_changed_handler(move |data| {
+            if data
+                .as_ref()
+                .is_some_and(|win| ignore.is_ignored(&win.class, &win.title))
+            {
+                return;
+            }
+            focus_modes.focus_changed(
+                data.as_ref()
+                    .map(|win| win.class.as_str()),
+            );
+            let mode = focus_modes.active_mode();
+            expose::set(&exposed, expose::State::AppMode, mode.as_deref().unwrap_or_default());
+        });
+
+        let reload_modes = Arc::clone(&self.app_modes);
+        event_listener.add_config_reloaded_handler(move || reload_modes.config_reloaded());
+    }
+
     fn setup_window_handler(
         &self,
         event_listener: &mut EventListener,
@@ -345,15 +1264,14 @@ impl ReactionManager {
         reaction: Arc<Reaction>,
     ) {
         let window_handler_reaction = Arc::clone(&reaction);
-        let window_handler = move |class: &str, title: &str| {
-            if is_window_match(
-                window_handler_reaction
-                    .window_filter
-                    .as_ref(),
-                class,
-                title,
-            ) {
-                if let Err(e) = window_handler_reaction.execute() {
+        let ignore = Arc::clone(&self.ignore);
+        let window_handler = move |class: &str, title: &str, event: Vars| {
+            if ignore.is_ignored(class, title) {
+                return;
+            }
+            let address = event.get("address").unwrap_or_default();
+            if window_handler_reaction.window_matches(address, class, title) {
+                if let Err(e) = window_handler_reaction.execute(&event) {
                     eprintln!("Error executing reaction: {e}");
                 }
             }
@@ -362,49 +1280,79 @@ impl ReactionManager {
         match subtype {
             WindowEventType::Opened => {
                 event_listener.add_window_opened_handler(move |data| {
-                    window_handler(&data.window_class, &data.window_title);
+                    window_handler(
+                        &data.window_class,
+                        &data.window_title,
+                        Vars::from([
+                            ("address", data.window_address.to_string()),
+                            ("class", data.window_class.clone()),
+                            ("title", data.window_title.clone()),
+                            ("workspace", data.workspace_name.clone()),
+                        ]),
+                    );
                 });
             },
             WindowEventType::Active => {
                 let active_handler_reaction = Arc::clone(&reaction);
                 event_listener.add_active_window_changed_handler(move |data| {
                     if let Some(win_data) = data {
-                        window_handler(&win_data.class, &win_data.title);
+                        window_handler(&win_data.class, &win_data.title, window_vars(&win_data));
                     } else if active_handler_reaction
                         .window_filter
                         .is_some()
                     {
-                    } else if let Err(e) = active_handler_reaction.execute() {
+                    } else if let Err(e) = active_handler_reaction.execute(&Vars::new()) {
                         eprintln!("Error executing reaction: {e}");
                     }
                 });
             },
+            WindowEventType::ClassChanged => {
+                // Focus moving to an empty workspace keeps the last class, so returning to the
+                // same application does not fire again.
+                let last_class = std::sync::Mutex::new(None::<String>);
+                event_listener.add_active_window_changed_handler(move |data| {
+                    let Some(win_data) = data else {
+                        return;
+                    };
+                    let mut last_class = last_class.lock().unwrap();
+                    if last_class.as_deref() != Some(win_data.class.as_str()) {
+                        *last_class = Some(win_data.class.clone());
+                        drop(last_class);
+                        window_handler(&win_data.class, &win_data.title, window_vars(&win_data));
+                    }
+                });
+            },
             WindowEventType::Closed => {
                 let closed_handler_reaction = Arc::clone(&reaction);
-                event_listener.add_window_closed_handler(move |_| {
-                    if closed_handler_reaction
-                        .window_filter
-                        .is_some()
-                    {
-                        println!("Note: Window filter is not applicable to 'closed' events.");
+                let ignore = Arc::clone(&self.ignore);
+                let windows = Arc::clone(&self.windows);
+                event_listener.add_window_closed_handler(move |address| {
+                    if ignore.is_address_ignored(&address) {
+                        return;
                     }
-                    if let Err(e) = closed_handler_reaction.execute() {
-                        eprintln!("Error executing reaction: {e}");
+                    let mut event = Vars::from([("address", address.to_string())]);
+                    if let Some(window) = windows.get(&address.to_string()) {
+                        event.set("workspace", window.workspace);
                     }
+                    closed_handler_reaction.execute_for_address(&windows, event);
                 });
             },
             WindowEventType::Moved => {
                 let moved_handler_reaction = Arc::clone(&reaction);
-                event_listener.add_window_moved_handler(move |_| {
-                    if moved_handler_reaction
-                        .window_filter
-                        .is_some()
-                    {
-                        println!("Note: Window filter is not applicable to 'moved' events.");
-                    }
-                    if let Err(e) = moved_handler_reaction.execute() {
-                        eprintln!("Error executing reaction: {e}");
+                let ignore = Arc::clone(&self.ignore);
+                let windows = Arc::clone(&self.windows);
+                event_listener.add_window_moved_handler(move |data| {
+                    if ignore.is_address_ignored(&data.window_address) {
+                        return;
                     }
+                    moved_handler_reaction.execute_for_address(
+                        &windows,
+                        Vars::from([
+                            ("address", data.window_address.to_string()),
+                            ("workspace", data.workspace_name.to_string()),
+                            ("workspace_id", data.workspace_id.to_string()),
+                        ]),
+                    );
                 });
             },
         }
@@ -414,18 +1362,18 @@ impl ReactionManager {
         &self,
         event_listener: &mut EventListener,
         subtype: WorkspaceEventType,
-        handler: impl Fn() + Send + Sync + 'static,
+        handler: impl Fn(Vars) + Send + Sync + 'static,
     ) {
+        let handler = move |data: WorkspaceEventData| {
+            handler(Vars::from([
+                ("workspace", data.name.to_string()),
+                ("workspace_id", data.id.to_string()),
+            ]))
+        };
         match subtype {
-            WorkspaceEventType::Changed => {
-                event_listener.add_workspace_changed_handler(move |_| handler())
-            },
-            WorkspaceEventType::Added => {
-                event_listener.add_workspace_added_handler(move |_| handler())
-            },
-            WorkspaceEventType::Deleted => {
-                event_listener.add_workspace_deleted_handler(move |_| handler())
-            },
+            WorkspaceEventType::Changed => event_listener.add_workspace_changed_handler(handler),
+            WorkspaceEventType::Added => event_listener.add_workspace_added_handler(handler),
+            WorkspaceEventType::Deleted => event_listener.add_workspace_deleted_handler(handler),
         }
     }
 
@@ -433,52 +1381,81 @@ impl ReactionManager {
         &self,
         event_listener: &mut EventListener,
         subtype: GroupEventType,
-        handler: impl Fn() + Send + Sync + 'static,
+        handler: impl Fn(Vars) + Send + Sync + 'static,
     ) {
         match subtype {
-            GroupEventType::Toggled => event_listener.add_group_toggled_handler(move |_| handler()),
+            GroupEventType::Toggled => {
+                event_listener.add_group_toggled_handler(move |_| handler(Vars::new()))
+            },
             GroupEventType::MovedIn => {
-                event_listener.add_window_moved_into_group_handler(move |_| handler())
+                event_listener.add_window_moved_into_group_handler(move |address| {
+                    handler(Vars::from([("address", address.to_string())]))
+                })
             },
             GroupEventType::MovedOut => {
-                event_listener.add_window_moved_out_of_group_handler(move |_| handler())
+                event_listener.add_window_moved_out_of_group_handler(move |address| {
+                    handler(Vars::from([("address", address.to_string())]))
+                })
             },
         }
     }
 }
 
-fn is_window_match(
+/// Payload fields of a focus change.
+fn window_vars(window: &WindowEventData) -> Vars {
+    Vars::from([
+        ("address", window.address.to_string()),
+        ("class", window.class.clone()),
+        ("title", window.title.clone()),
+    ])
+}
+
+pub fn is_window_match(
     filter: Option<&WindowIdentifier>,
     window_class: &str,
     window_title: &str,
 ) -> bool {
     match filter {
-        Some(WindowIdentifier::ClassRegularExpression(pattern)) => window_class.contains(pattern),
-        Some(WindowIdentifier::Title(pattern)) => window_title.contains(pattern),
+        Some(WindowIdentifier::ClassRegularExpression(pattern)) => {
+            pattern_matches(window_class, pattern)
+        },
+        Some(WindowIdentifier::Title(pattern)) => pattern_matches(window_title, pattern),
         Some(_) => false,
         None => true,
     }
 }
 
 /// A dispatcher to be executed as part of a reaction chain.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Dispatcher {
-    Exec(Vec<String>),
+    Exec(Vec<String>, ExecRules),
+    Raw(String),
     KillActiveWindow,
-    ToggleFloating(Option<WindowId>),
+    /// The window, and whether to remember its floating size and position.
+    ToggleFloating(Option<WindowId>, bool),
     ToggleSplit,
+    SplitRatio(f32, bool),
     ToggleOpaque,
+    ToggleSwallow,
     MoveCursorToCorner(String),
-    MoveCursor(i64, i64),
+    MoveCursor(i64, i64, bool),
+    MoveCursorToWindow(String),
     ToggleFullscreen(Option<String>),
-    MoveToWorkspace(String),
+    /// The workspace, and whether to swap with a window already there.
+    MoveToWorkspace(String, bool),
     MoveToWorkspaceSilent(String, Option<WindowId>),
     Workspace(String),
-    CycleWindow(Option<String>),
+    /// Switch to the workspace, or back to the previous one if it is already focused.
+    WorkspaceToggle(String),
+    CycleWindow(Option<String>, CycleScopeArgs),
+    SwapNext(Option<String>),
     MoveFocus(String),
     SwapWindow(String),
-    FocusWindow(WindowId),
-    MoveWindow(String),
+    /// The window, and whether finding none is an error.
+    FocusWindow(WindowId, bool),
+    Pass(WindowId),
+    /// The target, and whether to swap with a window already there.
+    MoveWindow(String, bool),
     ToggleFakeFullscreen,
     TogglePseudo,
     TogglePin,
@@ -488,8 +1465,204 @@ pub enum Dispatcher {
     FocusCurrentOrLast,
     ForceRendererReload,
     Exit,
+    SwapWithMaster(Option<String>),
+    FocusMaster(Option<String>),
+    AddMaster,
+    RemoveMaster,
+    OrientationLeft,
+    OrientationRight,
+    OrientationTop,
+    OrientationBottom,
+    OrientationCenter,
+    OrientationNext,
+    OrientationPrev,
+    MoveGroupWindow(String),
     ResizeActive(ResizeCmd),
     ResizeWindowPixel(ResizeCmd, WindowId),
+    /// Keyboard device and layout (`next`, `prev` or an index).
+    SwitchLayout(String, String),
+    Submap(String),
+    /// Property, value, window (the active one if `None`) and whether to lock it.
+    SetProp(String, String, Option<WindowId>, bool),
+    SetCursor(String, u16),
+    /// Tag (`+tag`, `-tag` or a plain tag to toggle) and window, the active one if `None`.
+    TagWindow(String, Option<WindowId>),
+    /// Path and content, both templated.
+    WriteFile(String, String),
+    Signal(Target, String),
+    /// Milliseconds to wait before the next dispatcher.
+    Sleep(u64),
+    /// Summary, body and urgency.
+    Notify(String, Option<String>, Option<String>),
+    /// `where` conditions and the dispatcher's command line words, templated per window.
+    ForEach(Vec<String>, Vec<String>),
+    /// Any other dispatcher whose arguments use placeholders, parsed once they are filled in.
+    Template(Box<RawDispatcher>),
+}
+
+impl Dispatcher {
+    /// Dispatcher names accepted in config files.
+    pub const NAMES: [&str; 56] = [
+        "exec",
+        "raw",
+        "killactivewindow",
+        "togglefloating",
+        "togglesplit",
+        "splitratio",
+        "toggleopaque",
+        "toggleswallow",
+        "movecursortocorner",
+        "movecursor",
+        "movecursortowindow",
+        "togglefullscreen",
+        "movetoworkspace",
+        "movetoworkspacesilent",
+        "workspace",
+        "workspacetoggle",
+        "cyclewindow",
+        "swapnext",
+        "movefocus",
+        "swapwindow",
+        "focuswindow",
+        "pass",
+        "movewindow",
+        "togglefakefullscreen",
+        "togglepseudo",
+        "togglepin",
+        "centerwindow",
+        "bringactivetotop",
+        "focusurgentorlast",
+        "focuscurrentorlast",
+        "forcerendererreload",
+        "exit",
+        "swapwithmaster",
+        "focusmaster",
+        "addmaster",
+        "removemaster",
+        "orientationleft",
+        "orientationright",
+        "orientationtop",
+        "orientationbottom",
+        "orientationcenter",
+        "orientationnext",
+        "orientationprev",
+        "movegroupwindow",
+        "resizeactive",
+        "resizewindowpixel",
+        "switchlayout",
+        "submap",
+        "setprop",
+        "setcursor",
+        "tagwindow",
+        "writefile",
+        "signal",
+        "foreach",
+        "sleep",
+        "notify",
+    ];
+
+    /// Fill in the `{placeholder}`s of arguments that take them.
+    pub fn render(&self, vars: &Vars) -> Result<Self, String> {
+        match self {
+            Dispatcher::WriteFile(path, content) => Ok(Dispatcher::WriteFile(
+                template::render(path, vars)?,
+                template::render(content, vars)?,
+            )),
+            Dispatcher::Notify(summary, body, urgency) => Ok(Dispatcher::Notify(
+                template::render(summary, vars)?,
+                body.as_deref()
+                    .map(|body| template::render(body, vars))
+                    .transpose()?,
+                urgency.clone(),
+            )),
+            Dispatcher::Exec(command, rules) => {
+                let render_all = |values: &[String]| {
+                    values
+                        .iter()
+                        .map(|value| template::render_known(value, vars))
+                        .collect::<Result<Vec<_>, _>>()
+                };
+                Ok(Dispatcher::Exec(render_all(command)?, ExecRules {
+                    rules: render_all(&rules.rules)?,
+                    env: render_all(&rules.env)?,
+                    ..rules.clone()
+                }))
+            },
+            // Filled in per window when it runs; only checked here.
+            Dispatcher::ForEach(_, command) => {
+                foreach::validate_command(command, vars)?;
+                Ok(self.clone())
+            },
+            Dispatcher::Template(raw) => Dispatcher::from_raw::<de::value::Error>(&raw.fill(vars)?)
+                .map_err(|e| format!("{}: {e}", raw.name)),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// A dispatcher as written in the config, before its arguments are parsed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RawDispatcher {
+    name: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Window rules for `exec`, e.g. `["float", "workspace 3 silent"]`.
+    #[serde(default)]
+    rules: Vec<String>,
+    /// Environment variables for `exec`.
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    /// `false` to have `exec` wait for the command instead of Hyprland detaching it.
+    detach: Option<bool>,
+    /// Process name for `signal`.
+    process: Option<String>,
+    /// Pidfile for `signal`.
+    pidfile: Option<String>,
+    /// Signal for `signal`, e.g. `"SIGRTMIN+8"`.
+    sig: Option<String>,
+    /// Window conditions for `foreach`, e.g. `["floating", "workspace:active"]`.
+    #[serde(default, rename = "where")]
+    conditions: Vec<String>,
+    /// Urgency for `notify`: `low`, `normal` or `critical`.
+    urgency: Option<String>,
+}
+
+impl RawDispatcher {
+    /// Dispatchers that fill in the placeholders in their arguments themselves.
+    const RENDERS_ITSELF: [&str; 4] = ["exec", "writefile", "notify", "foreach"];
+
+    fn normalized_name(&self) -> String {
+        self.name
+            .to_lowercase()
+            .replace(['-', '_'], "")
+    }
+
+    /// Whether the arguments can only be parsed once the event filled in their placeholders.
+    fn has_placeholders(&self) -> bool {
+        !Self::RENDERS_ITSELF.contains(&self.normalized_name().as_str())
+            && self
+                .args
+                .iter()
+                .any(|arg| arg.contains('{'))
+    }
+
+    /// A copy with the placeholders in the arguments filled in. `raw` is handed to Hyprland as
+    /// written, so like `exec` it keeps braces that aren't placeholders.
+    pub fn fill(&self, vars: &Vars) -> Result<Self, String> {
+        let raw = self.normalized_name() == "raw";
+        let args =
+            self.args
+                .iter()
+                .map(|arg| {
+                    if raw {
+                        template::render_known(arg, vars)
+                    } else {
+                        template::render(arg, vars)
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+        Ok(Self { args, ..self.clone() })
+    }
 }
 
 impl<'de> Deserialize<'de> for Dispatcher {
@@ -497,14 +1670,24 @@ impl<'de> Deserialize<'de> for Dispatcher {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        struct Helper {
-            name: String,
-            #[serde(default)]
-            args: Vec<String>,
+        let raw = RawDispatcher::deserialize(deserializer)?;
+        if !raw.has_placeholders() {
+            return Self::from_raw(&raw);
         }
+        // Only the name can be checked before the event fills in the arguments.
+        if !Self::NAMES.contains(&raw.normalized_name().as_str()) {
+            return Err(de::Error::custom(format!(
+                "unknown dispatcher `{}`{}",
+                raw.name,
+                suggest::hint(&raw.name, Dispatcher::NAMES)
+            )));
+        }
+        Ok(Dispatcher::Template(Box::new(raw)))
+    }
+}
 
-        let h = Helper::deserialize(deserializer)?;
+impl Dispatcher {
+    fn from_raw<E: de::Error>(h: &RawDispatcher) -> Result<Self, E> {
         let args = &h.args;
 
         let get_arg = |i: usize| {
@@ -519,7 +1702,13 @@ impl<'de> Deserialize<'de> for Dispatcher {
                 .map_err(de::Error::custom)
         };
 
-        let parse_window_id = |i: usize| -> Result<WindowId, D::Error> {
+        let parse_float = |i: usize| -> Result<f32, E> {
+            get_arg(i)?
+                .parse()
+                .map_err(de::Error::custom)
+        };
+
+        let parse_window_id = |i: usize| -> Result<WindowId, E> {
             let s = get_arg(i)?;
             let parts: Vec<&str> = s.splitn(2, ':').collect();
             if parts.len() != 2 {
@@ -546,25 +1735,97 @@ impl<'de> Deserialize<'de> for Dispatcher {
             Ok(id)
         };
 
-        match h
-            .name
-            .to_lowercase()
-            .replace('-', "")
-            .as_str()
-        {
-            "exec" => Ok(Dispatcher::Exec(args.clone())),
+        // A flag word that may follow the arguments, like `strict` after `focuswindow`'s window.
+        let flag_at = |i: usize, flag: &str| -> Result<bool, E> {
+            match args.get(i) {
+                None => Ok(false),
+                Some(arg) if arg == flag => Ok(true),
+                Some(other) => Err(de::Error::custom(format!(
+                    "unexpected argument `{other}`, expected `{flag}`"
+                ))),
+            }
+        };
+
+        // `cyclewindow` takes an optional direction followed by any of the scope flags.
+        let parse_cycle_scope = || -> Result<(Option<String>, CycleScopeArgs), E> {
+            let mut direction = None;
+            let mut scope = CycleScopeArgs::default();
+            for (i, arg) in args.iter().enumerate() {
+                match arg.as_str() {
+                    "tiled" => scope.tiled = true,
+                    "floating" => scope.floating = true,
+                    "visible" => scope.visible = true,
+                    "same-class" => scope.same_class = true,
+                    _ if i == 0 => direction = Some(arg.clone()),
+                    other => {
+                        return Err(de::Error::unknown_variant(other, &[
+                            "tiled",
+                            "floating",
+                            "visible",
+                            "same-class",
+                        ]));
+                    },
+                }
+            }
+            if scope.tiled && scope.floating {
+                return Err(de::Error::custom("tiled and floating can't be combined"));
+            }
+            Ok((direction, scope))
+        };
+
+        match h.normalized_name().as_str() {
+            "exec" => {
+                let rules = ExecRules {
+                    rules: h.rules.clone(),
+                    env: h
+                        .env
+                        .iter()
+                        .map(|(key, value)| format!("{key}={value}"))
+                        .collect(),
+                    no_detach: h.detach == Some(false),
+                    ..ExecRules::default()
+                };
+                if rules.no_detach && !rules.rules.is_empty() {
+                    return Err(de::Error::custom(
+                        "exec window rules need Hyprland to start the command, they can't be used \
+                         with detach = false",
+                    ));
+                }
+                rules
+                    .env_vars()
+                    .map_err(de::Error::custom)?;
+                Ok(Dispatcher::Exec(args.clone(), rules))
+            },
+            "raw" => Ok(Dispatcher::Raw(args.join(" "))),
             "killactivewindow" => Ok(Dispatcher::KillActiveWindow),
-            "togglefloating" => Ok(Dispatcher::ToggleFloating(
-                args.first()
+            "togglefloating" => {
+                let window = args
+                    .first()
+                    .filter(|arg| *arg != "remember")
                     .map(|_| parse_window_id(0))
-                    .transpose()?,
-            )),
+                    .transpose()?;
+                let remember = flag_at(usize::from(window.is_some()), "remember")?;
+                Ok(Dispatcher::ToggleFloating(window, remember))
+            },
             "togglesplit" => Ok(Dispatcher::ToggleSplit),
+            "splitratio" => match args.first().map(String::as_str) {
+                Some("exact") => Ok(Dispatcher::SplitRatio(parse_float(1)?, true)),
+                _ => Ok(Dispatcher::SplitRatio(parse_float(0)?, false)),
+            },
             "toggleopaque" => Ok(Dispatcher::ToggleOpaque),
+            "toggleswallow" => Ok(Dispatcher::ToggleSwallow),
             "movecursortocorner" => Ok(Dispatcher::MoveCursorToCorner(get_arg(0)?)),
-            "movecursor" => Ok(Dispatcher::MoveCursor(parse_arg(0)?, parse_arg(1)?)),
+            "movecursor" => Ok(Dispatcher::MoveCursor(
+                parse_arg(0)?,
+                parse_arg(1)?,
+                args.get(2)
+                    .is_some_and(|a| a == "relative"),
+            )),
+            "movecursortowindow" => Ok(Dispatcher::MoveCursorToWindow(get_arg(0)?)),
             "togglefullscreen" => Ok(Dispatcher::ToggleFullscreen(args.first().cloned())),
-            "movetoworkspace" => Ok(Dispatcher::MoveToWorkspace(get_arg(0)?)),
+            "movetoworkspace" => {
+                Ok(Dispatcher::MoveToWorkspace(get_arg(0)?, flag_at(1, "swap-if-occupied")?))
+            },
             "movetoworkspacesilent" => Ok(Dispatcher::MoveToWorkspaceSilent(
                 get_arg(0)?,
                 args.get(1)
@@ -572,11 +1833,21 @@ impl<'de> Deserialize<'de> for Dispatcher {
                     .transpose()?,
             )),
             "workspace" => Ok(Dispatcher::Workspace(get_arg(0)?)),
-            "cyclewindow" => Ok(Dispatcher::CycleWindow(args.first().cloned())),
+            "workspacetoggle" => Ok(Dispatcher::WorkspaceToggle(get_arg(0)?)),
+            "cyclewindow" => {
+                let (direction, scope) = parse_cycle_scope()?;
+                Ok(Dispatcher::CycleWindow(direction, scope))
+            },
+            "swapnext" => Ok(Dispatcher::SwapNext(args.first().cloned())),
             "movefocus" => Ok(Dispatcher::MoveFocus(get_arg(0)?)),
             "swapwindow" => Ok(Dispatcher::SwapWindow(get_arg(0)?)),
-            "focuswindow" => Ok(Dispatcher::FocusWindow(parse_window_id(0)?)),
-            "movewindow" => Ok(Dispatcher::MoveWindow(get_arg(0)?)),
+            "focuswindow" => {
+                Ok(Dispatcher::FocusWindow(parse_window_id(0)?, flag_at(1, "strict")?))
+            },
+            "pass" => Ok(Dispatcher::Pass(parse_window_id(0)?)),
+            "movewindow" => {
+                Ok(Dispatcher::MoveWindow(get_arg(0)?, flag_at(1, "swap-if-occupied")?))
+            },
             "togglefakefullscreen" => Ok(Dispatcher::ToggleFakeFullscreen),
             "togglepseudo" => Ok(Dispatcher::TogglePseudo),
             "togglepin" => Ok(Dispatcher::TogglePin),
@@ -586,6 +1857,18 @@ impl<'de> Deserialize<'de> for Dispatcher {
             "focuscurrentorlast" => Ok(Dispatcher::FocusCurrentOrLast),
             "forcerendererreload" => Ok(Dispatcher::ForceRendererReload),
             "exit" => Ok(Dispatcher::Exit),
+            "swapwithmaster" => Ok(Dispatcher::SwapWithMaster(args.first().cloned())),
+            "focusmaster" => Ok(Dispatcher::FocusMaster(args.first().cloned())),
+            "addmaster" => Ok(Dispatcher::AddMaster),
+            "removemaster" => Ok(Dispatcher::RemoveMaster),
+            "orientationleft" => Ok(Dispatcher::OrientationLeft),
+            "orientationright" => Ok(Dispatcher::OrientationRight),
+            "orientationtop" => Ok(Dispatcher::OrientationTop),
+            "orientationbottom" => Ok(Dispatcher::OrientationBottom),
+            "orientationcenter" => Ok(Dispatcher::OrientationCenter),
+            "orientationnext" => Ok(Dispatcher::OrientationNext),
+            "orientationprev" => Ok(Dispatcher::OrientationPrev),
+            "movegroupwindow" => Ok(Dispatcher::MoveGroupWindow(get_arg(0)?)),
             "resizeactive" => {
                 let resize_type = get_arg(0)?;
                 let params = match resize_type.as_str() {
@@ -615,38 +1898,115 @@ impl<'de> Deserialize<'de> for Dispatcher {
                 let window = parse_window_id(3)?;
                 Ok(Dispatcher::ResizeWindowPixel(params, window))
             },
-            _ => Err(de::Error::unknown_variant(&h.name, &["exec" /* ... */])),
+            "switchlayout" => Ok(Dispatcher::SwitchLayout(get_arg(0)?, get_arg(1)?)),
+            "submap" => Ok(Dispatcher::Submap(get_arg(0)?)),
+            // `setprop` takes the property and value, then optionally a window and `lock`.
+            "setprop" => {
+                let mut window = None;
+                let mut lock = false;
+                for (i, arg) in args.iter().enumerate().skip(2) {
+                    if arg == "lock" {
+                        lock = true;
+                    } else {
+                        window = Some(parse_window_id(i)?);
+                    }
+                }
+                Ok(Dispatcher::SetProp(get_arg(0)?, get_arg(1)?, window, lock))
+            },
+            "setcursor" => {
+                let size = get_arg(1)?
+                    .parse()
+                    .map_err(de::Error::custom)?;
+                Ok(Dispatcher::SetCursor(get_arg(0)?, size))
+            },
+            "writefile" => Ok(Dispatcher::WriteFile(get_arg(0)?, get_arg(1)?)),
+            "signal" => {
+                let target = match (&h.process, &h.pidfile) {
+                    (Some(name), None) => Target::Process(name.clone()),
+                    (None, Some(path)) => Target::Pidfile(path.clone()),
+                    _ => return Err(de::Error::custom("signal needs either process or pidfile")),
+                };
+                let sig = match &h.sig {
+                    Some(sig) => sig.clone(),
+                    None => get_arg(0)?,
+                };
+                Ok(Dispatcher::Signal(target, sig))
+            },
+            "sleep" => Ok(Dispatcher::Sleep(
+                units::parse_duration(&get_arg(0)?)
+                    .map_err(de::Error::custom)?
+                    .as_millis() as u64,
+            )),
+            "notify" => {
+                notify::validate_urgency(h.urgency.as_deref()).map_err(de::Error::custom)?;
+                Ok(Dispatcher::Notify(get_arg(0)?, args.get(1).cloned(), h.urgency.clone()))
+            },
+            "foreach" => {
+                if args.is_empty() {
+                    return Err(de::Error::custom("foreach needs a dispatcher in args"));
+                }
+                Ok(Dispatcher::ForEach(h.conditions.clone(), args.clone()))
+            },
+            "tagwindow" => {
+                let window = if args.len() > 1 { Some(parse_window_id(1)?) } else { None };
+                Ok(Dispatcher::TagWindow(get_arg(0)?, window))
+            },
+            _ => Err(de::Error::custom(format!(
+                "unknown dispatcher `{}`{}",
+                h.name,
+                suggest::hint(&h.name, Dispatcher::NAMES)
+            ))),
         }
     }
 }
 
-impl From<Dispatcher> for Dispatch {
-    fn from(dispatcher: Dispatcher) -> Self {
-        match dispatcher {
-            Dispatcher::Exec(command) => Dispatch::Exec { command },
+impl TryFrom<Dispatcher> for Dispatch {
+    type Error = String;
+
+    fn try_from(dispatcher: Dispatcher) -> Result<Self, Self::Error> {
+        Ok(match dispatcher {
+            Dispatcher::Exec(command, rules) => Dispatch::Exec { rules, command },
+            Dispatcher::Raw(dispatcher) => Dispatch::Raw { dispatcher: vec![dispatcher] },
             Dispatcher::KillActiveWindow => Dispatch::KillActiveWindow,
-            Dispatcher::ToggleFloating(window) => {
-                Dispatch::ToggleFloating { window: window.unwrap_or_default() }
+            Dispatcher::ToggleFloating(window, remember) => {
+                Dispatch::ToggleFloating { window: window.unwrap_or_default(), remember }
             },
             Dispatcher::ToggleSplit => Dispatch::ToggleSplit,
+            Dispatcher::SplitRatio(value, exact) => Dispatch::SplitRatio { value, exact },
             Dispatcher::ToggleOpaque => Dispatch::ToggleOpaque,
+            Dispatcher::ToggleSwallow => Dispatch::ToggleSwallow,
             Dispatcher::MoveCursorToCorner(corner) => Dispatch::MoveCursorToCorner { corner },
-            Dispatcher::MoveCursor(x, y) => Dispatch::MoveCursor { x, y },
+            Dispatcher::MoveCursor(x, y, relative) => {
+                Dispatch::MoveCursor { x: Some(x), y: Some(y), relative, to_window: None }
+            },
+            Dispatcher::MoveCursorToWindow(window) => {
+                Dispatch::MoveCursor { x: None, y: None, relative: false, to_window: Some(window) }
+            },
             Dispatcher::ToggleFullscreen(mode) => {
                 Dispatch::ToggleFullscreen { mode: mode.unwrap_or_else(|| "noparam".to_string()) }
             },
-            Dispatcher::MoveToWorkspace(workspace) => Dispatch::MoveToWorkspace { workspace },
+            Dispatcher::MoveToWorkspace(workspace, swap_if_occupied) => {
+                Dispatch::MoveToWorkspace { workspace, swap_if_occupied }
+            },
             Dispatcher::MoveToWorkspaceSilent(workspace, window) => {
                 Dispatch::MoveToWorkspaceSilent { workspace, window: window.unwrap_or_default() }
             },
             Dispatcher::Workspace(workspace) => Dispatch::Workspace { workspace },
-            Dispatcher::CycleWindow(direction) => {
-                Dispatch::CycleWindow { direction: direction.unwrap_or_else(|| "next".to_string()) }
+            Dispatcher::WorkspaceToggle(workspace) => Dispatch::WorkspaceToggle { workspace },
+            Dispatcher::CycleWindow(direction, scope) => Dispatch::CycleWindow {
+                direction: direction.unwrap_or_else(|| "next".to_string()),
+                scope,
+            },
+            Dispatcher::SwapNext(direction) => {
+                Dispatch::SwapNext { direction: direction.unwrap_or_else(|| "next".to_string()) }
             },
             Dispatcher::MoveFocus(direction) => Dispatch::MoveFocus { direction },
             Dispatcher::SwapWindow(direction) => Dispatch::SwapWindow { direction },
-            Dispatcher::FocusWindow(window) => Dispatch::FocusWindow { window },
-            Dispatcher::MoveWindow(target) => Dispatch::MoveWindow { target },
+            Dispatcher::FocusWindow(window, strict) => Dispatch::FocusWindow { window, strict },
+            Dispatcher::Pass(window) => Dispatch::Pass { window },
+            Dispatcher::MoveWindow(target, swap_if_occupied) => {
+                Dispatch::MoveWindow { target, swap_if_occupied }
+            },
             Dispatcher::ToggleFakeFullscreen => Dispatch::ToggleFakeFullscreen,
             Dispatcher::TogglePseudo => Dispatch::TogglePseudo,
             Dispatcher::TogglePin => Dispatch::TogglePin,
@@ -656,35 +2016,103 @@ impl From<Dispatcher> for Dispatch {
             Dispatcher::FocusCurrentOrLast => Dispatch::FocusCurrentOrLast,
             Dispatcher::ForceRendererReload => Dispatch::ForceRendererReload,
             Dispatcher::Exit => Dispatch::Exit,
+            Dispatcher::SwapWithMaster(param) => {
+                Dispatch::SwapWithMaster { param: param.unwrap_or_else(|| "auto".to_string()) }
+            },
+            Dispatcher::FocusMaster(param) => {
+                Dispatch::FocusMaster { param: param.unwrap_or_else(|| "auto".to_string()) }
+            },
+            Dispatcher::AddMaster => Dispatch::AddMaster,
+            Dispatcher::RemoveMaster => Dispatch::RemoveMaster,
+            Dispatcher::OrientationLeft => Dispatch::OrientationLeft,
+            Dispatcher::OrientationRight => Dispatch::OrientationRight,
+            Dispatcher::OrientationTop => Dispatch::OrientationTop,
+            Dispatcher::OrientationBottom => Dispatch::OrientationBottom,
+            Dispatcher::OrientationCenter => Dispatch::OrientationCenter,
+            Dispatcher::OrientationNext => Dispatch::OrientationNext,
+            Dispatcher::OrientationPrev => Dispatch::OrientationPrev,
+            Dispatcher::MoveGroupWindow(direction) => Dispatch::MoveGroupWindow { direction },
             Dispatcher::ResizeActive(params) => Dispatch::ResizeActive { params },
             Dispatcher::ResizeWindowPixel(params, window) => {
                 Dispatch::ResizeWindowPixel { params, window }
             },
-        }
+            Dispatcher::SwitchLayout(device, layout) => Dispatch::SwitchLayout { device, layout },
+            Dispatcher::Submap(name) => Dispatch::Submap { name },
+            Dispatcher::SetProp(property, value, window, lock) => {
+                Dispatch::SetProp { window: window.unwrap_or_default(), property, value, lock }
+            },
+            Dispatcher::SetCursor(theme, size) => Dispatch::SetCursor { theme, size },
+            Dispatcher::TagWindow(tag, window) => {
+                Dispatch::TagWindow { tag, window: window.unwrap_or_default() }
+            },
+            Dispatcher::WriteFile(path, content) => Dispatch::WriteFile { path, content },
+            Dispatcher::Signal(target, signal) => match target {
+                Target::Process(name) => {
+                    Dispatch::Signal { process: Some(name), pidfile: None, signal }
+                },
+                Target::Pidfile(path) => {
+                    Dispatch::Signal { process: None, pidfile: Some(path), signal }
+                },
+            },
+            Dispatcher::Sleep(ms) => Dispatch::Sleep { ms },
+            Dispatcher::Notify(summary, body, urgency) => {
+                Dispatch::Notify { summary, body, urgency }
+            },
+            Dispatcher::ForEach(conditions, dispatcher) => {
+                Dispatch::ForEach { conditions, dispatcher }
+            },
+            // Reactions fill in the placeholders before running a dispatcher, and loading a config
+            // rejects them anywhere else, so this only happens if a render path was missed.
+            Dispatcher::Template(raw) => {
+                return Err(format!(
+                    "dispatcher `{}` has unfilled placeholders: {:?}",
+                    raw.name, raw.args
+                ));
+            },
+        })
     }
 }
 
 impl From<Dispatch> for Dispatcher {
     fn from(dispatch: Dispatch) -> Self {
         match dispatch {
-            Dispatch::Exec { command } => Dispatcher::Exec(command),
+            Dispatch::Exec { rules, command } => Dispatcher::Exec(command, rules),
+            Dispatch::Raw { dispatcher } => Dispatcher::Raw(dispatcher.join(" ")),
             Dispatch::KillActiveWindow => Dispatcher::KillActiveWindow,
-            Dispatch::ToggleFloating { window } => Dispatcher::ToggleFloating(Some(window)),
+            Dispatch::ToggleFloating { window, remember } => {
+                Dispatcher::ToggleFloating(Some(window), remember)
+            },
             Dispatch::ToggleSplit => Dispatcher::ToggleSplit,
+            Dispatch::SplitRatio { value, exact } => Dispatcher::SplitRatio(value, exact),
             Dispatch::ToggleOpaque => Dispatcher::ToggleOpaque,
+            Dispatch::ToggleSwallow => Dispatcher::ToggleSwallow,
             Dispatch::MoveCursorToCorner { corner } => Dispatcher::MoveCursorToCorner(corner),
-            Dispatch::MoveCursor { x, y } => Dispatcher::MoveCursor(x, y),
+            Dispatch::MoveCursor { to_window: Some(window), .. } => {
+                Dispatcher::MoveCursorToWindow(window)
+            },
+            Dispatch::MoveCursor { x, y, relative, to_window: None } => {
+                Dispatcher::MoveCursor(x.unwrap_or_default(), y.unwrap_or_default(), relative)
+            },
             Dispatch::ToggleFullscreen { mode } => Dispatcher::ToggleFullscreen(Some(mode)),
-            Dispatch::MoveToWorkspace { workspace } => Dispatcher::MoveToWorkspace(workspace),
+            Dispatch::MoveToWorkspace { workspace, swap_if_occupied } => {
+                Dispatcher::MoveToWorkspace(workspace, swap_if_occupied)
+            },
             Dispatch::MoveToWorkspaceSilent { workspace, window } => {
                 Dispatcher::MoveToWorkspaceSilent(workspace, Some(window))
             },
             Dispatch::Workspace { workspace } => Dispatcher::Workspace(workspace),
-            Dispatch::CycleWindow { direction } => Dispatcher::CycleWindow(Some(direction)),
+            Dispatch::WorkspaceToggle { workspace } => Dispatcher::WorkspaceToggle(workspace),
+            Dispatch::CycleWindow { direction, scope } => {
+                Dispatcher::CycleWindow(Some(direction), scope)
+            },
+            Dispatch::SwapNext { direction } => Dispatcher::SwapNext(Some(direction)),
             Dispatch::MoveFocus { direction } => Dispatcher::MoveFocus(direction),
             Dispatch::SwapWindow { direction } => Dispatcher::SwapWindow(direction),
-            Dispatch::FocusWindow { window } => Dispatcher::FocusWindow(window),
-            Dispatch::MoveWindow { target } => Dispatcher::MoveWindow(target),
+            Dispatch::FocusWindow { window, strict } => Dispatcher::FocusWindow(window, strict),
+            Dispatch::Pass { window } => Dispatcher::Pass(window),
+            Dispatch::MoveWindow { target, swap_if_occupied } => {
+                Dispatcher::MoveWindow(target, swap_if_occupied)
+            },
             Dispatch::ToggleFakeFullscreen => Dispatcher::ToggleFakeFullscreen,
             Dispatch::TogglePseudo => Dispatcher::TogglePseudo,
             Dispatch::TogglePin => Dispatcher::TogglePin,
@@ -694,10 +2122,54 @@ impl From<Dispatch> for Dispatcher {
             Dispatch::FocusCurrentOrLast => Dispatcher::FocusCurrentOrLast,
             Dispatch::ForceRendererReload => Dispatcher::ForceRendererReload,
             Dispatch::Exit => Dispatcher::Exit,
+            Dispatch::SwapWithMaster { param } => Dispatcher::SwapWithMaster(Some(param)),
+            Dispatch::FocusMaster { param } => Dispatcher::FocusMaster(Some(param)),
+            Dispatch::AddMaster => Dispatcher::AddMaster,
+            Dispatch::RemoveMaster => Dispatcher::RemoveMaster,
+            Dispatch::OrientationLeft => Dispatcher::OrientationLeft,
+            Dispatch::OrientationRight => Dispatcher::OrientationRight,
+            Dispatch::OrientationTop => Dispatcher::OrientationTop,
+            Dispatch::OrientationBottom => Dispatcher::OrientationBottom,
+            Dispatch::OrientationCenter => Dispatcher::OrientationCenter,
+            Dispatch::OrientationNext => Dispatcher::OrientationNext,
+            Dispatch::OrientationPrev => Dispatcher::OrientationPrev,
+            Dispatch::MoveGroupWindow { direction } => Dispatcher::MoveGroupWindow(direction),
             Dispatch::ResizeActive { params } => Dispatcher::ResizeActive(params),
             Dispatch::ResizeWindowPixel { params, window } => {
                 Dispatcher::ResizeWindowPixel(params, window)
             },
+            Dispatch::SwitchLayout { device, layout } => Dispatcher::SwitchLayout(device, layout),
+            Dispatch::Submap { name } => Dispatcher::Submap(name),
+            Dispatch::SetProp { window, property, value, lock } => {
+                let window = window
+                    .to_identifier_string()
+                    .is_some()
+                    .then_some(window);
+                Dispatcher::SetProp(property, value, window, lock)
+            },
+            Dispatch::SetCursor { theme, size } => Dispatcher::SetCursor(theme, size),
+            Dispatch::TagWindow { tag, window } => {
+                let window = window
+                    .to_identifier_string()
+                    .is_some()
+                    .then_some(window);
+                Dispatcher::TagWindow(tag, window)
+            },
+            Dispatch::WriteFile { path, content } => Dispatcher::WriteFile(path, content),
+            Dispatch::Signal { process, pidfile, signal } => {
+                let target = match (process, pidfile) {
+                    (Some(name), _) => Target::Process(name),
+                    (None, path) => Target::Pidfile(path.unwrap_or_default()),
+                };
+                Dispatcher::Signal(target, signal)
+            },
+            Dispatch::Sleep { ms } => Dispatcher::Sleep(ms),
+            Dispatch::Notify { summary, body, urgency } => {
+                Dispatcher::Notify(summary, body, urgency)
+            },
+            Dispatch::ForEach { conditions, dispatcher } => {
+                Dispatcher::ForEach(conditions, dispatcher)
+            },
         }
     }
 }
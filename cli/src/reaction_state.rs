@@ -0,0 +1,123 @@
+//! Reaction state carried over when the daemon is restarted to pick up a changed config.
+//!
+//! Each time a named reaction with a `max_count` or a `cooldown` fires, its counter and the time
+//! it fired are saved together with the Hyprland instance signature. A daemon started later on the
+//! same instance restores them for reactions whose name is unchanged, so a reload neither re-arms
+//! reactions that already used up their count nor cuts a cooldown short. A new Hyprland session
+//! starts from zero. Pausing and scene toggles live in their own state files and carry over on
+//! their own.
+//!
+//! Every config file gets its own state file, so daemons running different configs don't restore
+//! or prune each other's reactions.
+
+use crate::{instance, state_file};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// What is saved for one reaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Saved {
+    #[serde(default)]
+    pub count: usize,
+    /// Unix milliseconds of the last time it fired.
+    last_fired: Option<u64>,
+}
+
+impl Saved {
+    /// When the reaction last fired, on this process's clock. None if that is too long ago to
+    /// express, which no cooldown cares about anyway.
+    pub fn last_fired(&self) -> Option<Instant> {
+        let elapsed = now().checked_sub(self.last_fired?)?;
+        Instant::now().checked_sub(Duration::from_millis(elapsed))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedState {
+    instance: Option<String>,
+    #[serde(default)]
+    reactions: BTreeMap<String, Saved>,
+}
+
+/// Serializes the read-modify-write of the state file between handler threads.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// State file of this daemon, set once from its config path.
+static STATE_FILE: OnceLock<String> = OnceLock::new();
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Key the saved state by the config the daemon runs. Reactions given on the command line share
+/// one file.
+pub fn set_config_path(config_path: Option<&Path>) {
+    let key = config_path.map_or_else(
+        || "inline".to_string(),
+        |path| {
+            path.canonicalize()
+                .unwrap_or_else(|_| path.to_path_buf())
+                .to_string_lossy()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                .collect()
+        },
+    );
+    let _ = STATE_FILE.set(format!("reactions/{key}.toml"));
+}
+
+fn state_file() -> &'static str {
+    STATE_FILE
+        .get()
+        .map_or("reactions/inline.toml", String::as_str)
+}
+
+fn update(reaction: &str, change: impl FnOnce(&mut Saved)) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut state: SavedState = state_file::load(state_file()).unwrap_or_default();
+    let current = instance::current_signature();
+    if state.instance != current {
+        state = SavedState { instance: current, ..SavedState::default() };
+    }
+    change(
+        state
+            .reactions
+            .entry(reaction.to_string())
+            .or_default(),
+    );
+    state_file::save(state_file(), &state)
+}
+
+pub fn save_counter(reaction: &str, count: usize) -> Result<(), String> {
+    update(reaction, |saved| saved.count = count)
+}
+
+pub fn save_fired(reaction: &str) -> Result<(), String> {
+    update(reaction, |saved| saved.last_fired = Some(now()))
+}
+
+/// State saved by an earlier daemon with this config on this Hyprland instance, limited to
+/// `names`. Reactions that are no longer configured are dropped.
+pub fn restore<'a>(names: impl IntoIterator<Item = &'a str>) -> BTreeMap<String, Saved> {
+    let _guard = LOCK.lock().unwrap();
+    let Some(state) = state_file::load::<SavedState>(state_file()) else {
+        return BTreeMap::new();
+    };
+    if state.instance.is_none() || state.instance != instance::current_signature() {
+        return BTreeMap::new();
+    }
+    let mut reactions = state.reactions;
+    let names: Vec<&str> = names.into_iter().collect();
+    reactions.retain(|name, _| names.contains(&name.as_str()));
+    let state = SavedState { instance: state.instance, reactions: reactions.clone() };
+    if let Err(e) = state_file::save(state_file(), &state) {
+        eprintln!("Error: {e}");
+    }
+    reactions
+}
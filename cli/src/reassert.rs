@@ -0,0 +1,52 @@
+//! Keywords set again after every Hyprland config reload.
+//!
+//! A reload puts every keyword back to what `hyprland.conf` says, wiping overrides made at runtime
+//! with `hyprctl keyword` or `hyde-ipc keyword --set`. The reaction daemon sets the keywords in
+//! the config's `[reassert]` table again after each reload, followed by the ones saved with
+//! `keyword --set ... --watch-config-reload`, which win where both name the same keyword.
+
+use crate::app_modes::AppMode;
+use crate::state_file;
+use hyprland::keyword::Keyword;
+use std::collections::BTreeMap;
+
+const STATE_FILE: &str = "reassert.toml";
+
+/// Keywords saved with `--watch-config-reload`, with their values.
+fn saved() -> BTreeMap<String, String> {
+    state_file::load(STATE_FILE).unwrap_or_default()
+}
+
+fn save(keywords: &BTreeMap<String, String>) -> Result<(), String> {
+    state_file::save(STATE_FILE, keywords)
+}
+
+/// Set `keyword` to `value` again after every reload from now on.
+pub fn watch(keyword: &str, value: &str) -> Result<(), String> {
+    let mut keywords = saved();
+    keywords.insert(keyword.to_string(), value.to_string());
+    save(&keywords)
+}
+
+/// Stop setting `keyword` again after reloads. Keywords from the config's `[reassert]` table stay.
+pub fn unwatch(keyword: &str) -> Result<(), String> {
+    let mut keywords = saved();
+    if keywords.remove(keyword).is_none() {
+        return Err(format!("{keyword} is not set again after reloads"));
+    }
+    save(&keywords)
+}
+
+/// Set the configured and saved keywords again, after Hyprland reloaded its config.
+pub fn apply(configured: &AppMode) {
+    let mut keywords: BTreeMap<String, String> = configured
+        .iter()
+        .map(|(keyword, value)| (keyword.clone(), value.to_string()))
+        .collect();
+    keywords.extend(saved());
+    for (keyword, value) in keywords {
+        if let Err(e) = Keyword::set(keyword.as_str(), value.as_str()) {
+            eprintln!("Error setting {keyword} again after reload: {e}");
+        }
+    }
+}
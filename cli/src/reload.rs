@@ -0,0 +1,41 @@
+//! `hyde-ipc reload`: reload Hyprland's config and put back everything the reload undoes.
+//!
+//! A config reload resets every keyword and the cursor to what `hyprland.conf` says. This reloads,
+//! then sets the active scene's keywords and the saved cursor again and refreshes the exposed
+//! environment, so the compositor matches the config plus hyde-ipc's state. App modes belong to
+//! the reaction daemon, which applies them again when it sees the reload.
+
+use crate::react_config::ReactConfig;
+use crate::{cursor, dnd, expose, scene};
+use hyde_ipc_lib::service;
+use hyprland::dispatch::{Dispatch, DispatchType};
+use std::path::PathBuf;
+
+pub fn run(config: Option<String>, renderer: bool) -> Result<(), String> {
+    hyprland::ctl::reload::call().map_err(|e| format!("Failed to reload Hyprland: {e}"))?;
+
+    if scene::active_name().is_some() {
+        let path = match config {
+            Some(path) => PathBuf::from(path),
+            None => service::get_config_path().map_err(|e| e.to_string())?,
+        };
+        scene::reassert(&ReactConfig::from_file(path)?.scenes)?;
+    }
+    cursor::reassert();
+
+    let exposed = expose::from_global_config();
+    expose::dnd(&exposed, dnd::is_enabled());
+    expose::set(
+        &exposed,
+        expose::State::Scene,
+        scene::active_name()
+            .as_deref()
+            .unwrap_or_default(),
+    );
+
+    if renderer {
+        Dispatch::call(DispatchType::ForceRendererReload).map_err(|e| e.to_string())?;
+    }
+    println!("Reloaded");
+    Ok(())
+}
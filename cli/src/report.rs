@@ -0,0 +1,229 @@
+//! `hyde-ipc report`: a tarball to attach to bug reports.
+//!
+//! Everything is collected locally and nothing is uploaded. Unless `--no-redact` is given, window
+//! titles, `exec` and `raw` commands and quoted strings in the service log are replaced with
+//! [`REDACTED`] before they are written.
+
+use crate::react_config::ReactConfig;
+use hyde_ipc_lib::service;
+use hyprland::data::Version;
+use hyprland::prelude::*;
+use std::fs::{self, DirBuilder};
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const REDACTED: &str = "<redacted>";
+
+pub fn run(
+    config: Option<String>,
+    output: Option<String>,
+    minutes: u64,
+    redact: bool,
+) -> Result<(), String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let output =
+        PathBuf::from(output.unwrap_or_else(|| format!("hyde-ipc-report-{timestamp}.tar.gz")));
+    let dir = std::env::temp_dir().join(format!("hyde-ipc-report-{}-{timestamp}", process::id()));
+    // Private, and never one that is already there: /tmp is shared with other users.
+    DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("Failed to create report dir {}: {e}", dir.display()))?;
+
+    let result = collect(&dir, config, minutes, redact).and_then(|()| archive(&dir, &output));
+    let _ = fs::remove_dir_all(&dir);
+    result?;
+    println!("Report written to {}", output.display());
+    if redact {
+        println!("Window titles and commands were redacted; check the contents before sharing.");
+    } else {
+        println!("Nothing was redacted; check the contents before sharing.");
+    }
+    Ok(())
+}
+
+fn collect(dir: &Path, config: Option<String>, minutes: u64, redact: bool) -> Result<(), String> {
+    let write = |name: &str, content: String| {
+        fs::write(dir.join(name), content).map_err(|e| format!("Failed to write {name}: {e}"))
+    };
+    let config = match config {
+        Some(path) => PathBuf::from(path),
+        None => service::get_config_path().map_err(|e| e.to_string())?,
+    };
+    write("config.toml", effective_config(&config, redact))?;
+    write("versions.txt", versions())?;
+    write("service-status.txt", service_status(redact))?;
+    write("service-log.txt", service_log(minutes, redact))
+}
+
+fn archive(dir: &Path, output: &Path) -> Result<(), String> {
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(output)
+        .arg("-C")
+        .arg(dir)
+        .arg(".")
+        .status()
+        .map_err(|e| format!("Failed to run tar: {e}"))?;
+    if !status.success() {
+        return Err(format!("tar exited with {status}"));
+    }
+    Ok(())
+}
+
+fn effective_config(path: &Path, redact: bool) -> String {
+    let content = match ReactConfig::effective_toml(path) {
+        Ok(content) => content,
+        Err(e) => return format!("# {}: {e}\n", path.display()),
+    };
+    if !redact {
+        return content;
+    }
+    match toml::from_str::<toml::Value>(&content) {
+        Ok(mut value) => {
+            redact_config(&mut value);
+            toml::to_string_pretty(&value).unwrap_or(content)
+        },
+        Err(e) => format!("# Failed to re-parse the effective config: {e}\n"),
+    }
+}
+
+/// Blank out window titles, `exec` and `raw` commands and their environment anywhere in the
+/// config.
+fn redact_config(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            // Dispatcher names are matched like the config loader does, ignoring case, `-` and `_`.
+            let is_exec = table
+                .get("name")
+                .and_then(toml::Value::as_str)
+                .map(|name| {
+                    name.to_lowercase()
+                        .replace(['-', '_'], "")
+                })
+                .is_some_and(|name| name == "exec" || name == "raw");
+            for (key, value) in table.iter_mut() {
+                match (key.as_str(), value) {
+                    ("args", toml::Value::Array(args)) if is_exec => {
+                        args.iter_mut()
+                            .for_each(|arg| *arg = REDACTED.into());
+                    },
+                    ("env", toml::Value::Table(env)) if is_exec => {
+                        env.iter_mut()
+                            .for_each(|(_, value)| *value = REDACTED.into());
+                    },
+                    ("window_filter", toml::Value::String(filter)) => {
+                        *filter = redact_filter(filter)
+                    },
+                    ("ignore", toml::Value::Array(filters)) => {
+                        for filter in filters {
+                            if let toml::Value::String(filter) = filter {
+                                *filter = redact_filter(filter);
+                            }
+                        }
+                    },
+                    (_, value) => redact_config(value),
+                }
+            }
+        },
+        toml::Value::Array(values) => values
+            .iter_mut()
+            .for_each(redact_config),
+        _ => {},
+    }
+}
+
+/// Keep the kind of a window filter (`class:`, `title:`, ...) but drop titles. Bare patterns can
+/// match titles too, so they are dropped as well.
+fn redact_filter(filter: &str) -> String {
+    match filter.split_once(':') {
+        Some((kind @ ("title" | "initialtitle"), _)) => format!("{kind}:{REDACTED}"),
+        Some(_) => filter.to_string(),
+        None => REDACTED.to_string(),
+    }
+}
+
+fn versions() -> String {
+    let hyprland = match Version::get() {
+        Ok(version) => format!(
+            "{} (commit {}{}, branch {})",
+            version.version.unwrap_or(version.tag),
+            version.commit,
+            if version.dirty { ", dirty" } else { "" },
+            version.branch
+        ),
+        Err(e) => format!("unavailable: {e}"),
+    };
+    format!(
+        "hyde-ipc {}\nHyprland {hyprland}\nOS {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+fn service_status(redact: bool) -> String {
+    let active = match service::is_active() {
+        Ok(true) => "active".to_string(),
+        Ok(false) => "inactive".to_string(),
+        Err(e) => format!("unknown: {e}"),
+    };
+    let details = command_output("systemctl", &[
+        "--user",
+        "status",
+        "--no-pager",
+        "hyde-ipc.service",
+    ]);
+    format!("Service is {active}\n\n{}", redact_log(&details, redact))
+}
+
+/// The service log of the last `minutes`, which covers the events it reacted to.
+fn service_log(minutes: u64, redact: bool) -> String {
+    let since = format!("-{minutes}min");
+    let log = command_output("journalctl", &[
+        "--user",
+        "--no-pager",
+        "-u",
+        "hyde-ipc.service",
+        "--since",
+        &since,
+    ]);
+    redact_log(&log, redact)
+}
+
+fn command_output(program: &str, args: &[&str]) -> String {
+    match Command::new(program)
+        .args(args)
+        .output()
+    {
+        Ok(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            text
+        },
+        Err(e) => format!("Failed to run {program}: {e}\n"),
+    }
+}
+
+/// Titles and commands end up in the log as debug-printed strings, so blank out everything
+/// between double quotes.
+fn redact_log(log: &str, redact: bool) -> String {
+    if !redact {
+        return log.to_string();
+    }
+    log.lines()
+        .map(|line| {
+            line.split('"')
+                .enumerate()
+                .map(|(index, part)| if index % 2 == 1 { REDACTED } else { part })
+                .collect::<Vec<_>>()
+                .join("\"")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
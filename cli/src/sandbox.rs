@@ -0,0 +1,60 @@
+//! Running reaction `exec` commands in their own systemd scope.
+//!
+//! Scoped commands show up as separate units in `systemctl --user`, can be given resource limits,
+//! and are not torn down together with the daemon's service.
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExecSandbox {
+    /// Wrap `exec` commands in `systemd-run --user --scope`.
+    #[serde(default)]
+    pub systemd_scope: bool,
+    /// Passed as `MemoryMax=`, e.g. `"512M"`.
+    pub memory_max: Option<String>,
+    /// Passed as `CPUQuota=`, e.g. `"50%"`.
+    pub cpu_quota: Option<String>,
+    /// Passed as `TasksMax=`.
+    pub tasks_max: Option<u32>,
+    /// Slice to place the scopes in.
+    pub slice: Option<String>,
+}
+
+impl ExecSandbox {
+    /// Prefix `command` with the `systemd-run` invocation, if enabled.
+    pub fn wrap(&self, command: Vec<String>) -> Vec<String> {
+        if !self.systemd_scope {
+            return command;
+        }
+        let mut wrapped: Vec<String> = [
+            "systemd-run",
+            "--user",
+            "--scope",
+            "--collect",
+            "--quiet",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+        if let Some(slice) = &self.slice {
+            wrapped.push(format!("--slice={slice}"));
+        }
+        let properties = [
+            self.memory_max
+                .as_ref()
+                .map(|v| format!("MemoryMax={v}")),
+            self.cpu_quota
+                .as_ref()
+                .map(|v| format!("CPUQuota={v}")),
+            self.tasks_max
+                .map(|v| format!("TasksMax={v}")),
+        ];
+        for property in properties.into_iter().flatten() {
+            wrapped.push("-p".to_string());
+            wrapped.push(property);
+        }
+        wrapped.push("--".to_string());
+        wrapped.extend(command);
+        wrapped
+    }
+}
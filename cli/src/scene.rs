@@ -0,0 +1,151 @@
+//! Scenes: named bundles of keyword changes, dispatches and reaction toggles.
+//!
+//! Applying a scene records what it changed in the state directory. `scene revert` restores the
+//! saved keyword values, and a running reaction daemon reads the same file to decide which
+//! reactions the scene has enabled or disabled.
+
+use crate::app_modes::{self, AppMode};
+use crate::dispatch::handle_dispatch;
+use crate::flags::{Dispatch, SceneAction};
+use crate::react_config::ReactConfig;
+use crate::reaction_handler::Dispatcher;
+use crate::{expose, state_file};
+use hyde_ipc_lib::service;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Scene {
+    /// Keywords to set while the scene is active.
+    #[serde(default)]
+    pub keywords: AppMode,
+    /// Dispatchers run once when the scene is applied.
+    #[serde(default)]
+    pub dispatchers: Vec<Dispatcher>,
+    /// Names of `disabled` reactions that fire while the scene is active.
+    #[serde(default)]
+    pub enable: Vec<String>,
+    /// Names of reactions that don't fire while the scene is active.
+    #[serde(default)]
+    pub disable: Vec<String>,
+}
+
+/// The scene currently applied, as stored on disk.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ActiveScene {
+    name: String,
+    saved: Vec<(String, String)>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+}
+
+const STATE_FILE: &str = "scene.toml";
+
+fn load_active() -> Option<ActiveScene> {
+    state_file::load_cached(STATE_FILE)
+}
+
+/// The name of the applied scene, if any.
+pub fn active_name() -> Option<String> {
+    load_active().map(|active| active.name)
+}
+
+pub fn run(config: Option<String>, action: SceneAction) -> Result<(), String> {
+    match action {
+        SceneAction::Apply { name } => {
+            let path = match config {
+                Some(path) => PathBuf::from(path),
+                None => service::get_config_path().map_err(|e| e.to_string())?,
+            };
+            apply(&name, &ReactConfig::from_file(path)?.scenes)
+        },
+        SceneAction::Revert => revert(),
+    }
+}
+
+pub fn apply(name: &str, scenes: &HashMap<String, Scene>) -> Result<(), String> {
+    let scene = scenes.get(name).ok_or_else(|| {
+        let mut known: Vec<_> = scenes
+            .keys()
+            .map(String::as_str)
+            .collect();
+        known.sort_unstable();
+        format!("Unknown scene '{name}' (known scenes: {})", known.join(", "))
+    })?;
+
+    if load_active().is_some() {
+        revert()?;
+    }
+
+    save_active(&ActiveScene {
+        name: name.to_string(),
+        saved: app_modes::apply_keywords(&scene.keywords),
+        enable: scene.enable.clone(),
+        disable: scene.disable.clone(),
+    })?;
+
+    expose::set(&expose::from_global_config(), expose::State::Scene, name);
+
+    for dispatcher in &scene.dispatchers {
+        match Dispatch::try_from(dispatcher.clone()) {
+            Ok(command) => handle_dispatch(command, false),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+    }
+    println!("Applied scene '{name}'");
+    Ok(())
+}
+
+fn save_active(active: &ActiveScene) -> Result<(), String> {
+    state_file::save(STATE_FILE, active)
+}
+
+/// Set the active scene's keywords again after a config reload reset them. The values the reload
+/// put in place become the ones `revert` goes back to.
+pub fn reassert(scenes: &HashMap<String, Scene>) -> Result<(), String> {
+    let Some(mut active) = load_active() else {
+        return Ok(());
+    };
+    let Some(scene) = scenes.get(&active.name) else {
+        eprintln!("Scene '{}' is no longer in the config, not applying it again", active.name);
+        return Ok(());
+    };
+    active.saved = app_modes::apply_keywords(&scene.keywords);
+    save_active(&active)
+}
+
+/// Undo the active scene's keyword changes and reaction toggles.
+pub fn revert() -> Result<(), String> {
+    let Some(active) = load_active() else {
+        println!("No scene is active");
+        return Ok(());
+    };
+    app_modes::restore_keywords(active.saved);
+    state_file::remove(STATE_FILE)?;
+    expose::set(&expose::from_global_config(), expose::State::Scene, "");
+    println!("Reverted scene '{}'", active.name);
+    Ok(())
+}
+
+/// Whether a reaction may fire under the active scene, if any.
+pub fn is_reaction_enabled(name: Option<&str>, disabled: bool) -> bool {
+    is_enabled_in(load_active().as_ref(), name, disabled)
+}
+
+/// [`is_reaction_enabled`] for many reactions at once, reading the active scene only once.
+pub fn reaction_checker() -> impl Fn(Option<&str>, bool) -> bool {
+    let active = load_active();
+    move |name, disabled| is_enabled_in(active.as_ref(), name, disabled)
+}
+
+fn is_enabled_in(active: Option<&ActiveScene>, name: Option<&str>, disabled: bool) -> bool {
+    let Some(name) = name else {
+        return !disabled;
+    };
+    match active {
+        Some(scene) if scene.disable.iter().any(|n| n == name) => false,
+        Some(scene) if scene.enable.iter().any(|n| n == name) => true,
+        _ => !disabled,
+    }
+}
@@ -0,0 +1,471 @@
+//! Scheduled reactions: `event_type = { Schedule = "..." }`.
+//!
+//! A schedule is either a cron expression or a phrase. Cron takes the usual five fields (minute,
+//! hour, day of month, month, day of week) with lists, ranges, steps and month or day names, plus
+//! `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`. Phrases are `every 15 minutes` or
+//! `every 2h` (counted from when the daemon started), `hourly`, `daily at 21:00`,
+//! `weekdays at 9am`, `on mon, thu at 18:30` and the like. Clock times are local, in 24-hour or
+//! 12-hour form, and the next one is worked out from the wall clock again after every wait, so
+//! schedules stay on time across suspend and daylight saving changes.
+
+use crate::{suggest, units};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{fmt, thread};
+
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+const WEEKDAYS: [&str; 7] = [
+    "sun", "mon", "tue", "wed", "thu", "fri", "sat",
+];
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+const PHRASES: [&str; 10] = [
+    "every", "hourly", "daily", "weekly", "monthly", "yearly", "weekdays", "weekends", "on", "at",
+];
+
+/// How many days ahead to look for the next match. Enough for February 29th on a given weekday.
+const MAX_DAYS: usize = 30 * 366;
+
+/// How long to sleep at most before checking the clock again.
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Schedule {
+    /// As written in the config.
+    text: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Kind {
+    Every(Duration),
+    Calendar(Box<Calendar>),
+}
+
+/// Clock times and days a schedule fires on, as bit sets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Calendar {
+    /// Minutes of the day.
+    times: [u64; MINUTES_PER_DAY.div_ceil(64)],
+    /// Days of the month, 1 to 31.
+    days: u32,
+    /// Months, 1 to 12.
+    months: u16,
+    /// Days of the week, 0 (Sunday) to 6.
+    weekdays: u8,
+    /// Both the day of the month and of the week were restricted, so either is enough, as in cron.
+    either_day: bool,
+}
+
+impl Calendar {
+    fn new() -> Self {
+        Self {
+            times: [0; MINUTES_PER_DAY.div_ceil(64)],
+            days: 0,
+            months: 0,
+            weekdays: 0,
+            either_day: false,
+        }
+    }
+
+    fn every_day() -> Self {
+        Self {
+            days: all_bits(1, 31) as u32,
+            months: all_bits(1, 12) as u16,
+            weekdays: 0x7f,
+            ..Self::new()
+        }
+    }
+
+    fn add_time(&mut self, hour: usize, minute: usize) {
+        let index = hour * 60 + minute;
+        self.times[index / 64] |= 1 << (index % 64);
+    }
+
+    fn has_time(&self, index: usize) -> bool {
+        self.times[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn day_matches(&self, tm: &libc::tm) -> bool {
+        let day = self.days & (1 << tm.tm_mday) != 0;
+        let weekday = self.weekdays & (1 << tm.tm_wday) != 0;
+        let day = if self.either_day { day || weekday } else { day && weekday };
+        day && self.months & (1 << (tm.tm_mon + 1)) != 0
+    }
+
+    /// The first time after `after` (unix seconds) this fires, in unix seconds.
+    fn next_after(&self, after: i64) -> Option<i64> {
+        let mut t = after - after.rem_euclid(60) + 60;
+        for _ in 0..MAX_DAYS {
+            let tm = local(t);
+            if self.day_matches(&tm) {
+                let start = (tm.tm_hour * 60 + tm.tm_min) as usize;
+                if let Some(index) = (start..MINUTES_PER_DAY).find(|&index| self.has_time(index)) {
+                    let mut at = tm;
+                    at.tm_hour = (index / 60) as i32;
+                    at.tm_min = (index % 60) as i32;
+                    at.tm_sec = 0;
+                    at.tm_isdst = -1;
+                    let candidate = make_time(at);
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                    // A time skipped by a daylight saving change: look on from the next minute.
+                    t = candidate.max(t) + 60;
+                    continue;
+                }
+            }
+            let mut next_day = tm;
+            next_day.tm_mday += 1;
+            next_day.tm_hour = 0;
+            next_day.tm_min = 0;
+            next_day.tm_sec = 0;
+            next_day.tm_isdst = -1;
+            t = make_time(next_day);
+        }
+        None
+    }
+}
+
+fn all_bits(min: u32, max: u32) -> u64 {
+    (min..=max).fold(0, |bits, n| bits | 1 << n)
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn local(seconds: i64) -> libc::tm {
+    let seconds = seconds as libc::time_t;
+    // SAFETY: `tm` is plain data, and localtime_r only writes the struct it is given.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&seconds, &mut tm);
+        tm
+    }
+}
+
+fn make_time(mut tm: libc::tm) -> i64 {
+    // SAFETY: mktime only reads and normalizes the struct it is given.
+    unsafe { libc::mktime(&mut tm) as i64 }
+}
+
+/// One cron field as a bit set: `*`, `5`, `1-5`, `*/15`, `10-40/10`, names, and lists of those.
+fn parse_field(field: &str, what: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_lowercase();
+        if let Some(index) = names
+            .iter()
+            .position(|name| lower.starts_with(name) && name.len() >= 3)
+        {
+            return Ok(index as u32 + min);
+        }
+        let n: u32 = s
+            .parse()
+            .map_err(|_| format!("Invalid {what} '{s}'"))?;
+        // Cron allows 7 for Sunday as well as 0.
+        let n = if what == "weekday" && n == 7 { 0 } else { n };
+        if n < min || n > max {
+            return Err(format!("{what} {n} is out of range {min}-{max}"));
+        }
+        Ok(n)
+    };
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|&step| step > 0)
+                    .ok_or_else(|| format!("Invalid step '{step}' in {what} field '{field}'"))?;
+                (range, Some(step))
+            },
+            None => (part, None),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => match (value(first)?, value(last)?) {
+                // A weekday range can end on Sunday as 0 or by name, as in `mon-sun`.
+                (first, 0) if what == "weekday" && first > 0 => (first, 7),
+                range => range,
+            },
+            None if step.is_some() => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if first > last {
+            return Err(format!("Empty {what} range '{range}'"));
+        }
+        bits |= (first..=last)
+            .step_by(step.unwrap_or(1) as usize)
+            .fold(0, |bits, n| bits | 1 << n);
+    }
+    Ok(bits)
+}
+
+/// Weekday bits with cron's 7 for Sunday folded into 0, once ranges up to 7 are expanded.
+fn weekdays(bits: u64) -> u8 {
+    (bits & 0x7f | bits >> 7 & 1) as u8
+}
+
+fn parse_cron(expression: &str) -> Result<Calendar, String> {
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let [minute, hour, day, month, weekday] = fields[..] else {
+        return Err(format!(
+            "A cron expression has 5 fields (minute hour day month weekday), got {}",
+            fields.len()
+        ));
+    };
+    let minutes = parse_field(minute, "minute", 0, 59, &[])?;
+    let hours = parse_field(hour, "hour", 0, 23, &[])?;
+    let mut calendar = Calendar {
+        days: parse_field(day, "day", 1, 31, &[])? as u32,
+        months: parse_field(month, "month", 1, 12, &MONTHS)? as u16,
+        weekdays: weekdays(parse_field(weekday, "weekday", 0, 7, &WEEKDAYS)?),
+        either_day: day != "*" && weekday != "*",
+        ..Calendar::new()
+    };
+    for hour in (0..24).filter(|hour| hours & (1 << hour) != 0) {
+        for minute in (0..60).filter(|minute| minutes & (1 << minute) != 0) {
+            calendar.add_time(hour, minute);
+        }
+    }
+    Ok(calendar)
+}
+
+/// `21:00`, `9:30`, `9pm`, `9:30 am`, `noon` or `midnight`, as hour and minute.
+fn parse_time(s: &str) -> Result<(usize, usize), String> {
+    match s {
+        "noon" => return Ok((12, 0)),
+        "midnight" => return Ok((0, 0)),
+        _ => {},
+    }
+    let invalid = || format!("Invalid time '{s}', expected e.g. 21:00, 9:30 or 9pm");
+    let compact = s.replace(' ', "");
+    let (clock, meridiem) = match compact
+        .strip_suffix("am")
+        .map(|clock| (clock, Some(0)))
+        .or_else(|| {
+            compact
+                .strip_suffix("pm")
+                .map(|clock| (clock, Some(12)))
+        }) {
+        Some(split) => split,
+        None => (compact.as_str(), None),
+    };
+    let (hour, minute) = clock
+        .split_once(':')
+        .unwrap_or((clock, "0"));
+    let hour: usize = hour.parse().map_err(|_| invalid())?;
+    let minute: usize = minute.parse().map_err(|_| invalid())?;
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return Err(invalid()),
+        Some(offset) => hour % 12 + offset,
+        None => hour,
+    };
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok((hour, minute))
+}
+
+/// `every 15 minutes`, `every minute`, `every 2h`.
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let compact = s.replace(' ', "");
+    let split = compact
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(compact.len());
+    let (count, unit) = compact.split_at(split);
+    let count = if count.is_empty() { "1" } else { count };
+    // Spelled-out units go by their short name, which is what the duration parser reads.
+    let unit = match unit {
+        "sec" | "secs" | "second" | "seconds" => "s",
+        "min" | "mins" | "minute" | "minutes" => "m",
+        "hr" | "hrs" | "hour" | "hours" => "h",
+        "day" | "days" => "d",
+        "" => {
+            return Err(format!(
+                "Interval '{s}' has no unit, expected e.g. every 30s or every 5 minutes"
+            ));
+        },
+        unit if unit.len() > 2 => {
+            return Err(format!(
+                "Unknown unit '{unit}', expected seconds, minutes, hours or days{}",
+                suggest::hint(unit, ["seconds", "minutes", "hours", "days"])
+            ));
+        },
+        unit => unit,
+    };
+    let interval = units::parse_duration(&format!("{count}{unit}"))?;
+    if interval < Duration::from_secs(1) {
+        return Err(format!("Interval '{s}' is shorter than a second"));
+    }
+    Ok(interval)
+}
+
+/// `daily`, `every day`, `weekdays`, `weekends`, `every monday`, `on mon, thu`, `fridays`.
+fn parse_days(s: &str) -> Result<u8, String> {
+    match s {
+        "" | "daily" | "every day" | "everyday" => return Ok(0x7f),
+        "weekdays" | "every weekday" => return Ok(0b011_1110),
+        "weekends" | "every weekend" => return Ok(0b100_0001),
+        _ => {},
+    }
+    let list = s
+        .strip_prefix("every ")
+        .or_else(|| s.strip_prefix("on "))
+        .unwrap_or(s);
+    let mut weekdays = 0;
+    for day in list
+        .split(',')
+        .flat_map(|part| part.split(" and "))
+        .map(str::trim)
+        .filter(|day| !day.is_empty())
+    {
+        let index = WEEKDAYS
+            .iter()
+            .position(|name| day.len() >= 3 && day.starts_with(name))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown day '{day}', expected e.g. daily, weekdays, weekends or on mon, thu{}",
+                    suggest::hint(day, WEEKDAYS)
+                )
+            })?;
+        weekdays |= 1 << index;
+    }
+    Ok(weekdays)
+}
+
+/// A phrase like `daily at 21:00` or `on mon, thu at 9am and 5pm`.
+fn parse_phrase(s: &str) -> Result<Kind, String> {
+    let calendar = |cron: &str| parse_cron(cron).map(|calendar| Kind::Calendar(Box::new(calendar)));
+    match s {
+        "hourly" => return calendar("0 * * * *"),
+        "daily" | "midnight" => return calendar("0 0 * * *"),
+        "weekly" => return calendar("0 0 * * 0"),
+        "monthly" => return calendar("0 0 1 * *"),
+        "yearly" | "annually" => return calendar("0 0 1 1 *"),
+        _ => {},
+    }
+    let (days, times) = match s.strip_prefix("at ") {
+        Some(times) => ("", times),
+        None => s.rsplit_once(" at ").unwrap_or((s, "")),
+    };
+    if times.is_empty() {
+        if let Some(interval) = s.strip_prefix("every ") {
+            if parse_days(s).is_err() {
+                return parse_interval(interval).map(Kind::Every);
+            }
+        }
+        return Err("Expected a time, like daily at 21:00".to_string());
+    }
+    let mut calendar = Calendar { weekdays: parse_days(days)?, ..Calendar::every_day() };
+    for time in times
+        .split(',')
+        .flat_map(|part| part.split(" and "))
+        .map(str::trim)
+    {
+        let (hour, minute) = parse_time(time)?;
+        calendar.add_time(hour, minute);
+    }
+    Ok(Kind::Calendar(Box::new(calendar)))
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let is_cron = normalized.starts_with(|c: char| c.is_ascii_digit() || c == '*');
+        let kind = if let Some(shorthand) = normalized.strip_prefix('@') {
+            parse_phrase(shorthand)
+        } else if is_cron {
+            parse_cron(&normalized).map(|calendar| Kind::Calendar(Box::new(calendar)))
+        } else {
+            parse_phrase(&normalized)
+        };
+        let kind = kind.map_err(|e| {
+            let first = normalized
+                .split(' ')
+                .next()
+                .unwrap_or_default();
+            format!(
+                "Invalid schedule '{s}': {e}. Use a cron expression like '*/15 * * * *' or a \
+                 phrase like 'every 15 minutes' or 'daily at 21:00'{}",
+                if is_cron || PHRASES.contains(&first) {
+                    String::new()
+                } else {
+                    suggest::hint(first, PHRASES)
+                }
+            )
+        })?;
+        if let Kind::Calendar(calendar) = &kind {
+            if calendar
+                .next_after(now_secs())
+                .is_none()
+            {
+                return Err(format!("Invalid schedule '{s}': it never fires"));
+            }
+        }
+        Ok(Self { text: s.trim().to_string(), kind })
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Schedule {
+    /// When this next fires as unix milliseconds, for clock-based schedules.
+    pub fn next_at(&self) -> Option<i64> {
+        match &self.kind {
+            Kind::Every(_) => None,
+            Kind::Calendar(calendar) => calendar
+                .next_after(now_secs())
+                .map(|at| at * 1000),
+        }
+    }
+
+    /// Call `fire` every time the schedule comes due, forever.
+    pub fn run(&self, mut fire: impl FnMut()) {
+        match &self.kind {
+            Kind::Every(interval) => {
+                let mut next = Instant::now() + *interval;
+                loop {
+                    thread::sleep(next.saturating_duration_since(Instant::now()));
+                    fire();
+                    next += *interval;
+                    // Don't catch up on runs missed while fire() was busy.
+                    if next < Instant::now() {
+                        next = Instant::now() + *interval;
+                    }
+                }
+            },
+            Kind::Calendar(calendar) => loop {
+                let Some(at) = calendar.next_after(now_secs()) else {
+                    return;
+                };
+                loop {
+                    let left = at - now_secs();
+                    if left <= 0 {
+                        break;
+                    }
+                    thread::sleep(MAX_SLEEP.min(Duration::from_secs(left as u64)));
+                }
+                fire();
+            },
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! The `signal` dispatcher: poke a bar or daemon that refreshes on a Unix signal.
+
+use std::fs;
+use std::process::Command;
+
+/// Which process(es) to signal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// Every process with exactly this name.
+    Process(String),
+    /// The process whose pid is in this file.
+    Pidfile(String),
+}
+
+const NAMES: [&str; 16] = [
+    "HUP", "INT", "QUIT", "KILL", "USR1", "USR2", "PIPE", "ALRM", "TERM", "CHLD", "CONT", "STOP",
+    "TSTP", "WINCH", "IO", "PWR",
+];
+
+/// Normalize a signal like `SIGRTMIN+8`, `usr1` or `10` to what `kill -s` understands.
+pub fn parse(signal: &str) -> Result<String, String> {
+    let upper = signal.to_uppercase();
+    let name = upper
+        .strip_prefix("SIG")
+        .unwrap_or(&upper);
+    let valid = name.parse::<u8>().is_ok()
+        || NAMES.contains(&name)
+        || ["RTMIN", "RTMAX"].iter().any(|rt| {
+            name.strip_prefix(rt)
+                .is_some_and(|offset| {
+                    offset.is_empty()
+                        || offset
+                            .trim_start_matches(['+', '-'])
+                            .parse::<u8>()
+                            .is_ok()
+                })
+        });
+    if valid { Ok(name.to_string()) } else { Err(format!("Unknown signal: {signal}")) }
+}
+
+pub fn send(target: &Target, signal: &str) -> Result<(), String> {
+    let signal = parse(signal)?;
+    let status = match target {
+        Target::Process(name) => Command::new("pkill")
+            .args(["--signal", &signal, "-x", name])
+            .status(),
+        Target::Pidfile(path) => {
+            let pid = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read pidfile {path}: {e}"))?;
+            let pid = pid.trim();
+            pid.parse::<u32>()
+                .map_err(|_| format!("Invalid pid in {path}: {pid}"))?;
+            Command::new("kill")
+                .args(["-s", &signal, pid])
+                .status()
+        },
+    }
+    .map_err(|e| format!("Failed to send signal: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        match target {
+            Target::Process(name) => Err(format!("No process named {name} to signal")),
+            Target::Pidfile(path) => Err(format!("Failed to signal the process in {path}")),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! `hyde-ipc hypr restart-safe` and `hypr restore`: restart Hyprland without losing the layout.
+//!
+//! `restart-safe` saves every window's workspace, floating state and geometry, plus the command
+//! line of its process, to the state dir before exiting (or reloading) Hyprland. `restore` puts
+//! windows that are still open back where they were and starts the ones that are missing on their
+//! old workspace. It forgets the snapshot afterwards, so it is safe to keep in `exec-once`.
+
+use crate::flags::HyprAction;
+use crate::ignore::IgnoreList;
+use crate::parsers::ParsedWorkspaceIdentifier;
+use crate::state_file;
+use hyprland::ctl::reload;
+use hyprland::data::{Client, Clients, Workspace};
+use hyprland::dispatch::{
+    Dispatch, DispatchType, Position, WindowIdentifier, WorkspaceIdentifierWithSpecial,
+};
+use hyprland::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{fs, thread};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Window {
+    class: String,
+    title: String,
+    /// Workspace as `hyde-ipc dispatch workspace` takes it: an id, `name:<name>` or
+    /// `special:<name>`.
+    workspace: String,
+    floating: bool,
+    at: (i16, i16),
+    size: (i16, i16),
+    pid: i32,
+    /// The process's command line, empty if it couldn't be read.
+    command: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    active_workspace: Option<String>,
+    windows: Vec<Window>,
+}
+
+const STATE_FILE: &str = "session.toml";
+
+fn load() -> Option<Snapshot> {
+    state_file::load(STATE_FILE)
+}
+
+fn save(snapshot: &Snapshot) -> Result<(), String> {
+    state_file::save(STATE_FILE, snapshot)
+}
+
+fn forget() -> Result<(), String> {
+    state_file::remove(STATE_FILE)
+}
+
+/// A workspace as `dispatch workspace` takes it: its id, `name:<name>` or `special:<name>`.
+pub fn workspace_target(id: i32, name: &str) -> String {
+    if id > 0 {
+        id.to_string()
+    } else if name.starts_with("special") {
+        name.to_string()
+    } else {
+        format!("name:{name}")
+    }
+}
+
+fn command_line(pid: i32) -> Vec<String> {
+    fs::read(format!("/proc/{pid}/cmdline"))
+        .map(|raw| {
+            raw.split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn snapshot() -> Result<Snapshot, String> {
+    let ignore = IgnoreList::from_global_config();
+    let windows = Clients::get()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| c.mapped && !ignore.is_client_ignored(c))
+        .map(|c| Window {
+            workspace: workspace_target(c.workspace.id, &c.workspace.name),
+            command: command_line(c.pid),
+            class: c.class,
+            title: c.title,
+            floating: c.floating,
+            at: c.at,
+            size: c.size,
+            pid: c.pid,
+        })
+        .collect();
+    let active_workspace = Workspace::get_active()
+        .ok()
+        .map(|w| workspace_target(w.id, &w.name));
+    Ok(Snapshot { active_workspace, windows })
+}
+
+fn confirm(question: &str) -> Result<bool, String> {
+    print!("{question} [y/N] ");
+    io::stdout()
+        .flush()
+        .map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| e.to_string())?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn call(dispatch: DispatchType) -> Result<(), String> {
+    Dispatch::call(dispatch).map_err(|e| e.to_string())
+}
+
+fn workspace_id(target: &str) -> Result<WorkspaceIdentifierWithSpecial<'static>, String> {
+    ParsedWorkspaceIdentifier::from_str(target).map(|id| id.0)
+}
+
+/// Put an open window back on its workspace with its floating state and geometry.
+fn place(client: &Client, window: &Window) -> Result<(), String> {
+    let address = WindowIdentifier::Address(client.address.clone());
+    if workspace_target(client.workspace.id, &client.workspace.name) != window.workspace {
+        call(DispatchType::MoveToWorkspaceSilent(
+            workspace_id(&window.workspace)?,
+            Some(address.clone()),
+        ))?;
+    }
+    if client.floating != window.floating {
+        call(DispatchType::ToggleFloating(Some(address.clone())))?;
+    }
+    if window.floating {
+        call(DispatchType::ResizeWindowPixel(
+            Position::Exact(window.size.0, window.size.1),
+            address.clone(),
+        ))?;
+        call(DispatchType::MoveWindowPixel(Position::Exact(window.at.0, window.at.1), address))?;
+    }
+    Ok(())
+}
+
+/// Start a window's program again with rules that open it where it was.
+fn launch(window: &Window) -> Result<(), String> {
+    let workspace = window
+        .workspace
+        .strip_prefix("name:")
+        .unwrap_or(&window.workspace);
+    let mut rules = vec![format!("workspace {workspace} silent")];
+    if window.floating {
+        rules.push("float".to_string());
+        rules.push(format!("size {} {}", window.size.0, window.size.1));
+        rules.push(format!("move {} {}", window.at.0, window.at.1));
+    }
+    let command: Vec<String> = window
+        .command
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect();
+    let line = format!("[{}] {}", rules.join("; "), command.join(" "));
+    call(DispatchType::Exec(&line))
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+fn restore() -> Result<(), String> {
+    let Some(snapshot) = load() else {
+        println!("No saved session");
+        return Ok(());
+    };
+    let clients = Clients::get()
+        .map_err(|e| e.to_string())?
+        .to_vec();
+    let mut claimed = HashSet::new();
+    let mut launched = HashSet::new();
+    let (mut placed, mut started) = (0, 0);
+    for window in &snapshot.windows {
+        // Prefer the window with the same title, then any unclaimed one of the class.
+        let candidates = || {
+            clients
+                .iter()
+                .filter(|c| c.class == window.class && !claimed.contains(&c.address))
+        };
+        let client = candidates()
+            .find(|c| c.title == window.title)
+            .or_else(|| candidates().next());
+        if let Some(client) = client {
+            claimed.insert(client.address.clone());
+            match place(client, window) {
+                Ok(()) => placed += 1,
+                Err(e) => eprintln!("Error restoring {}: {e}", window.class),
+            }
+        } else if !window.command.is_empty() && launched.insert(window.pid) {
+            // One process can own several windows; starting it once brings them all back.
+            match launch(window) {
+                Ok(()) => started += 1,
+                Err(e) => eprintln!("Error starting {}: {e}", window.class),
+            }
+        }
+    }
+    if let Some(workspace) = &snapshot.active_workspace {
+        if let Err(e) = workspace_id(workspace).and_then(|id| call(DispatchType::Workspace(id))) {
+            eprintln!("Error focusing workspace {workspace}: {e}");
+        }
+    }
+    forget()?;
+    println!("Restored {placed} windows, started {started} programs");
+    Ok(())
+}
+
+pub fn run(action: HyprAction) -> Result<(), String> {
+    match action {
+        HyprAction::RestartSafe { reload: reload_only, yes } => {
+            let snapshot = snapshot()?;
+            let what = if reload_only { "Reload" } else { "Exit" };
+            save(&snapshot)?;
+            println!("Saved {} windows", snapshot.windows.len());
+            if !yes && !confirm(&format!("{what} Hyprland now?"))? {
+                // Don't leave a snapshot behind for the next `hypr restore` to act on.
+                forget()?;
+                println!("Cancelled");
+                return Ok(());
+            }
+            if reload_only {
+                reload::call().map_err(|e| e.to_string())?;
+                // Give the compositor a moment to apply the reloaded config before moving windows.
+                thread::sleep(Duration::from_millis(500));
+                restore()
+            } else {
+                println!("Run `hyde-ipc hypr restore` once Hyprland is back, e.g. from exec-once");
+                call(DispatchType::Exit)
+            }
+        },
+        HyprAction::Restore => restore(),
+    }
+}
@@ -0,0 +1,88 @@
+//! Signals understood by the reaction daemon.
+//!
+//! `SIGHUP` reloads the config: if the file still parses, the daemon replaces itself with a fresh
+//! process running the same command line (counters and cooldowns carry over through
+//! [`reaction_state`](crate::reaction_state)), otherwise the error is logged and the running
+//! reactions stay in place. `SIGUSR1` and `SIGUSR2` print per-reaction statistics to the log.
+//! `SIGTERM` and `SIGINT` exit cleanly, which is how `react --replace` stops the previous daemon.
+
+use crate::react_config::ReactConfig;
+use crate::reaction_handler::Reaction;
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM, SIGUSR1, SIGUSR2};
+use signal_hook::iterator::Signals;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::{env, process, thread};
+
+pub fn spawn_handler(config_path: Option<PathBuf>, reactions: Vec<Arc<Reaction>>) {
+    let mut signals = match Signals::new([SIGHUP, SIGUSR1, SIGUSR2, SIGTERM, SIGINT]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            eprintln!("Error installing signal handlers: {e}");
+            return;
+        },
+    };
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            match signal {
+                SIGHUP => reload(config_path.as_ref()),
+                SIGTERM | SIGINT => {
+                    println!("Received signal {signal}, shutting down");
+                    process::exit(0);
+                },
+                _ => print_stats(&reactions),
+            }
+        }
+    });
+}
+
+fn reload(config_path: Option<&PathBuf>) {
+    let Some(path) = config_path else {
+        println!("Received SIGHUP, but inline reactions have no config to reload");
+        return;
+    };
+    if let Err(e) = ReactConfig::from_file(path) {
+        eprintln!("Not reloading, keeping the current reactions: {e}");
+        return;
+    }
+    println!("Reloading config from {}", path.display());
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Error reloading: {e}");
+            return;
+        },
+    };
+    // `exec` only returns if replacing the process failed.
+    let e = Command::new(exe)
+        .args(env::args_os().skip(1))
+        .exec();
+    eprintln!("Error reloading: {e}");
+}
+
+fn print_stats(reactions: &[Arc<Reaction>]) {
+    println!("Reaction statistics:");
+    for (index, reaction) in reactions.iter().enumerate() {
+        let name = reaction
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", index + 1));
+        let stats = &reaction.stats;
+        let last_fired = stats
+            .last_fired
+            .lock()
+            .unwrap()
+            .map_or("never".to_string(), |at| format!("{:.1?} ago", at.elapsed()));
+        println!(
+            "  {name} ({}): triggered {}, fired {}, errors {}, last fired {last_fired}",
+            reaction.event_type,
+            stats.triggered.load(Ordering::Relaxed),
+            stats.fired.load(Ordering::Relaxed),
+            stats.errors.load(Ordering::Relaxed),
+        );
+    }
+    println!("  busy socket retries: {}", hyprland::shared::retried_writes());
+}
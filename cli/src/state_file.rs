@@ -0,0 +1,117 @@
+//! Small state files: the ones for bars and scripts written by the `writefile` dispatcher, and the
+//! TOML files in the state dir hyde-ipc keeps its own switches and sessions in.
+//!
+//! Every file is replaced in one step, so a daemon reading one on each event never sees it half
+//! written. Switches checked on every event go through [`load_cached`], which only reads the file
+//! again once it was replaced.
+
+use hyde_ipc_lib::service;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use std::{env, fs, io, process};
+
+/// What a cached state file was read from. Replacing the file changes at least the inode.
+type Stamp = (Option<SystemTime>, u64, u64);
+
+/// A parsed state file, with the stamp of the file it was read from.
+type Cached = (Stamp, Box<dyn Any + Send>);
+
+static CACHE: Mutex<BTreeMap<String, Cached>> = Mutex::new(BTreeMap::new());
+
+/// Numbers this process's writes, for their temporary file names.
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// `~/` at the start of `path` is the home directory.
+fn expand(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Replace the file at `path` with `content`. The content goes to a temporary file next to it
+/// first, so readers never see a half-written file.
+pub fn write(path: &str, content: &str) -> Result<(), String> {
+    replace(&expand(path), content)
+}
+
+fn replace(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    // Handler threads of one daemon can write the same file at once, so each write gets its own
+    // temporary file.
+    let write_id = WRITES.fetch_add(1, Ordering::Relaxed);
+    let mut temp = path.to_path_buf().into_os_string();
+    temp.push(format!(".{}.{write_id}.tmp", process::id()));
+    fs::write(&temp, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to write {}: {e}", path.display())
+    })
+}
+
+/// The file `name` in the state dir.
+pub fn path(name: &str) -> Result<PathBuf, String> {
+    let mut path = service::get_state_dir().map_err(|e| e.to_string())?;
+    path.push(name);
+    Ok(path)
+}
+
+/// The TOML state file `name`, or none if it is missing or can't be read.
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let content = fs::read_to_string(path(name).ok()?).ok()?;
+    toml::from_str(&content).ok()
+}
+
+/// [`load`] for files checked on every event: the file is only read again once it changed, so
+/// an unchanged switch costs a `stat` rather than a read and a parse.
+pub fn load_cached<T: DeserializeOwned + Clone + Send + 'static>(name: &str) -> Option<T> {
+    let path = path(name).ok()?;
+    let mut cache = CACHE.lock().unwrap();
+    let Ok(metadata) = fs::metadata(&path) else {
+        cache.remove(name);
+        return None;
+    };
+    let stamp = (metadata.modified().ok(), metadata.len(), metadata.ino());
+    if let Some((cached, value)) = cache.get(name) {
+        if let Some(value) = value
+            .downcast_ref::<Option<T>>()
+            .filter(|_| *cached == stamp)
+        {
+            return value.clone();
+        }
+    }
+    let value: Option<T> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok());
+    cache.insert(name.to_string(), (stamp, Box::new(value.clone())));
+    value
+}
+
+/// Replace the TOML state file `name` with `value`.
+pub fn save<T: Serialize + ?Sized>(name: &str, value: &T) -> Result<(), String> {
+    let content = toml::to_string(value).map_err(|e| e.to_string())?;
+    replace(&path(name)?, &content)
+}
+
+/// Remove the state file `name`. One that isn't there is fine.
+pub fn remove(name: &str) -> Result<(), String> {
+    let path = path(name)?;
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {e}", path.display())),
+    }
+}
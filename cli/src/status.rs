@@ -0,0 +1,284 @@
+//! `hyde-ipc ctl status`: what the running reaction daemons are doing.
+//!
+//! Every daemon writes its per-reaction statistics to `status-<pid>.json` in the runtime dir every
+//! couple of seconds. `ctl status` reads the snapshots of daemons that are still alive and removes
+//! the rest. `--waybar` turns them into one object for a waybar `custom` module: the number of
+//! active reactions as `text`, the statistics as `tooltip` and `ok`, `paused` or `error` as
+//! `class`, the last when no daemon is running or a reaction failed in the last
+//! [`RECENT_ERRORS`].
+
+use crate::flags::OutputFormat;
+use crate::history::now_millis;
+use crate::output::{Printer, Record};
+use crate::reaction_handler::Reaction;
+use crate::{ctl, daemon_lock, scene, state_file};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use std::{fs, process, thread};
+
+/// How often a daemon writes its snapshot.
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// A snapshot this old belongs to a daemon that is gone, even if its pid was reused.
+const STALE_AFTER: Duration = Duration::from_secs(10);
+
+/// How long a failed reaction keeps the waybar class at `error`.
+const RECENT_ERRORS: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    pid: u32,
+    config: Option<String>,
+    /// Unix milliseconds.
+    written: i64,
+    reactions: Vec<ReactionStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReactionStatus {
+    name: String,
+    event: String,
+    /// Not disabled by the active scene and not out of `max_count`.
+    active: bool,
+    triggered: usize,
+    fired: usize,
+    errors: usize,
+    /// Unix milliseconds.
+    last_fired: Option<i64>,
+    /// Unix milliseconds.
+    #[serde(default)]
+    last_error: Option<i64>,
+}
+
+fn snapshot_path(pid: u32) -> Result<PathBuf, String> {
+    Ok(daemon_lock::runtime_dir()?.join(format!("status-{pid}.json")))
+}
+
+/// Unix milliseconds of an instant in the past.
+fn unix_millis(at: Instant) -> i64 {
+    now_millis() - at.elapsed().as_millis() as i64
+}
+
+fn snapshot(config: Option<&Path>, reactions: &[Arc<Reaction>]) -> Snapshot {
+    let is_enabled = scene::reaction_checker();
+    let reactions = reactions
+        .iter()
+        .enumerate()
+        .map(|(index, reaction)| {
+            let stats = &reaction.stats;
+            let exhausted = reaction
+                .max_count
+                .is_some_and(|max| max > 0 && reaction.counter.load(Ordering::SeqCst) >= max);
+            ReactionStatus {
+                name: reaction
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("#{}", index + 1)),
+                event: reaction.event_type.to_string(),
+                active: !exhausted && is_enabled(reaction.name.as_deref(), reaction.disabled),
+                triggered: stats.triggered.load(Ordering::Relaxed),
+                fired: stats.fired.load(Ordering::Relaxed),
+                errors: stats.errors.load(Ordering::Relaxed),
+                last_fired: stats
+                    .last_fired
+                    .lock()
+                    .unwrap()
+                    .map(unix_millis),
+                last_error: stats
+                    .last_error
+                    .lock()
+                    .unwrap()
+                    .map(unix_millis),
+            }
+        })
+        .collect();
+    Snapshot {
+        pid: process::id(),
+        config: config.map(|path| path.display().to_string()),
+        written: now_millis(),
+        reactions,
+    }
+}
+
+/// Keep writing the daemon's snapshot for `ctl status` in the background.
+pub fn spawn_publisher(config: Option<PathBuf>, reactions: Vec<Arc<Reaction>>) {
+    let path = match snapshot_path(process::id()) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return;
+        },
+    };
+    thread::spawn(move || {
+        loop {
+            let written = serde_json::to_string(&snapshot(config.as_deref(), &reactions))
+                .map_err(|e| e.to_string())
+                .and_then(|content| state_file::write(&path.to_string_lossy(), &content));
+            if let Err(e) = written {
+                eprintln!("Error writing status: {e}");
+            }
+            thread::sleep(INTERVAL);
+        }
+    });
+}
+
+fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: kill takes no pointers, and signal 0 only checks that the process exists.
+    unsafe { libc::kill(pid, 0) == 0 }
+}
+
+/// Snapshots of the daemons still running. Those of daemons that are gone are removed.
+fn running() -> Result<Vec<Snapshot>, String> {
+    let Ok(entries) = fs::read_dir(daemon_lock::runtime_dir()?) else {
+        return Ok(Vec::new());
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_snapshot = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("status-") && name.ends_with(".json"));
+        if !is_snapshot {
+            continue;
+        }
+        let snapshot = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Snapshot>(&content).ok());
+        match snapshot {
+            Some(snapshot)
+                if is_alive(snapshot.pid)
+                    && now_millis() - snapshot.written < STALE_AFTER.as_millis() as i64 =>
+            {
+                snapshots.push(snapshot);
+            },
+            _ => {
+                let _ = fs::remove_file(&path);
+            },
+        }
+    }
+    snapshots.sort_by_key(|snapshot| snapshot.pid);
+    Ok(snapshots)
+}
+
+/// How long ago a unix millisecond time was, roughly.
+fn ago(millis: i64) -> String {
+    let seconds = (now_millis() - millis).max(0) / 1000;
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+fn reaction_line(reaction: &ReactionStatus) -> String {
+    let mut line = format!(
+        "{} ({}): fired {} of {}, last {}",
+        reaction.name,
+        reaction.event,
+        reaction.fired,
+        reaction.triggered,
+        reaction
+            .last_fired
+            .map_or("never".to_string(), ago)
+    );
+    if reaction.errors > 0 {
+        line.push_str(&format!(", {} errors", reaction.errors));
+    }
+    if !reaction.active {
+        line.push_str(", inactive");
+    }
+    line
+}
+
+/// Waybar reads tooltips as Pango markup.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn waybar(snapshots: &[Snapshot], paused: bool) -> String {
+    let reactions = || {
+        snapshots
+            .iter()
+            .flat_map(|snapshot| &snapshot.reactions)
+    };
+    let active = reactions()
+        .filter(|reaction| reaction.active)
+        .count();
+    let failing = reactions().any(|reaction| {
+        reaction
+            .last_error
+            .is_some_and(|at| now_millis() - at < RECENT_ERRORS.as_millis() as i64)
+    });
+    let class = if snapshots.is_empty() || failing {
+        "error"
+    } else if paused {
+        "paused"
+    } else {
+        "ok"
+    };
+    let mut tooltip = Vec::new();
+    if snapshots.is_empty() {
+        tooltip.push("No reaction daemon running".to_string());
+    }
+    if paused {
+        tooltip.push("Reactions paused".to_string());
+    }
+    tooltip.extend(reactions().map(reaction_line));
+    json!({
+        "text": active.to_string(),
+        "tooltip": escape_markup(&tooltip.join("\n")),
+        "class": class,
+    })
+    .to_string()
+}
+
+pub fn run(waybar_output: bool, format: OutputFormat) -> Result<(), String> {
+    let snapshots = running()?;
+    let paused = ctl::is_paused();
+    if waybar_output || format == OutputFormat::Waybar {
+        println!("{}", waybar(&snapshots, paused));
+        return Ok(());
+    }
+    let mut plain = Vec::new();
+    if snapshots.is_empty() {
+        plain.push("No reaction daemon running".to_string());
+    }
+    if paused {
+        plain.push("Reactions paused".to_string());
+    }
+    for snapshot in &snapshots {
+        let active = snapshot
+            .reactions
+            .iter()
+            .filter(|reaction| reaction.active)
+            .count();
+        plain.push(format!(
+            "Daemon {} ({}): {active} of {} reactions active",
+            snapshot.pid,
+            snapshot
+                .config
+                .as_deref()
+                .unwrap_or("inline reactions"),
+            snapshot.reactions.len()
+        ));
+        plain.extend(
+            snapshot
+                .reactions
+                .iter()
+                .map(|reaction| format!("  {}", reaction_line(reaction))),
+        );
+    }
+    let value = json!({ "paused": paused, "daemons": snapshots });
+    Printer::new(format).print(&Record::new(&value, plain.join("\n"))?)
+}
@@ -0,0 +1,191 @@
+//! `--swap-if-occupied` for `move-window` and `move-to-workspace`: when the destination already
+//! holds a window, the two trade places instead of stacking.
+//!
+//! The destination is worked out from a clients query, then the moves go to Hyprland as one batch.
+//! The window swapped back is the destination's most recently focused one.
+
+use crate::parsers::{ParsedWindowMove, ParsedWorkspaceIdentifier};
+use crate::session::workspace_target;
+use hyprland::data::{Client, Clients, Monitor, Monitors};
+use hyprland::dispatch::{
+    Direction, DispatchType, MonitorIdentifier, WindowIdentifier, WindowMove,
+    WorkspaceIdentifierWithSpecial,
+};
+use hyprland::prelude::*;
+use std::str::FromStr;
+
+fn active_client(clients: &[Client]) -> Result<&Client, String> {
+    clients
+        .iter()
+        .find(|c| c.mapped && c.focus_history_id == 0)
+        .ok_or_else(|| "No active window".to_string())
+}
+
+/// The most recently focused window on workspace `id`, other than `moving`.
+fn occupant<'a>(clients: &'a [Client], id: i32, moving: &Client) -> Option<&'a Client> {
+    clients
+        .iter()
+        .filter(|c| c.mapped && c.workspace.id == id && c.address != moving.address)
+        .min_by_key(|c| c.focus_history_id)
+}
+
+/// Send `occupant` back to where `moving` came from, without following it.
+fn swap_back(moving: &Client, occupant: &Client) -> Result<DispatchType<'static>, String> {
+    let source = workspace_target(moving.workspace.id, &moving.workspace.name);
+    Ok(DispatchType::MoveToWorkspaceSilent(
+        ParsedWorkspaceIdentifier::from_str(&source)?.0,
+        Some(WindowIdentifier::Address(occupant.address.clone())),
+    ))
+}
+
+/// `move-to-workspace workspace`, swapping with a window already there. Only workspace ids and
+/// names can be looked up.
+pub fn to_workspace(workspace: &str) -> Result<Vec<DispatchType<'static>>, String> {
+    let target = ParsedWorkspaceIdentifier::from_str(workspace)?.0;
+    if !matches!(
+        target,
+        WorkspaceIdentifierWithSpecial::Id(_) | WorkspaceIdentifierWithSpecial::Name(_)
+    ) {
+        return Err(format!(
+            "--swap-if-occupied needs a workspace id or name:<name>, got {workspace}"
+        ));
+    }
+    let clients = Clients::get()
+        .map_err(|e| e.to_string())?
+        .to_vec();
+    let active = active_client(&clients)?;
+    let target_id = clients
+        .iter()
+        .find(|c| match target {
+            WorkspaceIdentifierWithSpecial::Id(id) => c.workspace.id == id,
+            WorkspaceIdentifierWithSpecial::Name(name) => c.workspace.name == name,
+            _ => false,
+        })
+        .map(|c| c.workspace.id);
+    let mut batch = vec![DispatchType::MoveToWorkspace(
+        target,
+        Some(WindowIdentifier::Address(active.address.clone())),
+    )];
+    if let Some(other) = target_id
+        .filter(|&id| id != active.workspace.id)
+        .and_then(|id| occupant(&clients, id, active))
+    {
+        batch.push(swap_back(active, other)?);
+    }
+    Ok(batch)
+}
+
+/// `move-window target`, swapping with a window already there. Directions swap with the
+/// neighbouring window if there is one, monitors with a window on the monitor's active workspace.
+/// A direction without a neighbouring window leads to the next monitor over, which then counts as
+/// the target monitor.
+pub fn move_window(target: &str) -> Result<Vec<DispatchType<'static>>, String> {
+    let window_move = ParsedWindowMove::from_str(target)?.0;
+    let clients = Clients::get()
+        .map_err(|e| e.to_string())?
+        .to_vec();
+    let active = active_client(&clients)?;
+    let target_id = match &window_move {
+        WindowMove::Direction(direction) => {
+            if has_neighbour(&clients, active, direction) {
+                return Ok(vec![DispatchType::SwapWindow(direction.clone())]);
+            }
+            let monitors = Monitors::get().map_err(|e| e.to_string())?;
+            let next = monitors
+                .iter()
+                .find(|m| Some(m.id) == active.monitor)
+                .and_then(|current| neighbour_monitor(&monitors, current, direction));
+            match next {
+                Some(next) => next.active_workspace.id,
+                None => return Ok(vec![DispatchType::MoveWindow(window_move)]),
+            }
+        },
+        WindowMove::Monitor(monitor) => {
+            let monitors = Monitors::get().map_err(|e| e.to_string())?;
+            let found = monitors
+                .iter()
+                .find(|m| match monitor {
+                    MonitorIdentifier::Name(name) => m.name == *name,
+                    MonitorIdentifier::Id(id) => m.id == *id,
+                    MonitorIdentifier::Current => m.focused,
+                    _ => false,
+                })
+                .ok_or_else(|| {
+                    format!("--swap-if-occupied needs a monitor name or id, got {target}")
+                })?;
+            found.active_workspace.id
+        },
+    };
+    let mut batch = vec![DispatchType::MoveWindow(window_move)];
+    if let Some(other) = Some(target_id)
+        .filter(|&id| id != active.workspace.id)
+        .and_then(|id| occupant(&clients, id, active))
+    {
+        batch.push(swap_back(active, other)?);
+    }
+    Ok(batch)
+}
+
+/// Where `monitor` sits in the layout, as left, top, right and bottom.
+fn monitor_rect(monitor: &Monitor) -> (i32, i32, i32, i32) {
+    let scale = if monitor.scale > 0.0 { monitor.scale } else { 1.0 };
+    let (width, height) = (monitor.width as f32 / scale, monitor.height as f32 / scale);
+    let (width, height) = match monitor.transform as u8 % 2 {
+        1 => (height, width),
+        _ => (width, height),
+    };
+    (monitor.x, monitor.y, monitor.x + width as i32, monitor.y + height as i32)
+}
+
+/// The closest enabled monitor in `direction` from `current` that lines up with it.
+fn neighbour_monitor<'a>(
+    monitors: &'a Monitors,
+    current: &Monitor,
+    direction: &Direction,
+) -> Option<&'a Monitor> {
+    let (left, top, right, bottom) = monitor_rect(current);
+    monitors
+        .iter()
+        .filter(|m| m.id != current.id && !m.disabled)
+        .filter_map(|m| {
+            let (m_left, m_top, m_right, m_bottom) = monitor_rect(m);
+            let overlaps_vertically = m_top < bottom && m_bottom > top;
+            let overlaps_horizontally = m_left < right && m_right > left;
+            let distance = match direction {
+                Direction::Left if overlaps_vertically && m_right <= left => left - m_right,
+                Direction::Right if overlaps_vertically && m_left >= right => m_left - right,
+                Direction::Up if overlaps_horizontally && m_bottom <= top => top - m_bottom,
+                Direction::Down if overlaps_horizontally && m_top >= bottom => m_top - bottom,
+                _ => return None,
+            };
+            Some((distance, m))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, m)| m)
+}
+
+/// Whether a window on the same workspace and layer (tiled or floating) lies in `direction`.
+fn has_neighbour(clients: &[Client], active: &Client, direction: &Direction) -> bool {
+    let (left, top) = (active.at.0, active.at.1);
+    let (right, bottom) = (left + active.size.0, top + active.size.1);
+    clients
+        .iter()
+        .filter(|c| {
+            c.mapped
+                && c.address != active.address
+                && c.workspace.id == active.workspace.id
+                && c.floating == active.floating
+        })
+        .any(|c| {
+            let (c_left, c_top) = (c.at.0, c.at.1);
+            let (c_right, c_bottom) = (c_left + c.size.0, c_top + c.size.1);
+            let overlaps_vertically = c_top < bottom && c_bottom > top;
+            let overlaps_horizontally = c_left < right && c_right > left;
+            match direction {
+                Direction::Left => c_right <= left && overlaps_vertically,
+                Direction::Right => c_left >= right && overlaps_vertically,
+                Direction::Up => c_bottom <= top && overlaps_horizontally,
+                Direction::Down => c_top >= bottom && overlaps_horizontally,
+            }
+        })
+}
@@ -0,0 +1,241 @@
+//! `{placeholder}` substitution in reaction dispatcher arguments.
+//!
+//! A placeholder is a name in braces, looked up when the reaction fires. `{{` and `}}` stand for
+//! literal braces. Unknown placeholders are an error rather than being left in place, so typos
+//! don't end up in files or commands.
+//!
+//! The value can be passed through filters separated by `|`, with `:` before each argument:
+//! `{class|lower}`, `{title|regex_replace:' - .*':''}`. Arguments containing `|`, `:` or `}` go in
+//! single quotes.
+
+use crate::suggest;
+use regex_lite::Regex;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+const FILTERS: [&str; 4] = ["lower", "upper", "trim", "regex_replace"];
+
+/// A placeholder's value, or how to work it out the first time it is used.
+#[derive(Debug, Clone)]
+enum Value {
+    Text(String),
+    /// Copies of the vars share the computed value.
+    Lazy(fn() -> String, Arc<OnceLock<String>>),
+}
+
+/// Values placeholders are replaced with.
+#[derive(Debug, Clone, Default)]
+pub struct Vars(BTreeMap<&'static str, Value>);
+
+impl Vars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: &'static str, value: impl ToString) -> &mut Self {
+        self.0
+            .insert(name, Value::Text(value.to_string()));
+        self
+    }
+
+    /// Set `name` to what `value` returns, called only once something looks it up.
+    pub fn set_lazy(&mut self, name: &'static str, value: fn() -> String) -> &mut Self {
+        self.0
+            .insert(name, Value::Lazy(value, Arc::default()));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .get(name)
+            .map(|value| match value {
+                Value::Text(text) => text.as_str(),
+                Value::Lazy(compute, cell) => cell.get_or_init(compute).as_str(),
+            })
+    }
+
+    /// Add `other`'s values, replacing any already set.
+    pub fn extend(&mut self, other: &Vars) {
+        self.0.extend(
+            other
+                .0
+                .iter()
+                .map(|(name, value)| (*name, value.clone())),
+        );
+    }
+
+    /// Every value as text, for saving them.
+    pub fn to_strings(&self) -> BTreeMap<String, String> {
+        self.0
+            .keys()
+            .filter_map(|name| Some((name.to_string(), self.get(name)?.to_string())))
+            .collect()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.keys().copied().collect()
+    }
+}
+
+impl<const N: usize> From<[(&'static str, String); N]> for Vars {
+    fn from(values: [(&'static str, String); N]) -> Self {
+        Self(
+            values
+                .into_iter()
+                .map(|(name, value)| (name, Value::Text(value)))
+                .collect(),
+        )
+    }
+}
+
+pub fn render(template: &str, vars: &Vars) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            },
+            '{' => {
+                let mut placeholder = String::new();
+                let mut closed = false;
+                let mut quoted = false;
+                for c in chars.by_ref() {
+                    match c {
+                        '}' if !quoted => {
+                            closed = true;
+                            break;
+                        },
+                        '\'' => quoted = !quoted,
+                        _ => {},
+                    }
+                    placeholder.push(c);
+                }
+                if !closed {
+                    return Err(format!("Unclosed {{ in \"{template}\""));
+                }
+                let mut parts = split_unquoted(&placeholder, '|').into_iter();
+                let name = parts.next().unwrap_or_default().trim();
+                let mut value = vars
+                    .get(name)
+                    .ok_or_else(|| {
+                        format!(
+                            "Unknown placeholder {{{name}}} in \"{template}\", available: {}",
+                            vars.names().join(", ")
+                        )
+                    })?
+                    .to_string();
+                for filter in parts {
+                    value = apply_filter(&value, filter)
+                        .map_err(|e| format!("{e} in \"{template}\""))?;
+                }
+                output.push_str(&value);
+            },
+            '}' => {
+                return Err(format!(
+                    "Unmatched }} in \"{template}\", use }}}} for a literal brace"
+                ));
+            },
+            c => output.push(c),
+        }
+    }
+    Ok(output)
+}
+
+/// Like [`render`], but only for placeholders naming one of `vars`. Anything else in braces, like
+/// the shell's `${HOME}` or awk's `{print $1}`, is left as it is, and so are `{{` and `}}`. For
+/// `exec` commands, which were passed through untouched before they took placeholders, and the
+/// commands `foreach` runs.
+pub fn render_known(template: &str, vars: &Vars) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let mut quoted = false;
+        let end = after
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '\'' {
+                    quoted = !quoted;
+                }
+                c == '}' && !quoted
+            })
+            .map(|(index, _)| index);
+        let placeholder = end.map(|end| &after[..end]);
+        let known = placeholder.is_some_and(|placeholder| {
+            let name = split_unquoted(placeholder, '|')[0].trim();
+            vars.get(name).is_some()
+        });
+        match (placeholder, end) {
+            (Some(placeholder), Some(end)) if known => {
+                output.push_str(&render(&format!("{{{placeholder}}}"), vars)?);
+                rest = &after[end + 1..];
+            },
+            _ => {
+                output.push('{');
+                rest = after;
+            },
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Split `s` on `separator`, except inside single quotes.
+fn split_unquoted(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, c) in s.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                parts.push(&s[start..index]);
+                start = index + c.len_utf8();
+            },
+            _ => {},
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn unquote(arg: &str) -> &str {
+    let arg = arg.trim();
+    arg.strip_prefix('\'')
+        .and_then(|arg| arg.strip_suffix('\''))
+        .unwrap_or(arg)
+}
+
+fn apply_filter(value: &str, filter: &str) -> Result<String, String> {
+    let mut parts = split_unquoted(filter, ':').into_iter();
+    let name = parts.next().unwrap_or_default().trim();
+    let args: Vec<&str> = parts.map(unquote).collect();
+    let expect_args = |count: usize| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(format!("Filter {name} takes {count} arguments, got {}", args.len()))
+        }
+    };
+    match name {
+        "lower" => expect_args(0).map(|()| value.to_lowercase()),
+        "upper" => expect_args(0).map(|()| value.to_uppercase()),
+        "trim" => expect_args(0).map(|()| value.trim().to_string()),
+        "regex_replace" => {
+            expect_args(2)?;
+            let regex =
+                Regex::new(args[0]).map_err(|e| format!("Invalid regex '{}': {e}", args[0]))?;
+            Ok(regex
+                .replace_all(value, args[1])
+                .into_owned())
+        },
+        _ => Err(format!("Unknown filter {name}{}", suggest::hint(name, FILTERS))),
+    }
+}
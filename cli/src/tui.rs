@@ -0,0 +1,300 @@
+//! `listen --tui`: an interactive event monitor. Needs the `tui` feature.
+//!
+//! Events go into a scrollable list, colored by type. The header lists every type seen so far with
+//! its count, and the number keys hide or show them. `p` pauses the view while events keep being
+//! collected, and `/` narrows the list to events containing some text. The listener itself runs in
+//! a thread and hands events over through a channel, so the filters from the command line still
+//! apply before anything reaches the monitor.
+
+#[cfg(feature = "tui")]
+pub use monitor::run;
+
+#[cfg(not(feature = "tui"))]
+pub fn run<F>(_listen: F) -> Result<(), String>
+where
+    F: FnOnce(crate::listen::Output) -> hyprland::Result<()>,
+{
+    Err("listen --tui needs hyde-ipc built with the tui feature".to_string())
+}
+
+#[cfg(feature = "tui")]
+mod monitor {
+    use crate::failure;
+    use crate::listen::Output;
+    use crate::output::Record;
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Paragraph};
+    use ratatui::{DefaultTerminal, Frame};
+    use std::collections::VecDeque;
+    use std::sync::mpsc::{self, Receiver, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Events kept for scrolling back; older ones are dropped.
+    const MAX_EVENTS: usize = 10_000;
+
+    /// How often the screen is redrawn while nothing is pressed.
+    const TICK: Duration = Duration::from_millis(100);
+
+    fn type_color(event: &str) -> Color {
+        match event {
+            "window" => Color::Cyan,
+            "workspace" => Color::Green,
+            "monitor" => Color::Magenta,
+            "float" | "fullscreen" => Color::Yellow,
+            "group" => Color::Blue,
+            "layout" | "submap" => Color::LightRed,
+            "config" => Color::Red,
+            "unknown" => Color::DarkGray,
+            _ => Color::White,
+        }
+    }
+
+    struct Entry {
+        event: String,
+        line: String,
+    }
+
+    enum Mode {
+        Normal,
+        /// Typing the search text.
+        Search,
+    }
+
+    struct Monitor {
+        entries: VecDeque<Entry>,
+        /// Every type seen so far, in order of first appearance, with its count and whether it is
+        /// shown.
+        types: Vec<(String, usize, bool)>,
+        /// How many entries are shown while paused.
+        paused_at: Option<usize>,
+        search: String,
+        mode: Mode,
+        /// Lines scrolled up from the newest event; 0 follows new events.
+        scroll: usize,
+    }
+
+    impl Monitor {
+        fn new() -> Self {
+            Self {
+                entries: VecDeque::new(),
+                types: Vec::new(),
+                paused_at: None,
+                search: String::new(),
+                mode: Mode::Normal,
+                scroll: 0,
+            }
+        }
+
+        fn push(&mut self, record: Record) {
+            let event = record
+                .value
+                .get("type")
+                .and_then(|value| value.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            match self
+                .types
+                .iter_mut()
+                .find(|(name, ..)| *name == event)
+            {
+                Some((_, count, _)) => *count += 1,
+                None => self
+                    .types
+                    .push((event.clone(), 1, true)),
+            }
+            self.entries
+                .push_back(Entry { event, line: record.plain });
+            if self.entries.len() > MAX_EVENTS {
+                self.entries.pop_front();
+                if let Some(paused_at) = &mut self.paused_at {
+                    *paused_at = paused_at.saturating_sub(1);
+                }
+            }
+        }
+
+        fn is_shown(&self, entry: &Entry) -> bool {
+            let type_shown = self
+                .types
+                .iter()
+                .any(|(name, _, shown)| *shown && *name == entry.event);
+            type_shown
+                && (self.search.is_empty()
+                    || entry
+                        .line
+                        .to_lowercase()
+                        .contains(&self.search.to_lowercase()))
+        }
+
+        fn visible(&self) -> Vec<&Entry> {
+            let end = self
+                .paused_at
+                .unwrap_or(self.entries.len());
+            self.entries
+                .range(..end)
+                .filter(|entry| self.is_shown(entry))
+                .collect()
+        }
+
+        /// Handle a key press, returning false to quit.
+        fn key(&mut self, key: KeyEvent) -> bool {
+            if key
+                .modifiers
+                .contains(KeyModifiers::CONTROL)
+                && key.code == KeyCode::Char('c')
+            {
+                return false;
+            }
+            if let Mode::Search = self.mode {
+                match key.code {
+                    KeyCode::Enter => self.mode = Mode::Normal,
+                    KeyCode::Esc => {
+                        self.search.clear();
+                        self.mode = Mode::Normal;
+                    },
+                    KeyCode::Backspace => {
+                        self.search.pop();
+                    },
+                    KeyCode::Char(c) => self.search.push(c),
+                    _ => {},
+                }
+                self.scroll = 0;
+                return true;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Char('p') | KeyCode::Char(' ') => {
+                    self.paused_at = match self.paused_at {
+                        Some(_) => None,
+                        None => Some(self.entries.len()),
+                    };
+                },
+                KeyCode::Char('/') => self.mode = Mode::Search,
+                KeyCode::Char(c @ '1'..='9') => {
+                    let index = c as usize - '1' as usize;
+                    if let Some((_, _, shown)) = self.types.get_mut(index) {
+                        *shown = !*shown;
+                    }
+                },
+                KeyCode::Char('a') => {
+                    for (_, _, shown) in &mut self.types {
+                        *shown = true;
+                    }
+                },
+                KeyCode::Up | KeyCode::Char('k') => self.scroll += 1,
+                KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::PageUp => self.scroll += 20,
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(20),
+                KeyCode::Home | KeyCode::Char('g') => self.scroll = usize::MAX,
+                KeyCode::End | KeyCode::Char('G') => self.scroll = 0,
+                _ => {},
+            }
+            true
+        }
+
+        fn draw(&mut self, frame: &mut Frame) {
+            let [header, body, footer] = Layout::vertical([
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .areas(frame.area());
+
+            let mut types = Vec::new();
+            for (index, (name, count, shown)) in self.types.iter().enumerate() {
+                let style = if *shown {
+                    Style::default().fg(type_color(name))
+                } else {
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::CROSSED_OUT)
+                };
+                let key = if index < 9 { format!("{}:", index + 1) } else { String::new() };
+                types.push(Span::styled(format!("{key}{name} {count}  "), style));
+            }
+            frame.render_widget(Paragraph::new(Line::from(types)), header);
+
+            let visible = self.visible();
+            let height = body.height.saturating_sub(2) as usize;
+            let max_scroll = visible.len().saturating_sub(height);
+            let scroll = self.scroll.min(max_scroll);
+            let end = visible.len() - scroll;
+            let lines: Vec<Line> = visible[end.saturating_sub(height)..end]
+                .iter()
+                .map(|entry| {
+                    Line::styled(entry.line.as_str(), Style::default().fg(type_color(&entry.event)))
+                })
+                .collect();
+            let mut title = format!(" {} of {} events ", visible.len(), self.entries.len());
+            if let Some(paused_at) = self.paused_at {
+                title.push_str(&format!("- paused, {} new ", self.entries.len() - paused_at));
+            }
+            frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), body);
+            self.scroll = scroll;
+
+            let status = match self.mode {
+                Mode::Search => format!("/{}", self.search),
+                Mode::Normal if !self.search.is_empty() => format!(
+                    "search: {}   q quit  p pause  / search  1-9 toggle type  a show all",
+                    self.search
+                ),
+                Mode::Normal => {
+                    "q quit  p pause  / search  1-9 toggle type  a show all  ↑↓ scroll".to_string()
+                },
+            };
+            frame.render_widget(
+                Paragraph::new(status).style(Style::default().add_modifier(Modifier::REVERSED)),
+                footer,
+            );
+        }
+    }
+
+    /// Run the monitor until `q`, with `listen` started in the background to feed it.
+    pub fn run<F>(listen: F) -> Result<(), String>
+    where
+        F: FnOnce(Output) -> hyprland::Result<()> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let listener = thread::spawn(move || listen(Output::Channel(sender)));
+        let mut terminal = ratatui::init();
+        let result = monitor(&mut terminal, &receiver);
+        ratatui::restore();
+        match result {
+            Ok(true) => Ok(()),
+            // The listener stopped, which only happens when it failed.
+            Ok(false) => match listener.join() {
+                Ok(Err(e)) => failure::listener_failed(e),
+                _ => Ok(()),
+            },
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Draw and handle keys until the user quits (true) or the listener goes away (false).
+    fn monitor(
+        terminal: &mut DefaultTerminal,
+        receiver: &Receiver<Record>,
+    ) -> std::io::Result<bool> {
+        let mut monitor = Monitor::new();
+        loop {
+            loop {
+                match receiver.try_recv() {
+                    Ok(record) => monitor.push(record),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(false),
+                }
+            }
+            terminal.draw(|frame| monitor.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !monitor.key(key) {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Config values with units: durations like `250ms` or `2s`, and lengths like `40%`.
+//!
+//! Options that take a time or a size use these instead of a raw integer, so every one of them
+//! accepts the same spellings, and so do command line flags and schedule intervals. A bare number
+//! keeps meaning milliseconds or pixels, which is what such options always took, so older configs
+//! read the same.
+
+use crate::parsers::ParsedDuration;
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A number or a string in a config file, for values that may carry a unit.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Raw {
+    Number(i64),
+    Text(String),
+}
+
+/// A duration like `250ms`, `2s`, `5m` or `1h`. A bare number is milliseconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    s.trim()
+        .parse::<ParsedDuration>()
+        .map(|duration| duration.0)
+}
+
+pub fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(millis) => u64::try_from(millis)
+            .map(Duration::from_millis)
+            .map_err(|_| serde::de::Error::custom(format!("Invalid duration: {millis}"))),
+        Raw::Text(s) => parse_duration(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+pub fn deserialize_optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_duration(deserializer).map(Some)
+}
+
+/// A size in pixels, or a percentage of whatever it is measured against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Pixels(i32),
+    Percent(f32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Self::Pixels(0)
+    }
+}
+
+impl Length {
+    /// The length in pixels, with percentages taken of `total`.
+    pub fn resolve(self, total: i32) -> i32 {
+        match self {
+            Self::Pixels(pixels) => pixels,
+            Self::Percent(percent) => (total as f32 * percent / 100.0).round() as i32,
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    /// `40%`, `20px` or a bare number of pixels.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(percent) = s.strip_suffix('%') {
+            return percent
+                .trim()
+                .parse()
+                .map(Self::Percent)
+                .map_err(|_| format!("Invalid percentage: {s}, expected e.g. 40%"));
+        }
+        s.strip_suffix("px")
+            .unwrap_or(s)
+            .trim()
+            .parse()
+            .map(Self::Pixels)
+            .map_err(|_| {
+                format!("Invalid length: {s}, expected pixels like 20 or a percentage like 40%")
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(pixels) => i32::try_from(pixels)
+                .map(Self::Pixels)
+                .map_err(serde::de::Error::custom),
+            Raw::Text(s) => s
+                .parse()
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! `hyde-ipc version check`: compare the running Hyprland with what hyde-ipc expects.
+//!
+//! Hyprland renames events and removes dispatchers between releases without a protocol version, so
+//! the known breaks are listed here by the release that introduced them.
+
+use crate::flags::VersionAction;
+use hyprland::data::Version;
+use hyprland::prelude::*;
+use hyprland::shared::HyprError;
+
+type Release = (u32, u32, u32);
+
+/// The oldest Hyprland whose `hyprctl version` output hyde-ipc can read.
+const OLDEST_SUPPORTED: Release = (0, 42, 0);
+
+/// The newest Hyprland this release of hyde-ipc was checked against.
+const NEWEST_TESTED: Release = (0, 50, 0);
+
+/// Something hyde-ipc offers that only works on one side of a Hyprland release.
+enum Break {
+    /// Only available from this release on.
+    AddedIn(Release, &'static str),
+}
+
+static BREAKS: &[Break] = &[Break::AddedIn(
+    (0, 42, 0),
+    "`hyprctl version` reports the aquamarine build, `query` and `report` fail to read older \
+     versions",
+)];
+
+fn format_release((major, minor, patch): Release) -> String {
+    format!("{major}.{minor}.{patch}")
+}
+
+/// `0.45.2`, `v0.45.2` or `v0.45.2-12-gdeadbeef`.
+fn parse_release(version: &str) -> Option<Release> {
+    let mut parts = version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map(str::parse::<u32>);
+    Some((parts.next()?.ok()?, parts.next()?.ok()?, parts.next()?.ok()?))
+}
+
+/// The known problems with Hyprland `running` under this hyde-ipc.
+fn problems(running: Release) -> Vec<String> {
+    let mut problems: Vec<String> = BREAKS
+        .iter()
+        .filter_map(|known| match *known {
+            Break::AddedIn(release, what) if running < release => {
+                Some(format!("before Hyprland {}: {what}", format_release(release)))
+            },
+            _ => None,
+        })
+        .collect();
+    if running < OLDEST_SUPPORTED {
+        problems.push(format!(
+            "Hyprland {} is older than the oldest supported release, {}; upgrade Hyprland",
+            format_release(running),
+            format_release(OLDEST_SUPPORTED)
+        ));
+    }
+    problems
+}
+
+fn check() -> Result<(), String> {
+    println!("hyde-ipc {}", env!("CARGO_PKG_VERSION"));
+    let version = Version::get().map_err(|e| match e {
+        HyprError::SerdeError(e) => format!(
+            "Can't read Hyprland's version ({e}). Hyprland older than {} reports it in a format \
+             hyde-ipc doesn't understand; upgrade Hyprland",
+            format_release(OLDEST_SUPPORTED)
+        ),
+        e => e.to_string(),
+    })?;
+    let reported = version.version.unwrap_or(version.tag);
+    let running = parse_release(&reported)
+        .ok_or_else(|| format!("Can't make sense of Hyprland version {reported}"))?;
+    println!("Hyprland {reported}");
+
+    let problems = problems(running);
+    if running > NEWEST_TESTED {
+        println!(
+            "Hyprland {} is newer than this hyde-ipc was tested against ({}). If events or \
+             dispatchers misbehave, check for a newer hyde-ipc.",
+            format_release(running),
+            format_release(NEWEST_TESTED)
+        );
+    }
+    if problems.is_empty() {
+        println!("No known incompatibilities");
+        return Ok(());
+    }
+    println!("Known incompatibilities:");
+    for problem in &problems {
+        println!("  - {problem}");
+    }
+    Err(format!("{} known incompatibilities with Hyprland {reported}", problems.len()))
+}
+
+pub fn run(action: VersionAction) -> Result<(), String> {
+    match action {
+        VersionAction::Check => check(),
+    }
+}
@@ -0,0 +1,63 @@
+//! `hyde-ipc wait`: block until a matching event arrives, for scripts that launch something and
+//! then act on its window.
+//!
+//! It is `listen` stopping after the first event that gets through the filters, with nothing
+//! printed unless asked for and a distinct exit status when the timeout runs out.
+
+use crate::failure;
+use crate::flags::OutputFormat;
+use crate::listen::{self, Extras, Output, Pacing, PayloadFilter};
+use crate::parsers::ParsedDuration;
+
+/// Exit code when no matching event came before the timeout.
+pub const TIMED_OUT: i32 = 2;
+
+/// Turn `FIELD:PATTERN` conditions into a filter on the payload.
+fn payload_filter(subtype: Option<String>, filters: &[String]) -> Result<PayloadFilter, String> {
+    let mut payload = PayloadFilter { subtype, ..PayloadFilter::default() };
+    for filter in filters {
+        let (field, pattern) = filter
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid filter: {filter}, expected FIELD:PATTERN"))?;
+        let slot = match field {
+            "class" => &mut payload.class,
+            "title" => &mut payload.title,
+            "workspace" => &mut payload.workspace,
+            _ => {
+                return Err(format!(
+                    "Unknown filter field '{field}' in {filter}, expected class, title or \
+                     workspace"
+                ));
+            },
+        };
+        if slot.is_some() {
+            return Err(format!("More than one {field} filter"));
+        }
+        *slot = Some(pattern.to_string());
+    }
+    Ok(payload)
+}
+
+/// Wait for the event, printing it in `format` if given. Exits the process when the event arrives
+/// or the timeout runs out, so it only returns on bad arguments.
+pub fn run(
+    event: Option<String>,
+    subtype: Option<String>,
+    filters: &[String],
+    timeout: Option<&str>,
+    format: Option<OutputFormat>,
+    flat: bool,
+) -> Result<(), String> {
+    let payload = payload_filter(subtype, filters)?;
+    let timeout = timeout
+        .map(str::parse::<ParsedDuration>)
+        .transpose()?
+        .map(|timeout| timeout.0);
+    let pacing = Pacing { max_events: 1, timeout, timeout_status: TIMED_OUT, ..Pacing::default() };
+    let output = format.map_or(Output::Discard, Output::Print);
+    let extras = Extras { flat, ..Extras::default() };
+    if let Err(e) = listen::listen(event, payload, None, None, pacing, extras, output) {
+        failure::listener_failed(e);
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! The class, title and workspace of every open window, for events that only carry an address.
+//!
+//! `closed` and `moved` events name their window by address alone. The reaction manager fills
+//! this from the client list when it starts and keeps it current from open, focus, title and move
+//! events, so window filters apply to those events too and `{class}` and `{title}` get filled in.
+
+use hyprland::data::Clients;
+use hyprland::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Default)]
+pub struct Window {
+    pub class: String,
+    pub title: String,
+    /// Workspace name, as `{workspace}` has it.
+    pub workspace: String,
+}
+
+#[derive(Debug, Default)]
+pub struct WindowCache {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl WindowCache {
+    /// Pick up the windows that were already open before the daemon started.
+    pub fn track_existing(&self) {
+        let Ok(clients) = Clients::get() else {
+            return;
+        };
+        let mut windows = self.windows.lock().unwrap();
+        for client in clients {
+            windows.insert(client.address.to_string(), Window {
+                class: client.class,
+                title: client.title,
+                workspace: client.workspace.name,
+            });
+        }
+    }
+
+    pub fn opened(&self, address: String, window: Window) {
+        self.windows
+            .lock()
+            .unwrap()
+            .insert(address, window);
+    }
+
+    /// Update the class and title, which focus and title events report.
+    pub fn renamed(&self, address: &str, class: Option<&str>, title: &str) {
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows
+            .entry(address.to_string())
+            .or_default();
+        if let Some(class) = class {
+            window.class = class.to_string();
+        }
+        window.title = title.to_string();
+    }
+
+    pub fn moved(&self, address: &str, workspace: String) {
+        if let Some(window) = self
+            .windows
+            .lock()
+            .unwrap()
+            .get_mut(address)
+        {
+            window.workspace = workspace;
+        }
+    }
+
+    pub fn get(&self, address: &str) -> Option<Window> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(address)
+            .cloned()
+    }
+
+    pub fn forget(&self, address: &str) {
+        self.windows
+            .lock()
+            .unwrap()
+            .remove(address);
+    }
+}
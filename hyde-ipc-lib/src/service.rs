@@ -78,6 +78,15 @@ pub fn get_config_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Directory for runtime state that should survive a daemon restart (journals and the like).
+pub fn get_state_dir() -> Result<PathBuf> {
+    let mut path = dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .ok_or_else(|| ServiceError::Config("Could not get user's state directory".to_string()))?;
+    path.push("hyde-ipc");
+    Ok(path)
+}
+
 pub fn install() -> Result<()> {
     let label = get_label();
     let manager = get_manager()?;
@@ -162,6 +171,23 @@ pub fn restart() -> Result<()> {
     start()
 }
 
+/// Ask the running service to re-read its config (`SIGHUP`).
+pub fn reload() -> Result<()> {
+    let status = Command::new("systemctl")
+        .args([
+            "--user",
+            "kill",
+            "--signal=HUP",
+            "hyde-ipc.service",
+        ])
+        .status()?;
+    if !status.success() {
+        return Err(ServiceError::Status("systemctl kill failed".to_string()));
+    }
+    println!("Service reloading.");
+    Ok(())
+}
+
 pub fn is_active() -> Result<bool> {
     // FIX: before next release:
     // This is a workaround.
@@ -41,6 +41,8 @@ pub(crate) enum DataCommands {
     Version,
     #[display("cursorpos")]
     CursorPosition,
+    #[display("submap")]
+    Submap,
     #[display("binds")]
     Binds,
     #[display("animations")]
@@ -466,6 +468,43 @@ pub struct CursorPosition {
 }
 impl_on!(CursorPosition);
 
+/// This struct holds the name of the active keybind submap,
+/// it is empty when the default submap is active
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Display)]
+pub struct Submap(
+    /// The submap name
+    pub String,
+);
+
+impl Submap {
+    // Hyprland does not emit valid JSON for this request, so it is read as plain text
+    fn from_raw(data: String) -> Self {
+        match data.trim() {
+            "default" => Self(String::new()),
+            name => Self(name.to_string()),
+        }
+    }
+}
+
+impl HyprData for Submap {
+    fn get() -> crate::Result<Self> {
+        let data = write_to_socket_sync(SocketType::Command, CommandContent {
+            flag: CommandFlag::Empty,
+            data: DataCommands::Submap.to_string(),
+        })?;
+        Ok(Self::from_raw(data))
+    }
+
+    async fn get_async() -> crate::Result<Self> {
+        let data = write_to_socket(SocketType::Command, CommandContent {
+            flag: CommandFlag::Empty,
+            data: DataCommands::Submap.to_string(),
+        })
+        .await?;
+        Ok(Self::from_raw(data))
+    }
+}
+
 /// A keybinding returned from the binds command
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Bind {
@@ -92,6 +92,31 @@ pub enum CycleDirection {
     Previous,
 }
 
+/// Which windows [DispatchType::CycleWindowIn] cycles through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CycleScope {
+    /// Only tiled windows
+    pub tiled: bool,
+    /// Only floating windows
+    pub floating: bool,
+    /// Only windows on visible workspaces
+    pub visible: bool,
+}
+
+impl std::fmt::Display for CycleScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let words: Vec<&str> = [
+            (self.tiled, "tiled"),
+            (self.floating, "floating"),
+            (self.visible, "visible"),
+        ]
+        .into_iter()
+        .filter_map(|(set, word)| set.then_some(word))
+        .collect();
+        write!(f, "{}", words.join(" "))
+    }
+}
+
 /// This enum holds a direction for switch windows in a group
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Display)]
@@ -171,9 +196,21 @@ pub enum WorkspaceIdentifierWithSpecial<'a> {
     /// The previous Workspace
     #[display("previous")]
     Previous,
+    /// The previous workspace on the current monitor
+    #[display("previous_per_monitor")]
+    PreviousPerMonitor,
     /// The first available empty workspace
     #[display("empty")]
     Empty,
+    /// The first available empty workspace on the current monitor
+    #[display("emptym")]
+    EmptyOnMonitor,
+    /// The next available empty workspace
+    #[display("emptyn")]
+    EmptyNext,
+    /// The next available empty workspace on the current monitor
+    #[display("emptynm")]
+    EmptyNextOnMonitor,
     /// The name of the workspace
     #[display("name:{_0}")]
     Name(&'a str),
@@ -306,6 +343,8 @@ pub enum DispatchType<'a> {
     MoveWindowPixel(Position, WindowIdentifier<'a>),
     /// This dispatcher cycles windows using a specified direction
     CycleWindow(CycleDirection),
+    /// This dispatcher cycles windows using a specified direction, limited to a [CycleScope]
+    CycleWindowIn(CycleDirection, CycleScope),
     /// This dispatcher swaps the focused window with the window on a workspace using a specified
     /// direction
     SwapNext(CycleDirection),
@@ -315,10 +354,12 @@ pub enum DispatchType<'a> {
     FocusWindow(WindowIdentifier<'a>),
     /// This dispatcher focuses a specified monitor
     FocusMonitor(MonitorIdentifier<'a>),
-    /// This dispatcher changed the split ratio
-    ChangeSplitRatio(f32),
+    /// This dispatcher changes the split ratio by an amount or to an exact value
+    ChangeSplitRatio(SplitRatioValue),
     /// This dispatcher toggle opacity for the current window/client
     ToggleOpaque,
+    /// This dispatcher toggles swallowing for the active window
+    ToggleSwallow,
     /// This dispatcher moves the cursor to a specified corner of a window
     MoveCursorToCorner(Corner),
     /// This dispatcher moves the cursor to a specified position
@@ -346,6 +387,14 @@ pub enum DispatchType<'a> {
     FocusUrgentOrLast,
     /// Switch focus from current to previously focused window
     FocusCurrentOrLast,
+    /// This dispatcher switches to a keybind submap, `reset` goes back to the default one
+    Submap(&'a str),
+    /// This dispatcher sets a window property (e.g. `opacity`, `rounding`), on the active window
+    /// if none is given. The last field locks the property so window rules can't override it
+    SetProp(Option<WindowIdentifier<'a>>, &'a str, &'a str, bool),
+    /// This dispatcher toggles a tag on a window (the active one if none is given), `+tag` adds
+    /// it and `-tag` removes it
+    TagWindow(&'a str, Option<WindowIdentifier<'a>>),
 
     // LAYOUT DISPATCHERS
     // DWINDLE
@@ -397,6 +446,8 @@ pub enum DispatchType<'a> {
     MoveIntoGroup(Direction),
     /// Moves the active window out of a group.
     MoveOutOfGroup,
+    /// Moves the active window back or forward inside its group
+    MoveGroupWindow(WindowSwitchDirection),
 }
 
 /// Enum used with [DispatchType::LockGroups], to determine how to lock/unlock
@@ -427,6 +478,23 @@ pub enum SwapWithMasterParam {
     Auto,
 }
 
+/// Param for [DispatchType::ChangeSplitRatio] dispatcher
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum SplitRatioValue {
+    /// Change the ratio by this amount
+    #[display("{_0}")]
+    Relative(f32),
+    /// Set the ratio to this value
+    #[display("exact {_0}")]
+    Exact(f32),
+}
+
+impl From<f32> for SplitRatioValue {
+    fn from(amount: f32) -> Self {
+        Self::Relative(amount)
+    }
+}
+
 /// Param for [DispatchType::FocusMaster] dispatcher
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
 pub enum FocusMasterParam {
@@ -487,12 +555,20 @@ pub(crate) fn gen_dispatch_str(cmd: DispatchType, dispatch: bool) -> crate::Resu
         ResizeWindowPixel(pos, win) => format!("resizewindowpixel{sep}{pos},{win}"),
         MoveWindowPixel(pos, win) => format!("movewindowpixel{sep}{pos},{win}"),
         CycleWindow(dir) => format!("cyclenext{sep}{dir}"),
+        CycleWindowIn(dir, scope) => {
+            let args: Vec<String> = [dir.to_string(), scope.to_string()]
+                .into_iter()
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            format!("cyclenext{sep}{}", args.join(" "))
+        },
         SwapNext(dir) => format!("swapnext{sep}{dir}"),
         SwapWindow(dir) => format!("swapwindow{sep}{dir}"),
         FocusWindow(win) => format!("focuswindow{sep}{win}"),
         FocusMonitor(mon) => format!("focusmonitor{sep}{mon}"),
-        ChangeSplitRatio(ratio) => format!("splitratio {ratio}"),
+        ChangeSplitRatio(value) => format!("splitratio{sep}{value}"),
         ToggleOpaque => "toggleopaque".to_string(),
+        ToggleSwallow => "toggleswallow".to_string(),
         MoveCursorToCorner(corner) => format!("movecursortocorner{sep}{}", corner.clone() as u8),
         MoveCursor(x, y) => format!("movecursor{sep}{x} {y}"),
         WorkspaceOption(opt) => format!("workspaceopt{sep}{opt}"),
@@ -510,6 +586,16 @@ pub(crate) fn gen_dispatch_str(cmd: DispatchType, dispatch: bool) -> crate::Resu
         SetCursor(theme, size) => format!("{theme} {}", *size),
         FocusUrgentOrLast => "focusurgentorlast".to_string(),
         FocusCurrentOrLast => "focuscurrentorlast".to_string(),
+        Submap(name) => format!("submap{sep}{name}"),
+        SetProp(win, prop, value, lock) => {
+            let win = win
+                .as_ref()
+                .map_or("activewindow".to_string(), ToString::to_string);
+            let lock = if *lock { " lock" } else { "" };
+            format!("setprop{sep}{win} {prop} {value}{lock}")
+        },
+        TagWindow(tag, Some(win)) => format!("tagwindow{sep}{tag} {win}"),
+        TagWindow(tag, None) => format!("tagwindow{sep}{tag}"),
         ToggleSplit => "togglesplit".to_string(),
         SwapWithMaster(param) => format!("swapwithmaster{sep}{param}"),
         FocusMaster(param) => format!("focusmaster{sep}{param}"),
@@ -527,6 +613,7 @@ pub(crate) fn gen_dispatch_str(cmd: DispatchType, dispatch: bool) -> crate::Resu
         LockGroups(how) => format!("lockgroups{sep}{how}"),
         MoveIntoGroup(dir) => format!("moveintogroup{sep}{dir}"),
         MoveOutOfGroup => "moveoutofgroup".to_string(),
+        MoveGroupWindow(dir) => format!("movegroupwindow{sep}{dir}"),
     };
 
     if let SetCursor(..) = cmd {
@@ -538,6 +625,25 @@ pub(crate) fn gen_dispatch_str(cmd: DispatchType, dispatch: bool) -> crate::Resu
     }
 }
 
+/// Joins dispatchers into a single `[[BATCH]]` request
+fn gen_batch_str(dispatch_types: Vec<DispatchType>) -> crate::Result<CommandContent> {
+    let commands = dispatch_types
+        .into_iter()
+        .map(|dispatch_type| gen_dispatch_str(dispatch_type, true).map(|command| command.data))
+        .collect::<crate::Result<Vec<_>>>()?;
+    Ok(command!(JSON, "[[BATCH]]{}", commands.join(";")))
+}
+
+/// Checks the replies to a batch, which Hyprland separates with blank lines
+fn check_batch_output(output: String) -> crate::Result<()> {
+    let failed: Vec<&str> = output
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|reply| !reply.is_empty() && *reply != "ok")
+        .collect();
+    if failed.is_empty() { Ok(()) } else { Err(HyprError::NotOkDispatch(failed.join("; "))) }
+}
+
 /// The struct that provides all dispatching methods
 pub struct Dispatch;
 
@@ -565,6 +671,51 @@ impl Dispatch {
         }
     }
 
+    /// This function calls several dispatchers in order over one socket connection (blocking)
+    ///
+    /// Hyprland splits batches on `;`, so the dispatchers' arguments must not contain one.
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> hyprland::Result<()> {
+    /// use hyprland::dispatch::{Dispatch, DispatchType};
+    /// Dispatch::call_batch(vec![
+    ///     DispatchType::Exec("kitty"),
+    ///     DispatchType::CenterWindow,
+    /// ])
+    /// # }
+    /// ```
+    pub fn call_batch(dispatch_types: Vec<DispatchType>) -> crate::Result<()> {
+        if dispatch_types.is_empty() {
+            return Ok(());
+        }
+        check_batch_output(write_to_socket_sync(
+            SocketType::Command,
+            gen_batch_str(dispatch_types)?,
+        )?)
+    }
+
+    /// This function calls several dispatchers in order over one socket connection (async)
+    pub async fn call_batch_async(dispatch_types: Vec<DispatchType<'_>>) -> crate::Result<()> {
+        if dispatch_types.is_empty() {
+            return Ok(());
+        }
+        let command = gen_batch_str(dispatch_types)?;
+        check_batch_output(write_to_socket(SocketType::Command, command).await?)
+    }
+
+    /// This function returns the exact command that [Dispatch::call] writes to the socket
+    ///
+    /// ```rust
+    /// # fn main() -> hyprland::Result<()> {
+    /// use hyprland::dispatch::{Dispatch, DispatchType};
+    /// assert_eq!(Dispatch::command_string(DispatchType::Exec("kitty"))?, "j/dispatch exec kitty");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn command_string(dispatch_type: DispatchType) -> crate::Result<String> {
+        Ok(gen_dispatch_str(dispatch_type, true)?.to_string())
+    }
+
     /// This function calls a specified dispatcher (async)
     ///
     /// ```rust
@@ -1,5 +1,28 @@
 use super::*;
+use std::cell::Cell;
 use std::io;
+use std::time::Instant;
+
+thread_local! {
+    static LAST_READ: Cell<Option<Instant>> = const { Cell::new(None) };
+    static EVENTS_HANDLED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// When [EventListener::start_listener] last read from the event socket.
+///
+/// Handlers run on the listener's thread, so calling this from inside a handler gives the time
+/// the event being handled was received.
+pub fn last_read_at() -> Option<Instant> {
+    LAST_READ.with(Cell::get)
+}
+
+/// Which event [EventListener::start_listener] is handling, counting from 1.
+///
+/// Like [last_read_at] this is per thread: it is `None` outside a listener's handlers, and every
+/// handler of one event sees the same number.
+pub fn current_event() -> Option<u64> {
+    Some(EVENTS_HANDLED.with(Cell::get)).filter(|&count| count > 0)
+}
 
 /// This struct is used for adding event handlers and executing them on events
 /// # The Event Listener
@@ -98,11 +121,13 @@ impl EventListener {
             if num_read == 0 {
                 break;
             }
+            LAST_READ.with(|last| last.set(Some(Instant::now())));
             let buf = &buf[..num_read];
             let string = String::from_utf8(buf.to_vec())?;
             let parsed: Vec<Event> = event_parser(string)?;
 
             for event in parsed {
+                EVENTS_HANDLED.with(|count| count.set(count.get() + 1));
                 self.event_primer(event, &mut active_windows)?;
             }
         }
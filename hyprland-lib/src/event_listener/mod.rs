@@ -15,7 +15,7 @@ mod shared;
 pub use crate::event_listener::shared::*;
 
 mod immutable;
-pub use crate::event_listener::immutable::EventListener;
+pub use crate::event_listener::immutable::{EventListener, current_event, last_read_at};
 
 mod async_im;
 pub use crate::event_listener::async_im::AsyncEventListener;
@@ -676,6 +676,16 @@ pub(crate) static EVENTS: phf::Map<&'static str, (usize, ParsedEventType)> = phf
     "moveoutofgroup" => (1,ParsedEventType::MoveOutOfGroup)
 };
 
+/// Whether `name` is an unparsed version of an event that is parsed, like `workspace` next to
+/// `workspacev2` or `focusedmonv2` next to `focusedmon`. Hyprland sends both versions, so a
+/// handler for unknown events would otherwise see these events a second time.
+pub fn is_twin_of_parsed_event(name: &str) -> bool {
+    EVENTS.contains_key(format!("{name}v2").as_str())
+        || name
+            .strip_suffix("v2")
+            .is_some_and(|base| EVENTS.contains_key(base))
+}
+
 use either::Either;
 
 fn new_event_parser(
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::env::{VarError, var};
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use std::{error, fmt, io};
 
 #[derive(Debug, derive_more::Display)]
@@ -252,7 +254,54 @@ pub(crate) async fn write_to_socket(
     Ok(String::from_utf8(response)?)
 }
 
+/// How long a blocking socket call may wait before it counts as a failed attempt
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often a busy socket is retried before giving up
+const SOCKET_RETRIES: usize = 3;
+
+static RETRIED_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// How many times a request to the command socket had to be retried because Hyprland was busy
+pub fn retried_writes() -> usize {
+    RETRIED_WRITES.load(Ordering::Relaxed)
+}
+
+/// Whether an io error means the socket is busy and the call is worth repeating
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
+/// Wait a little longer before each retry
+fn back_off(attempt: usize) {
+    RETRIED_WRITES.fetch_add(1, Ordering::Relaxed);
+    std::thread::sleep(Duration::from_millis(20 * attempt as u64));
+}
+
+/// Turn a socket io error into one that says what went wrong instead of only `os error 11`
+fn classify_socket_error(e: io::Error, path: &std::path::Path, stage: &str) -> HyprError {
+    let message = match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => {
+            format!("Hyprland is too busy: {stage} timed out after {SOCKET_RETRIES} retries ({e})")
+        },
+        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => {
+            format!("Can't connect to Hyprland at {}, is it running? ({e})", path.display())
+        },
+        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset => {
+            format!("Hyprland closed the connection while {stage} ({e})")
+        },
+        _ => format!("Socket error while {stage}: {e}"),
+    };
+    HyprError::IoError(io::Error::new(e.kind(), message))
+}
+
 /// This pub(crate) function is used to write a value to a socket and to get the response
+///
+/// Connecting and writing are retried a few times when the socket is busy. A request that was
+/// partly written is finished on the same connection rather than sent again, so Hyprland never
+/// sees a command twice.
 pub(crate) fn write_to_socket_sync(
     ty: SocketType,
     content: CommandContent,
@@ -261,16 +310,52 @@ pub(crate) fn write_to_socket_sync(
     use std::os::unix::net::UnixStream;
 
     let path = get_socket_path(ty)?;
-    let mut stream = UnixStream::connect(path)?;
-
-    stream.write_all(&content.as_bytes())?;
+    let mut attempt = 0;
+    let mut stream = loop {
+        match UnixStream::connect(&path) {
+            Ok(stream) => break stream,
+            Err(e) if is_transient(&e) && attempt < SOCKET_RETRIES => {
+                attempt += 1;
+                back_off(attempt);
+            },
+            Err(e) => return Err(classify_socket_error(e, &path, "connecting")),
+        }
+    };
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+
+    let bytes = content.as_bytes();
+    let mut written = 0;
+    let mut attempt = 0;
+    while written < bytes.len() {
+        match stream.write(&bytes[written..]) {
+            Ok(0) => {
+                let e = io::Error::from(io::ErrorKind::WriteZero);
+                return Err(classify_socket_error(e, &path, "sending the request"));
+            },
+            Ok(n) => written += n,
+            Err(e) if is_transient(&e) && attempt < SOCKET_RETRIES => {
+                attempt += 1;
+                back_off(attempt);
+            },
+            Err(e) => return Err(classify_socket_error(e, &path, "sending the request")),
+        }
+    }
 
     let mut response = Vec::new();
 
     const BUF_SIZE: usize = 8192;
     let mut buf = [0; BUF_SIZE];
+    let mut attempt = 0;
     loop {
-        let num_read = stream.read(&mut buf)?;
+        let num_read = match stream.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted && attempt < SOCKET_RETRIES => {
+                attempt += 1;
+                continue;
+            },
+            Err(e) => return Err(classify_socket_error(e, &path, "reading the reply")),
+        };
         let buf = &buf[..num_read];
         response.append(&mut buf.to_vec());
         if num_read == 0 || num_read != BUF_SIZE {